@@ -17,6 +17,8 @@ use serde_json::{Map, Value};
 #[derive(Clone, Deserialize)]
 struct Environment {
     authn_url: String,
+    /// Hex-encoded, as Mist's API hands it out.
+    api_key: String,
 }
 
 #[tokio::main]
@@ -36,14 +38,22 @@ async fn main() -> Result<()> {
 }
 
 async fn root(jar: CookieJar, State(env): State<Environment>) -> Result<Markup> {
-    let Some(cookie) = jar.get("mist") else {
+    // Mist sets a cookie per service, so pass them all along and let it pick ours.
+    let cookies = jar
+        .iter()
+        .filter(|cookie| cookie.name().starts_with("mist-"))
+        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    if cookies.is_empty() {
         return Ok(login(&env));
-    };
+    }
 
     // Check if the user is still logged in.
     let response = Client::new()
-        .get(format!("{}/whoami", env.authn_url))
-        .header("Cookie", format!("mist={}", cookie.value()))
+        .get(format!("{}/ACME/whoami", env.authn_url))
+        .header("Cookie", &cookies)
         .send()
         .await?;
 
@@ -68,15 +78,19 @@ async fn root(jar: CookieJar, State(env): State<Environment>) -> Result<Markup>
         script { "hljs.highlightAll();" }
 
         div class="mb-4 text-xs w-1/3 mockup-code" style="background-color: #1e1e2e;" {
-            pre class="language-none mb-2" data-prefix="$" { code { "curl -X GET " (env.authn_url) "/whoami -H 'Cookie: mist=" (cookie.value()) "' | jq" } }
+            pre class="language-none mb-2" data-prefix="$" { code { "curl -X GET " (env.authn_url) "/ACME/whoami -H 'Cookie: " (cookies) "' | jq" } }
 
             div class="mx-2 language-json catppuccin-mocha" {
                 (PreEscaped(json))
             }
         }
 
-        form method="POST" action={ (env.authn_url) "/ACME/out" } {
-            button class="btn btn-outline btn-neutral" type="submit" { "👋 Sign out" }
+        div class="join" {
+            a class="btn join-item btn-outline btn-neutral" href={ (env.authn_url) "/ACME/link" } { "🔗 Link another wallet" }
+
+            form method="POST" action={ (env.authn_url) "/ACME/out" } {
+                button class="btn join-item btn-outline btn-neutral" type="submit" { "👋 Sign out" }
+            }
         }
     }))
 }
@@ -110,10 +124,17 @@ fn login(env: &Environment) -> Markup {
 async fn hook(State(env): State<Environment>, Json(body): Json<String>) -> Result<StatusCode> {
     let body = serde_json::from_str::<Map<String, Value>>(&body)?;
 
-    // Validate the request, create a user, etc.
+    // Only registrations need an answer.
+    if body["meta"]["kind"] != "registration" {
+        return Ok(StatusCode::OK);
+    }
+
+    // Validate the request, create a user, etc. To turn someone away instead, send a
+    // `rejection` with a `code` and a `message` for the user.
 
     Client::new()
         .post(format!("{}/complete", env.authn_url))
+        .header("Authorization", &env.api_key)
         .json(&serde_json::json!({ "session_id": body["data"]["session_id"] }))
         .send()
         .await?;
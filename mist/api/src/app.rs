@@ -1,14 +1,27 @@
 use std::sync::Arc;
 
 use axum::{middleware, Router};
-use mist_common::env::Environment;
-use mist_db::repos::{keys::PgKeyRepo, services::PgServiceRepo};
+use fred::{
+    prelude::{ClientLike, RedisClient},
+    types::RedisConfig,
+};
+use mist_common::{env::Environment, Result};
+use mist_db::repos::{
+    credential_configurations::PgCredentialConfigurationRepo,
+    credential_offers::PgCredentialOfferRepo, identifiers::PgIdentifierRepo, keys::PgKeyRepo,
+    recovery_methods::PgRecoveryMethodRepo, redirect_uris::PgRedirectUriRepo,
+    services::PgServiceRepo, sessions::RedisSessionRepo, trusted_issuers::PgTrustedIssuerRepo,
+};
+use mist_jobs::jobs::JetStreamWebhookQueue;
 use sqlx::postgres::PgPoolOptions;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
-    handlers::{keys, services},
+    handlers::{
+        configurations, identifiers, issuers, keys, offers, recovery, redirect_uris, services,
+        sessions,
+    },
     middleware::auth,
     state::{ApiState, Repos},
 };
@@ -16,8 +29,8 @@ use crate::{
 #[derive(OpenApi)]
 #[openapi(
     info(version = "latest", title = "Mist", license(name = "Apache 2.0", url = "http://www.apache.org/licenses/LICENSE-2.0")),
-    nest((path = "/services", api = services::Api), (path = "/services/{service_id}/keys", api = keys::Api)),
-    tags((name = "Services"), (name = "Keys"))
+    nest((path = "/services", api = services::Api), (path = "/services/{service_id}/keys", api = keys::Api), (path = "/issuers", api = issuers::Api), (path = "/services/{service_id}/issuers", api = issuers::Api), (path = "/services/{service_id}/configurations", api = configurations::Api), (path = "/services/{service_id}/offers", api = offers::Api), (path = "/services/{service_id}/users/{user_id}/identifiers", api = identifiers::Api), (path = "/services/{service_id}/users/{user_id}/recovery", api = recovery::Api), (path = "/services/{service_id}", api = sessions::Api), (path = "/services/{service_id}/redirect-uris", api = redirect_uris::Api)),
+    tags((name = "Services"), (name = "Keys"), (name = "Issuers"), (name = "Credentials"), (name = "Users"))
 )]
 struct Api;
 
@@ -28,19 +41,37 @@ pub async fn app(env: Environment) -> Router {
         .await
         .unwrap();
 
+    let redis = create_redis_client(&env).await.unwrap();
+    let nats = async_nats::connect(env.nats_url.clone()).await.unwrap();
+
     let repos = Repos {
         services: Arc::new(PgServiceRepo::new(postgres.clone())),
         keys: Arc::new(PgKeyRepo::new(postgres.clone())),
+        trusted_issuers: Arc::new(PgTrustedIssuerRepo::new(postgres.clone())),
+        credential_configurations: Arc::new(PgCredentialConfigurationRepo::new(postgres.clone())),
+        credential_offers: Arc::new(PgCredentialOfferRepo::new(postgres.clone())),
+        identifiers: Arc::new(PgIdentifierRepo::new(postgres.clone())),
+        recovery_methods: Arc::new(PgRecoveryMethodRepo::new(postgres.clone())),
+        sessions: Arc::new(RedisSessionRepo::new(redis)),
+        redirect_uris: Arc::new(PgRedirectUriRepo::new(postgres.clone())),
     };
 
     let state = ApiState {
         env: env.clone(),
         repos,
+        webhooks: Arc::new(JetStreamWebhookQueue::new(async_nats::jetstream::new(nats))),
     };
 
     let mut app = Router::new()
         .nest("", services::router())
         .nest("", keys::router())
+        .nest("", issuers::router())
+        .nest("", configurations::router())
+        .nest("", offers::router())
+        .nest("", identifiers::router())
+        .nest("", recovery::router())
+        .nest("", sessions::router())
+        .nest("", redirect_uris::router())
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth::middleware,
@@ -53,3 +84,12 @@ pub async fn app(env: Environment) -> Router {
 
     app
 }
+
+async fn create_redis_client(env: &Environment) -> Result<RedisClient> {
+    let config = RedisConfig::from_url(&env.redis_url)?;
+    let client = RedisClient::new(config, None, None, None);
+
+    client.init().await?;
+
+    Ok(client)
+}
@@ -1,2 +1,9 @@
+pub(crate) mod configurations;
+pub(crate) mod identifiers;
+pub(crate) mod issuers;
 pub(crate) mod keys;
+pub(crate) mod offers;
+pub(crate) mod recovery;
+pub(crate) mod redirect_uris;
 pub(crate) mod services;
+pub(crate) mod sessions;
@@ -0,0 +1,49 @@
+mod create;
+mod destroy;
+mod get;
+mod list;
+
+use axum::{routing, Router};
+use mist_db::models::credential_configuration::{
+    CredentialClaim, CredentialConfiguration, CredentialConfigurationId,
+};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list::list_handler,
+        create::create_handler,
+        get::get_handler,
+        destroy::destroy_handler
+    ),
+    components(schemas(
+        CredentialConfigurationId,
+        CredentialConfiguration,
+        CredentialClaim,
+        create::Payload
+    ))
+)]
+pub(crate) struct Api;
+
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route(
+            "/services/:service_id/configurations",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/configurations",
+            routing::post(create::create_handler),
+        )
+        .route(
+            "/services/:service_id/configurations/:id",
+            routing::get(get::get_handler),
+        )
+        .route(
+            "/services/:service_id/configurations/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
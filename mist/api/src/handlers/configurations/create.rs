@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_garde::WithValidation;
+use garde::Validate;
+use mist_common::Result;
+use mist_db::models::{
+    credential_configuration::{CreateCredentialConfiguration, CredentialClaim},
+    service::ServiceId,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+#[schema(as = CreateCredentialConfigurationPayload)]
+pub(crate) struct Payload {
+    /// The credential type, e.g. `AcmeMembership`.
+    #[garde(ascii, alphanumeric, length(min = 3, max = 50))]
+    name: String,
+    #[garde(length(min = 1, max = 100))]
+    display_name: String,
+    #[garde(skip)]
+    claims: Vec<CredentialClaim>,
+    /// How many seconds issued credentials are valid for, if they expire.
+    #[garde(range(min = 1))]
+    valid_for: Option<i32>,
+}
+
+#[utoipa::path(
+    tags = ["Credentials"],
+    summary = "Create credential configuration",
+    post,
+    path = "",
+    params(PathParams),
+    request_body = CreateCredentialConfigurationPayload,
+    responses(
+        (status = 201, body = CredentialConfiguration)
+    )
+)]
+pub(crate) async fn create_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    WithValidation(payload): WithValidation<Json<Payload>>,
+) -> Result<impl IntoResponse> {
+    let configuration = state
+        .repos
+        .credential_configurations
+        .create(
+            &CreateCredentialConfiguration::builder()
+                .name(&payload.name)
+                .display_name(&payload.display_name)
+                .claims(payload.claims.clone())
+                .maybe_valid_for(payload.valid_for)
+                .service_id(path.service_id)
+                .build(),
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(configuration)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::credential_configuration::CredentialConfiguration,
+        repos::credential_configurations::MockCredentialConfigurationRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use crate::{handlers::configurations::router, state::Repos};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn creates() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        let mut credential_configurations = MockCredentialConfigurationRepo::new();
+
+        credential_configurations
+            .expect_create()
+            .with(eq(CreateCredentialConfiguration::builder()
+                .name("AcmeMembership")
+                .display_name("Member of ACME")
+                .claims(vec![CredentialClaim {
+                    name: "member_since".into(),
+                    display_name: None,
+                    required: true,
+                }])
+                .service_id(service_id)
+                .build()))
+            .once()
+            .returning(|_| Box::pin(ready(Ok(CredentialConfiguration::default()))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                credential_configurations: Arc::new(credential_configurations),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/services/{service_id}/configurations"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        r#"
+                        {
+                            "name": "AcmeMembership",
+                            "display_name": "Member of ACME",
+                            "claims": [{ "name": "member_since", "required": true }]
+                        }
+                    "#,
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{credential_configuration::CredentialConfigurationId, service::ServiceId};
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    id: CredentialConfigurationId,
+}
+
+#[utoipa::path(
+    tags = ["Credentials"],
+    summary = "Delete credential configuration",
+    delete,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = CredentialConfiguration),
+        (status = 404)
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let configuration = state.repos.credential_configurations.get(&path.id).await?;
+
+    if configuration.service_id != path.service_id.0 {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let configuration = state
+        .repos
+        .credential_configurations
+        .destroy(&path.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(configuration)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::credential_configuration::CredentialConfiguration,
+        repos::credential_configurations::MockCredentialConfigurationRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::configurations::router, state::Repos};
+
+    #[tokio::test]
+    async fn destroys() -> Result<()> {
+        let service_id = ServiceId::new();
+        let id = CredentialConfigurationId::new();
+
+        let mut credential_configurations = MockCredentialConfigurationRepo::new();
+
+        credential_configurations
+            .expect_get()
+            .with(eq(id))
+            .once()
+            .returning(move |_| {
+                Box::pin(ready(Ok(CredentialConfiguration {
+                    service_id: service_id.0,
+                    ..Default::default()
+                })))
+            });
+
+        credential_configurations
+            .expect_destroy()
+            .with(eq(id))
+            .once()
+            .returning(|_| Box::pin(ready(Ok(CredentialConfiguration::default()))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                credential_configurations: Arc::new(credential_configurations),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri(format!("/services/{service_id}/configurations/{id}"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+}
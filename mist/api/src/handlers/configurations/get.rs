@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{credential_configuration::CredentialConfigurationId, service::ServiceId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    id: CredentialConfigurationId,
+}
+
+#[utoipa::path(
+    tags = ["Credentials"],
+    summary = "Get credential configuration",
+    get,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = CredentialConfiguration),
+        (status = 404)
+    )
+)]
+pub(crate) async fn get_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let configuration = state.repos.credential_configurations.get(&path.id).await?;
+
+    if configuration.service_id != path.service_id.0 {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    Ok(Json(configuration).into_response())
+}
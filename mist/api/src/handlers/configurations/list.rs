@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct QueryParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[utoipa::path(
+    tags = ["Credentials"],
+    summary = "List credential configurations",
+    get,
+    path = "",
+    params(PathParams, QueryParams),
+    responses(
+        (status = 200, body = Vec<CredentialConfiguration>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    Query(query): Query<QueryParams>,
+) -> Result<impl IntoResponse> {
+    let limit = query.limit.unwrap_or(10);
+    let offset = (query.page.unwrap_or(1) - 1) * limit;
+
+    let configurations = state
+        .repos
+        .credential_configurations
+        .list(&path.service_id, limit as i64, offset as i64)
+        .await?;
+
+    Ok(Json(configurations))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::credential_configuration::CredentialConfiguration,
+        repos::credential_configurations::MockCredentialConfigurationRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::configurations::router, state::Repos};
+
+    #[tokio::test]
+    async fn lists() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        let mut credential_configurations = MockCredentialConfigurationRepo::new();
+
+        credential_configurations
+            .expect_list()
+            .with(eq(service_id), eq(10), eq(0))
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(vec![CredentialConfiguration::default()]))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                credential_configurations: Arc::new(credential_configurations),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/services/{service_id}/configurations"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+mod destroy;
+mod list;
+
+use axum::{routing, Router};
+use mist_common::Result;
+use mist_db::models::{
+    identifier::{Identifier, IdentifierId},
+    service::Service,
+};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list::list_handler, destroy::destroy_handler),
+    components(schemas(IdentifierId, Identifier))
+)]
+pub(crate) struct Api;
+
+/// The DIDs a user can sign in with. Users can link more than one wallet, but always
+/// keep at least one.
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route(
+            "/services/:service_id/users/:user_id/identifiers",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/identifiers/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
+
+/// Services only ever see the DID as the subject they know the user by, which under
+/// pairwise subjects is nothing like the DID.
+fn for_service(service: &Service, identifier: Identifier) -> Result<Identifier> {
+    Ok(Identifier {
+        value: service.subject(&identifier.value)?,
+        ..identifier
+    })
+}
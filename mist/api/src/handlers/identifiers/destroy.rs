@@ -0,0 +1,143 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{identifier::IdentifierId, service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{handlers::identifiers::for_service, state::ApiState};
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+    id: IdentifierId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "Delete identifier",
+    delete,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = Identifier),
+        (status = 404),
+        (status = 409, description = "The user's last identifier can't be removed")
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    let identifier = state
+        .repos
+        .identifiers
+        .destroy(&path.service_id, &path.user_id, &path.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(for_service(&service, identifier)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::{env::Environment, error::Conflict};
+    use mist_db::{
+        models::{identifier::Identifier, service::Service},
+        repos::{identifiers::MockIdentifierRepo, services::MockServiceRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::identifiers::router, state::Repos};
+
+    fn app(identifiers: MockIdentifierRepo) -> axum::Router {
+        let mut services = MockServiceRepo::new();
+
+        services
+            .expect_get()
+            .returning(|_| Box::pin(ready(Ok(Service::default()))));
+
+        router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                identifiers: Arc::new(identifiers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        })
+    }
+
+    fn request(service_id: ServiceId, user_id: UserId, id: IdentifierId) -> Result<Request<Body>> {
+        Ok(Request::builder()
+            .method(http::Method::DELETE)
+            .uri(format!(
+                "/services/{service_id}/users/{user_id}/identifiers/{id}"
+            ))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(()))?)
+    }
+
+    #[tokio::test]
+    async fn destroys() -> Result<()> {
+        let service_id = ServiceId::new();
+        let user_id = UserId::new();
+        let id = IdentifierId::new();
+
+        let mut identifiers = MockIdentifierRepo::new();
+
+        identifiers
+            .expect_destroy()
+            .with(eq(service_id), eq(user_id), eq(id))
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(Identifier::default()))));
+
+        let response = app(identifiers)
+            .oneshot(request(service_id, user_id, id)?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keeps_the_last_identifier() -> Result<()> {
+        let mut identifiers = MockIdentifierRepo::new();
+
+        identifiers.expect_destroy().once().returning(|_, _, _| {
+            Box::pin(ready(Err(Conflict(
+                "can't remove a user's last identifier",
+            )
+            .into())))
+        });
+
+        let response = app(identifiers)
+            .oneshot(request(
+                ServiceId::new(),
+                UserId::new(),
+                IdentifierId::new(),
+            )?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        Ok(())
+    }
+}
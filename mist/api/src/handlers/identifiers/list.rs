@@ -0,0 +1,145 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{handlers::identifiers::for_service, state::ApiState};
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "List identifiers",
+    get,
+    path = "",
+    params(PathParams),
+    responses(
+        (status = 200, body = Vec<Identifier>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    let identifiers = state
+        .repos
+        .identifiers
+        .list(&path.service_id, &path.user_id)
+        .await?
+        .into_iter()
+        .map(|identifier| for_service(&service, identifier))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Json(identifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::{
+            identifier::Identifier,
+            service::{Service, SubjectType},
+        },
+        repos::{identifiers::MockIdentifierRepo, services::MockServiceRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::identifiers::router, state::Repos};
+
+    const DID: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    async fn list(subject_type: SubjectType) -> Result<Vec<Identifier>> {
+        let service_id = ServiceId::new();
+        let user_id = UserId::new();
+
+        let mut services = MockServiceRepo::new();
+
+        services
+            .expect_get()
+            .with(eq(service_id))
+            .once()
+            .returning(move |_| {
+                Box::pin(ready(Ok(Service {
+                    subject_type: subject_type.clone(),
+                    ..Default::default()
+                })))
+            });
+
+        let mut identifiers = MockIdentifierRepo::new();
+
+        identifiers
+            .expect_list()
+            .with(eq(service_id), eq(user_id))
+            .once()
+            .returning(|_, _| {
+                Box::pin(ready(Ok(vec![Identifier {
+                    value: DID.into(),
+                    ..Default::default()
+                }])))
+            });
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                identifiers: Arc::new(identifiers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!(
+                        "/services/{service_id}/users/{user_id}/identifiers"
+                    ))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    #[tokio::test]
+    async fn lists() -> Result<()> {
+        assert_eq!(list(SubjectType::Public).await?[0].value, DID);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hides_dids_behind_pairwise_subjects() -> Result<()> {
+        assert_ne!(list(SubjectType::Pairwise).await?[0].value, DID);
+
+        Ok(())
+    }
+}
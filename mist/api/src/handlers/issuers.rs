@@ -0,0 +1,38 @@
+mod create;
+mod destroy;
+mod list;
+
+use axum::{routing, Router};
+use mist_db::models::trusted_issuer::{IssuerKind, TrustedIssuer, TrustedIssuerId};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list::list_handler, create::create_handler, destroy::destroy_handler),
+    components(schemas(TrustedIssuerId, TrustedIssuer, IssuerKind, create::Payload))
+)]
+pub(crate) struct Api;
+
+/// Issuers at `/issuers` apply to every service and can only be managed with the
+/// master key, while those at `/services/:service_id/issuers` only apply to that
+/// service.
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route("/issuers", routing::get(list::list_handler))
+        .route("/issuers", routing::post(create::create_handler))
+        .route("/issuers/:id", routing::delete(destroy::destroy_handler))
+        .route(
+            "/services/:service_id/issuers",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/issuers",
+            routing::post(create::create_handler),
+        )
+        .route(
+            "/services/:service_id/issuers/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
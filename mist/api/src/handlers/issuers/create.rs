@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_garde::WithValidation;
+use garde::Validate;
+use mist_common::Result;
+use mist_db::models::{
+    definition::DefinitionId,
+    service::ServiceId,
+    trusted_issuer::{CreateTrustedIssuer, IssuerKind},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: Option<ServiceId>,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+#[schema(as = CreateTrustedIssuerPayload)]
+pub(crate) struct Payload {
+    #[garde(skip)]
+    kind: IssuerKind,
+    /// A DID, DID prefix, or PEM-encoded trust anchor certificate, depending on the kind.
+    #[garde(length(min = 1))]
+    value: String,
+    /// Only trust the issuer for credentials presented for this definition.
+    #[garde(skip)]
+    definition_id: Option<Uuid>,
+}
+
+#[utoipa::path(
+    tags = ["Issuers"],
+    summary = "Create trusted issuer",
+    post,
+    path = "",
+    params(PathParams),
+    request_body = CreateTrustedIssuerPayload,
+    responses(
+        (status = 201, body = TrustedIssuer),
+        (status = 400)
+    )
+)]
+pub(crate) async fn create_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    WithValidation(payload): WithValidation<Json<Payload>>,
+) -> Result<impl IntoResponse> {
+    // Definitions belong to a service, so global issuers can't be scoped to one.
+    if path.service_id.is_none() && payload.definition_id.is_some() {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let issuer = state
+        .repos
+        .trusted_issuers
+        .create(
+            &CreateTrustedIssuer::builder()
+                .kind(payload.kind.clone())
+                .value(payload.value.trim())
+                .maybe_service_id(path.service_id)
+                .maybe_definition_id(payload.definition_id.map(DefinitionId))
+                .build(),
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(issuer)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::trusted_issuer::TrustedIssuer, repos::trusted_issuers::MockTrustedIssuerRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use crate::{handlers::issuers::router, state::Repos};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn creates() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        let mut trusted_issuers = MockTrustedIssuerRepo::new();
+
+        trusted_issuers
+            .expect_create()
+            .with(eq(CreateTrustedIssuer::builder()
+                .kind(IssuerKind::DidPrefix)
+                .value("did:web:example.com")
+                .service_id(service_id)
+                .build()))
+            .once()
+            .returning(|_| Box::pin(ready(Ok(TrustedIssuer::default()))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                trusted_issuers: Arc::new(trusted_issuers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/services/{service_id}/issuers"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        r#"
+                        {
+                            "kind": "did_prefix",
+                            "value": "did:web:example.com"
+                        }
+                    "#,
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_global_definition() -> Result<()> {
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos::mocked(),
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/issuers")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(format!(
+                        r#"
+                        {{
+                            "kind": "did",
+                            "value": "did:web:example.com",
+                            "definition_id": "{}"
+                        }}
+                    "#,
+                        Uuid::new_v4()
+                    )))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, trusted_issuer::TrustedIssuerId};
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: Option<ServiceId>,
+    id: TrustedIssuerId,
+}
+
+#[utoipa::path(
+    tags = ["Issuers"],
+    summary = "Delete trusted issuer",
+    delete,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = TrustedIssuer),
+        (status = 404)
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let issuer = state.repos.trusted_issuers.get(&path.id).await?;
+
+    // Don't let a service remove another service's (or a global) issuer.
+    if issuer.service_id != path.service_id.map(|id| id.0) {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let issuer = state.repos.trusted_issuers.destroy(&path.id).await?;
+
+    Ok((StatusCode::OK, Json(issuer)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::trusted_issuer::TrustedIssuer, repos::trusted_issuers::MockTrustedIssuerRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::issuers::router, state::Repos};
+
+    async fn destroy(service_id: ServiceId, owner: Option<ServiceId>) -> Result<StatusCode> {
+        let id = TrustedIssuerId::new();
+
+        let mut trusted_issuers = MockTrustedIssuerRepo::new();
+
+        trusted_issuers
+            .expect_get()
+            .with(eq(id))
+            .once()
+            .returning(move |_| {
+                Box::pin(ready(Ok(TrustedIssuer {
+                    service_id: owner.map(|id| id.0),
+                    ..Default::default()
+                })))
+            });
+
+        trusted_issuers
+            .expect_destroy()
+            .with(eq(id))
+            .returning(|_| Box::pin(ready(Ok(TrustedIssuer::default()))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                trusted_issuers: Arc::new(trusted_issuers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri(format!("/services/{service_id}/issuers/{id}"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn destroys() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        assert_eq!(destroy(service_id, Some(service_id)).await?, StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn hides_other_services_issuers() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        assert_eq!(destroy(service_id, None).await?, StatusCode::NOT_FOUND);
+        assert_eq!(
+            destroy(service_id, Some(ServiceId::new())).await?,
+            StatusCode::NOT_FOUND
+        );
+
+        Ok(())
+    }
+}
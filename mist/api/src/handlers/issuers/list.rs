@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use serde::{Deserialize, Serialize};
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: Option<ServiceId>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct QueryParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[utoipa::path(
+    tags = ["Issuers"],
+    summary = "List trusted issuers",
+    get,
+    path = "",
+    params(PathParams, QueryParams),
+    responses(
+        (status = 200, body = Vec<TrustedIssuer>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    Query(query): Query<QueryParams>,
+) -> Result<impl IntoResponse> {
+    let limit = query.limit.unwrap_or(10);
+    let offset = (query.page.unwrap_or(1) - 1) * limit;
+
+    let issuers = state
+        .repos
+        .trusted_issuers
+        .list(path.service_id, limit as i64, offset as i64)
+        .await?;
+
+    Ok(Json(issuers))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::trusted_issuer::TrustedIssuer, repos::trusted_issuers::MockTrustedIssuerRepo,
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::issuers::router, state::Repos};
+
+    #[tokio::test]
+    async fn lists() -> Result<()> {
+        let service_id = ServiceId::new();
+
+        let mut trusted_issuers = MockTrustedIssuerRepo::new();
+
+        trusted_issuers
+            .expect_list()
+            .with(eq(Some(service_id)), eq(10), eq(0))
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(vec![TrustedIssuer::default()]))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                trusted_issuers: Arc::new(trusted_issuers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/services/{service_id}/issuers"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lists_global() -> Result<()> {
+        let mut trusted_issuers = MockTrustedIssuerRepo::new();
+
+        trusted_issuers
+            .expect_list()
+            .with(eq(None), eq(10), eq(0))
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(vec![TrustedIssuer::default()]))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                trusted_issuers: Arc::new(trusted_issuers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri("/issuers")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+}
@@ -65,10 +65,8 @@ mod tests {
 
     use axum::{body::Body, extract::Request, http};
     use mist_common::env::Environment;
-    use mist_db::{
-        models::key::Key,
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
-    };
+    use mist_db::{models::key::Key, repos::keys::MockKeyRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use secstr::SecVec;
     use tower::ServiceExt;
@@ -103,9 +101,10 @@ mod tests {
                 ..Default::default()
             },
             repos: Repos {
-                services: Arc::new(MockServiceRepo::new()),
                 keys: Arc::new(keys),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
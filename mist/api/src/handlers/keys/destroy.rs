@@ -47,8 +47,9 @@ mod tests {
     use mist_common::env::Environment;
     use mist_db::{
         models::{key::Key, service::ServiceId},
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
+        repos::keys::MockKeyRepo,
     };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use tower::ServiceExt;
 
@@ -71,9 +72,10 @@ mod tests {
         let app = router().with_state(ApiState {
             env: Environment::default(),
             repos: Repos {
-                services: Arc::new(MockServiceRepo::new()),
                 keys: Arc::new(keys),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
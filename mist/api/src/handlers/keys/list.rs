@@ -59,10 +59,8 @@ mod tests {
         http::{self, Request, StatusCode},
     };
     use mist_common::env::Environment;
-    use mist_db::{
-        models::key::Key,
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
-    };
+    use mist_db::{models::key::Key, repos::keys::MockKeyRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use tower::ServiceExt;
 
     use super::*;
@@ -82,9 +80,10 @@ mod tests {
         let app = router().with_state(ApiState {
             env: Environment::default(),
             repos: Repos {
-                services: Arc::new(MockServiceRepo::new()),
                 keys: Arc::new(keys),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
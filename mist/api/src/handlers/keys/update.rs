@@ -68,8 +68,9 @@ mod tests {
             key::{Key, UpdateKey},
             service::ServiceId,
         },
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
+        repos::keys::MockKeyRepo,
     };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use tower::ServiceExt;
 
@@ -92,9 +93,10 @@ mod tests {
         let app = router().with_state(ApiState {
             env: Environment::default(),
             repos: Repos {
-                services: Arc::new(MockServiceRepo::new()),
                 keys: Arc::new(keys),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
@@ -50,8 +50,9 @@ mod tests {
     };
     use mist_db::{
         models::{key::Key, service::ServiceId},
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
+        repos::keys::MockKeyRepo,
     };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use secstr::SecVec;
     use tower::ServiceExt;
@@ -83,9 +84,10 @@ mod tests {
                 ..Default::default()
             },
             repos: Repos {
-                services: Arc::new(MockServiceRepo::new()),
                 keys: Arc::new(keys),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
@@ -0,0 +1,21 @@
+mod create;
+
+use axum::{routing, Router};
+use mist_db::models::credential_offer::{CredentialOfferId, OfferGrant};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(create::create_handler),
+    components(schemas(CredentialOfferId, OfferGrant, create::Payload, create::Response))
+)]
+pub(crate) struct Api;
+
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new().route(
+        "/services/:service_id/offers",
+        routing::post(create::create_handler),
+    )
+}
@@ -0,0 +1,280 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_garde::WithValidation;
+use chrono::{DateTime, Duration, Utc};
+use garde::Validate;
+use mist_common::Result;
+use mist_db::models::{
+    credential_configuration::CredentialConfigurationId,
+    credential_offer::{CreateCredentialOffer, CredentialOfferId, OfferGrant},
+    service::ServiceId,
+    user::UserId,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+#[schema(as = CreateCredentialOfferPayload)]
+pub(crate) struct Payload {
+    #[garde(skip)]
+    configuration_id: CredentialConfigurationId,
+    #[garde(skip)]
+    #[serde(default)]
+    grant_type: OfferGrant,
+    /// The user the credential is for. Required for the authorization code grant.
+    #[garde(skip)]
+    user_id: Option<Uuid>,
+    #[garde(skip)]
+    #[schema(value_type = Object)]
+    claims: Map<String, Value>,
+    /// How many seconds the offer can be redeemed for.
+    #[garde(range(min = 60, max = 60 * 60 * 24 * 7))]
+    #[serde(default = "default_expires_in")]
+    expires_in: i64,
+}
+
+fn default_expires_in() -> i64 {
+    60 * 10
+}
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = CreateCredentialOfferResponse)]
+pub(crate) struct Response {
+    id: CredentialOfferId,
+    /// A page showing the offer as a QR code, to send the user to.
+    offer_url: String,
+    /// For wallets, if you'd rather show the offer yourself.
+    credential_offer_uri: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[utoipa::path(
+    tags = ["Credentials"],
+    summary = "Create credential offer",
+    post,
+    path = "",
+    params(PathParams),
+    request_body = CreateCredentialOfferPayload,
+    responses(
+        (status = 201, body = CreateCredentialOfferResponse),
+        (status = 400),
+        (status = 404)
+    )
+)]
+pub(crate) async fn create_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    WithValidation(payload): WithValidation<Json<Payload>>,
+) -> Result<impl IntoResponse> {
+    let configuration = state
+        .repos
+        .credential_configurations
+        .get(&payload.configuration_id)
+        .await?;
+
+    if configuration.service_id != path.service_id.0 {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    // Make sure the claims match the configuration.
+    // ---------------------------------------------
+
+    let is_missing_claims = configuration
+        .claims
+        .iter()
+        .any(|claim| claim.required && !payload.claims.contains_key(&claim.name));
+
+    let has_unknown_claims = payload
+        .claims
+        .keys()
+        .any(|name| !configuration.claims.iter().any(|claim| &claim.name == name));
+
+    let is_missing_user =
+        payload.grant_type == OfferGrant::AuthorizationCode && payload.user_id.is_none();
+
+    if is_missing_claims || has_unknown_claims || is_missing_user {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    // Create the offer.
+    // -----------------
+
+    let pre_authorized_code = match payload.grant_type {
+        OfferGrant::PreAuthorizedCode => Some(Uuid::new_v4().simple().to_string()),
+        OfferGrant::AuthorizationCode => None,
+    };
+
+    let offer = state
+        .repos
+        .credential_offers
+        .create(
+            &CreateCredentialOffer::builder()
+                .grant_type(payload.grant_type.clone())
+                .claims(payload.claims.clone())
+                .maybe_pre_authorized_code(pre_authorized_code)
+                .expires_at(Utc::now() + Duration::seconds(payload.expires_in))
+                .configuration_id(payload.configuration_id)
+                .maybe_user_id(payload.user_id.map(UserId))
+                .build(),
+        )
+        .await?;
+
+    let offer_url = format!(
+        "{}/issuers/{}/offers/{}",
+        state.env.authn_url, path.service_id, offer.id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Response {
+            id: offer.id,
+            credential_offer_uri: format!("{offer_url}/offer"),
+            offer_url,
+            expires_at: offer.expires_at,
+        }),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::{
+            credential_configuration::{CredentialClaim, CredentialConfiguration},
+            credential_offer::CredentialOffer,
+        },
+        repos::{
+            credential_configurations::MockCredentialConfigurationRepo,
+            credential_offers::MockCredentialOfferRepo,
+        },
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use sqlx::types::Json as SqlJson;
+    use tower::ServiceExt;
+
+    use crate::{handlers::offers::router, state::Repos};
+
+    use super::*;
+
+    async fn create(body: &'static str, expected_offers: usize) -> Result<StatusCode> {
+        let service_id = ServiceId::new();
+
+        let mut credential_configurations = MockCredentialConfigurationRepo::new();
+
+        credential_configurations
+            .expect_get()
+            .once()
+            .returning(move |_| {
+                Box::pin(ready(Ok(CredentialConfiguration {
+                    claims: SqlJson(vec![CredentialClaim {
+                        name: "member_since".into(),
+                        display_name: None,
+                        required: true,
+                    }]),
+                    service_id: service_id.0,
+                    ..Default::default()
+                })))
+            });
+
+        let mut credential_offers = MockCredentialOfferRepo::new();
+
+        credential_offers
+            .expect_create()
+            .with(function(|data: &CreateCredentialOffer| {
+                data.pre_authorized_code.is_some() && data.claims.contains_key("member_since")
+            }))
+            .times(expected_offers)
+            .returning(|_| Box::pin(ready(Ok(CredentialOffer::default()))));
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                credential_configurations: Arc::new(credential_configurations),
+                credential_offers: Arc::new(credential_offers),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/services/{service_id}/offers"))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.replace(
+                        "$configuration_id",
+                        &CredentialConfigurationId::new().to_string(),
+                    )))?,
+            )
+            .await?;
+
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn creates() -> Result<()> {
+        let status = create(
+            r#"
+            {
+                "configuration_id": "$configuration_id",
+                "claims": { "member_since": "2024" }
+            }
+        "#,
+            1,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::CREATED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatched_claims() -> Result<()> {
+        let missing = create(
+            r#"
+            {
+                "configuration_id": "$configuration_id",
+                "claims": {}
+            }
+        "#,
+            0,
+        )
+        .await?;
+
+        let unknown = create(
+            r#"
+            {
+                "configuration_id": "$configuration_id",
+                "claims": { "member_since": "2024", "is_admin": true }
+            }
+        "#,
+            0,
+        )
+        .await?;
+
+        assert_eq!(missing, StatusCode::BAD_REQUEST);
+        assert_eq!(unknown, StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+mod create;
+mod destroy;
+mod list;
+
+use axum::{routing, Router};
+use mist_db::models::recovery_method::{RecoveryKind, RecoveryMethod, RecoveryMethodId};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list::list_handler, create::create_handler, destroy::destroy_handler),
+    components(schemas(
+        RecoveryMethodId,
+        RecoveryMethod,
+        RecoveryKind,
+        create::Payload,
+        create::Response
+    ))
+)]
+pub(crate) struct Api;
+
+/// How a user who's lost their wallet can get back into their account. Credential
+/// methods are added when the user registers, if the service has a `recovery_claim`.
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route(
+            "/services/:service_id/users/:user_id/recovery",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/recovery",
+            routing::post(create::create_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/recovery/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
@@ -0,0 +1,215 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_garde::WithValidation;
+use chrono::{DateTime, Duration, Utc};
+use garde::Validate;
+use mist_common::Result;
+use mist_db::models::{
+    recovery_method::{CreateRecoveryMethod, RecoveryKind, RecoveryMethodId},
+    service::ServiceId,
+    user::UserId,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+#[schema(as = CreateRecoveryMethodPayload)]
+pub(crate) struct Payload {
+    /// Either `did`, to set aside a DID the user can recover with, or `approval`, to
+    /// approve a recovery request. Credential methods are added at registration.
+    #[garde(skip)]
+    kind: RecoveryKind,
+    /// The recovery DID. Required for `did`.
+    #[garde(skip)]
+    did: Option<String>,
+    /// How many seconds an approval can be used for.
+    #[garde(range(min = 60, max = 60 * 60 * 24 * 7))]
+    #[serde(default = "default_expires_in")]
+    expires_in: i64,
+}
+
+fn default_expires_in() -> i64 {
+    60 * 60 * 24
+}
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = CreateRecoveryMethodResponse)]
+pub(crate) struct Response {
+    id: RecoveryMethodId,
+    kind: RecoveryKind,
+    expires_at: Option<DateTime<Utc>>,
+    /// Where to send the user to finish an approved recovery. Only shown once.
+    recovery_url: Option<String>,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "Create recovery method",
+    post,
+    path = "",
+    params(PathParams),
+    request_body = CreateRecoveryMethodPayload,
+    responses(
+        (status = 201, body = CreateRecoveryMethodResponse),
+        (status = 400),
+        (status = 404)
+    )
+)]
+pub(crate) async fn create_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    WithValidation(payload): WithValidation<Json<Payload>>,
+) -> Result<impl IntoResponse> {
+    let (value, expires_at) = match (&payload.kind, &payload.did) {
+        (RecoveryKind::Did, Some(did)) => (did.clone(), None),
+        (RecoveryKind::Approval, None) => (
+            Uuid::new_v4().simple().to_string(),
+            Some(Utc::now() + Duration::seconds(payload.expires_in)),
+        ),
+        _ => return Ok(StatusCode::BAD_REQUEST.into_response()),
+    };
+
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    let method = state
+        .repos
+        .recovery_methods
+        .create(
+            &CreateRecoveryMethod::builder()
+                .kind(payload.kind.clone())
+                .value(&value)
+                .maybe_expires_at(expires_at)
+                .service_id(path.service_id)
+                .user_id(path.user_id)
+                .build(),
+        )
+        .await?;
+
+    let recovery_url = (method.kind == RecoveryKind::Approval).then(|| {
+        format!(
+            "{}/{}/recover?code={value}",
+            state.env.authn_url, service.name
+        )
+    });
+
+    Ok((
+        StatusCode::CREATED,
+        Json(Response {
+            id: method.id,
+            kind: method.kind,
+            expires_at: method.expires_at,
+            recovery_url,
+        }),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::{recovery_method::RecoveryMethod, service::Service},
+        repos::{recovery_methods::MockRecoveryMethodRepo, services::MockServiceRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use crate::{handlers::recovery::router, state::Repos};
+
+    use super::*;
+
+    async fn create(body: &'static str, expected_methods: usize) -> Result<StatusCode> {
+        let mut services = MockServiceRepo::new();
+
+        services
+            .expect_get()
+            .times(expected_methods)
+            .returning(|_| Box::pin(ready(Ok(Service::default()))));
+
+        let mut recovery_methods = MockRecoveryMethodRepo::new();
+
+        recovery_methods
+            .expect_create()
+            .with(function(|data: &CreateRecoveryMethod| {
+                !data.value.is_empty()
+                    && (data.kind == RecoveryKind::Did) == data.expires_at.is_none()
+            }))
+            .times(expected_methods)
+            .returning(|data| {
+                Box::pin(ready(Ok(RecoveryMethod {
+                    kind: data.kind.clone(),
+                    ..Default::default()
+                })))
+            });
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                recovery_methods: Arc::new(recovery_methods),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!(
+                        "/services/{}/users/{}/recovery",
+                        ServiceId::new(),
+                        UserId::new()
+                    ))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn creates_recovery_dids() -> Result<()> {
+        let status = create(r#"{ "kind": "did", "did": "did:example:123" }"#, 1).await?;
+
+        assert_eq!(status, StatusCode::CREATED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn approves_recoveries() -> Result<()> {
+        let status = create(r#"{ "kind": "approval" }"#, 1).await?;
+
+        assert_eq!(status, StatusCode::CREATED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_credentials() -> Result<()> {
+        let status = create(r#"{ "kind": "credential" }"#, 0).await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+}
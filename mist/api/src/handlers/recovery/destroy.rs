@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{recovery_method::RecoveryMethodId, service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+    id: RecoveryMethodId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "Delete recovery method",
+    delete,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = RecoveryMethod),
+        (status = 404)
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let method = state
+        .repos
+        .recovery_methods
+        .destroy(&path.service_id, &path.user_id, &path.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(method)))
+}
@@ -0,0 +1,40 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "List recovery methods",
+    get,
+    path = "",
+    params(PathParams),
+    responses(
+        (status = 200, body = Vec<RecoveryMethod>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let methods = state
+        .repos
+        .recovery_methods
+        .list(&path.service_id, &path.user_id)
+        .await?;
+
+    Ok(Json(methods))
+}
@@ -0,0 +1,35 @@
+mod create;
+mod destroy;
+mod list;
+
+use axum::{routing, Router};
+use mist_db::models::redirect_uri::{RedirectUri, RedirectUriId, RedirectUriKind};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list::list_handler, create::create_handler, destroy::destroy_handler),
+    components(schemas(RedirectUriId, RedirectUri, RedirectUriKind, create::Payload))
+)]
+pub(crate) struct Api;
+
+/// Where users can be sent back to after signing in or logging out, besides the
+/// service's `redirect_url` and `logout_url`, and where wallets picking up credential
+/// offers can be sent back to. Requested URIs have to match one exactly.
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route(
+            "/services/:service_id/redirect-uris",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/redirect-uris",
+            routing::post(create::create_handler),
+        )
+        .route(
+            "/services/:service_id/redirect-uris/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
@@ -0,0 +1,162 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_garde::WithValidation;
+use garde::Validate;
+use mist_common::Result;
+use mist_db::models::{
+    redirect_uri::{CreateRedirectUri, RedirectUriKind},
+    service::ServiceId,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::state::ApiState;
+
+#[derive(Serialize, Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+#[schema(as = CreateRedirectUriPayload)]
+pub(crate) struct Payload {
+    #[garde(skip)]
+    #[serde(default)]
+    kind: RedirectUriKind,
+    /// Matched exactly, so it can't have a fragment.
+    #[garde(url)]
+    uri: String,
+}
+
+#[utoipa::path(
+    tags = ["Services"],
+    summary = "Create redirect URI",
+    post,
+    path = "",
+    params(PathParams),
+    request_body = CreateRedirectUriPayload,
+    responses(
+        (status = 201, body = RedirectUri),
+        (status = 400),
+        (status = 404)
+    )
+)]
+pub(crate) async fn create_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+    WithValidation(payload): WithValidation<Json<Payload>>,
+) -> Result<impl IntoResponse> {
+    // See: https://www.rfc-editor.org/rfc/rfc6749#section-3.1.2
+    if payload.uri.contains('#') {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let uri = state
+        .repos
+        .redirect_uris
+        .create(
+            &CreateRedirectUri::builder()
+                .kind(payload.kind.clone())
+                .uri(&payload.uri)
+                .service_id(path.service_id)
+                .build(),
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(uri)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::env::Environment;
+    use mist_db::{models::redirect_uri::RedirectUri, repos::redirect_uris::MockRedirectUriRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use crate::{handlers::redirect_uris::router, state::Repos};
+
+    use super::*;
+
+    async fn create(body: &'static str, expected_uris: usize) -> Result<StatusCode> {
+        let mut redirect_uris = MockRedirectUriRepo::new();
+
+        redirect_uris
+            .expect_create()
+            .with(function(|data: &CreateRedirectUri| {
+                data.kind == RedirectUriKind::PostLogout
+                    && data.uri == "https://example.com/logged-out"
+            }))
+            .times(expected_uris)
+            .returning(|data| {
+                Box::pin(ready(Ok(RedirectUri {
+                    kind: data.kind.clone(),
+                    uri: data.uri.clone(),
+                    ..Default::default()
+                })))
+            });
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                redirect_uris: Arc::new(redirect_uris),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri(format!("/services/{}/redirect-uris", ServiceId::new()))
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn registers_uris() -> Result<()> {
+        let status = create(
+            r#"{ "kind": "post_logout", "uri": "https://example.com/logged-out" }"#,
+            1,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::CREATED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_fragments() -> Result<()> {
+        let status = create(
+            r#"{ "kind": "post_logout", "uri": "https://example.com/logged-out#top" }"#,
+            0,
+        )
+        .await?;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_uris() -> Result<()> {
+        let status = create(r#"{ "kind": "post_logout", "uri": "logged-out" }"#, 0).await?;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        Ok(())
+    }
+}
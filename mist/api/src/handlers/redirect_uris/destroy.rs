@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{redirect_uri::RedirectUriId, service::ServiceId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    id: RedirectUriId,
+}
+
+#[utoipa::path(
+    tags = ["Services"],
+    summary = "Delete redirect URI",
+    delete,
+    path = "/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = RedirectUri),
+        (status = 404)
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let uri = state
+        .repos
+        .redirect_uris
+        .destroy(&path.service_id, &path.id)
+        .await?;
+
+    Ok((StatusCode::OK, Json(uri)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{body::Body, extract::Request, http};
+    use mist_common::{env::Environment, error::NotFound};
+    use mist_db::{models::redirect_uri::RedirectUri, repos::redirect_uris::MockRedirectUriRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use crate::{handlers::redirect_uris::router, state::Repos};
+
+    use super::*;
+
+    async fn destroy(found: bool) -> Result<StatusCode> {
+        let service_id = ServiceId::new();
+        let id = RedirectUriId::new();
+        let mut redirect_uris = MockRedirectUriRepo::new();
+
+        redirect_uris
+            .expect_destroy()
+            .with(eq(service_id), eq(id))
+            .times(1)
+            .returning(move |_, _| {
+                Box::pin(ready(if found {
+                    Ok(RedirectUri::default())
+                } else {
+                    Err(NotFound("redirect uri not found").into())
+                }))
+            });
+
+        let app = router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                redirect_uris: Arc::new(redirect_uris),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(MockWebhookQueue::new()),
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri(format!("/services/{service_id}/redirect-uris/{id}"))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        Ok(response.status())
+    }
+
+    #[tokio::test]
+    async fn removes_uris() -> Result<()> {
+        assert_eq!(destroy(true).await?, StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_removes_the_services_own() -> Result<()> {
+        assert_eq!(destroy(false).await?, StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
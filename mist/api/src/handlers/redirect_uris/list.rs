@@ -0,0 +1,35 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[utoipa::path(
+    tags = ["Services"],
+    summary = "List redirect URIs",
+    get,
+    path = "",
+    params(PathParams),
+    responses(
+        (status = 200, body = Vec<RedirectUri>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let uris = state.repos.redirect_uris.list(&path.service_id).await?;
+
+    Ok(Json(uris))
+}
@@ -5,7 +5,9 @@ mod list;
 mod update;
 
 use axum::{routing, Router};
-use mist_db::models::service::{Service, ServiceId};
+use mist_db::models::service::{
+    ResponseMode, RevocationPolicy, Service, ServiceId, SubjectType, UnknownSignIn, WebhookMode,
+};
 use utoipa::OpenApi;
 
 use crate::state::ApiState;
@@ -19,7 +21,17 @@ use crate::state::ApiState;
         update::update_handler,
         destroy::destroy_handler
     ),
-    components(schemas(ServiceId, Service, create::Payload, update::Payload))
+    components(schemas(
+        ServiceId,
+        Service,
+        ResponseMode,
+        RevocationPolicy,
+        WebhookMode,
+        UnknownSignIn,
+        SubjectType,
+        create::Payload,
+        update::Payload
+    ))
 )]
 pub(crate) struct Api;
 
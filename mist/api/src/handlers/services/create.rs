@@ -4,7 +4,10 @@ use garde::Validate;
 use mist_common::Result;
 use mist_db::models::{
     definition::{CreateDefinition, Value},
-    service::CreateService,
+    service::{
+        CreateService, ResponseMode, RevocationPolicy, SubjectType, UnknownSignIn, WebhookMode,
+        DEFAULT_SESSION_LIFETIME,
+    },
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -23,9 +26,60 @@ pub(crate) struct Payload {
     #[garde(url)]
     webhook_url: String,
     #[garde(skip)]
+    #[serde(default)]
+    response_mode: ResponseMode,
+    #[garde(skip)]
+    #[serde(default)]
+    revocation_policy: RevocationPolicy,
+    #[garde(skip)]
+    #[serde(default)]
+    webhook_mode: WebhookMode,
+    #[garde(skip)]
+    sign_in_webhook: Option<WebhookMode>,
+    #[garde(skip)]
+    #[serde(default)]
+    unknown_sign_in: UnknownSignIn,
+    #[garde(skip)]
+    recovery_claim: Option<String>,
+    #[garde(skip)]
+    sso_domain: Option<String>,
+    /// Seconds a signed in user can go without activity. Sessions last their whole
+    /// lifetime without one.
+    #[garde(inner(range(min = 60)))]
+    session_idle_timeout: Option<i64>,
+    /// Seconds a session can last at most.
+    #[garde(range(min = 60))]
+    #[serde(default = "default_session_lifetime")]
+    session_lifetime: i64,
+    #[garde(skip)]
+    #[serde(default)]
+    token_flow: bool,
+    /// Where to POST logout tokens when a user's session ends.
+    #[garde(url)]
+    backchannel_logout_uri: Option<String>,
+    /// A page to load in a hidden iframe when a user logs out.
+    #[garde(url)]
+    frontchannel_logout_uri: Option<String>,
+    /// Whether users are known by their DID, or by an id only this service gets.
+    #[garde(skip)]
+    #[serde(default)]
+    subject_type: SubjectType,
+    /// Why the service asks for the user's data. Setting one has users agree to share
+    /// it before signing up.
+    #[garde(inner(length(min = 1, max = 500)))]
+    consent_purpose: Option<String>,
+    #[garde(url)]
+    privacy_policy_url: Option<String>,
+    #[garde(url)]
+    terms_url: Option<String>,
+    #[garde(skip)]
     profile: Option<Value>,
 }
 
+fn default_session_lifetime() -> i64 {
+    DEFAULT_SESSION_LIFETIME
+}
+
 #[utoipa::path(
     tags = ["Services"],
     summary = "Create service",
@@ -50,6 +104,22 @@ pub(crate) async fn create_handler(
                 .redirect_url(&payload.redirect_url)
                 .logout_url(&payload.logout_url)
                 .webhook_url(&payload.webhook_url)
+                .response_mode(payload.response_mode.clone())
+                .revocation_policy(payload.revocation_policy.clone())
+                .webhook_mode(payload.webhook_mode.clone())
+                .maybe_sign_in_webhook(payload.sign_in_webhook.clone())
+                .unknown_sign_in(payload.unknown_sign_in.clone())
+                .maybe_recovery_claim(payload.recovery_claim.clone())
+                .maybe_sso_domain(payload.sso_domain.clone())
+                .maybe_session_idle_timeout(payload.session_idle_timeout)
+                .session_lifetime(payload.session_lifetime)
+                .token_flow(payload.token_flow)
+                .maybe_backchannel_logout_uri(payload.backchannel_logout_uri.clone())
+                .maybe_frontchannel_logout_uri(payload.frontchannel_logout_uri.clone())
+                .subject_type(payload.subject_type.clone())
+                .maybe_consent_purpose(payload.consent_purpose.clone())
+                .maybe_privacy_policy_url(payload.privacy_policy_url.clone())
+                .maybe_terms_url(payload.terms_url.clone())
                 .build(),
             &payload.profile.as_ref().map(|profile| {
                 CreateDefinition::builder()
@@ -72,8 +142,9 @@ mod tests {
     use mist_common::env::Environment;
     use mist_db::{
         models::service::{Service, ServiceId},
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
+        repos::services::MockServiceRepo,
     };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use secstr::SecVec;
     use tower::ServiceExt;
@@ -123,8 +194,9 @@ mod tests {
             },
             repos: Repos {
                 services: Arc::new(services),
-                keys: Arc::new(MockKeyRepo::new()),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
@@ -44,10 +44,8 @@ mod tests {
         extract::Request,
         http::{self, StatusCode},
     };
-    use mist_db::{
-        models::service::Service,
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
-    };
+    use mist_db::{models::service::Service, repos::services::MockServiceRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use tower::ServiceExt;
 
@@ -71,8 +69,9 @@ mod tests {
             env: mist_common::env::Environment::default(),
             repos: Repos {
                 services: Arc::new(services),
-                keys: Arc::new(MockKeyRepo::new()),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
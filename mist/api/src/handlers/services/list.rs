@@ -50,10 +50,8 @@ mod tests {
         http::{self, Request, StatusCode},
     };
     use mist_common::env::Environment;
-    use mist_db::{
-        models::service::Service,
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
-    };
+    use mist_db::{models::service::Service, repos::services::MockServiceRepo};
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use tower::ServiceExt;
 
     use super::*;
@@ -76,8 +74,9 @@ mod tests {
             env: Environment::default(),
             repos: Repos {
                 services: Arc::new(services),
-                keys: Arc::new(MockKeyRepo::new()),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
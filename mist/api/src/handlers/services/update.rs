@@ -6,8 +6,11 @@ use axum::{
 use axum_garde::WithValidation;
 use garde::Validate;
 use mist_common::Result;
-use mist_db::models::service::{ServiceId, UpdateService};
-use serde::{Deserialize, Serialize};
+use mist_db::models::service::{
+    ResponseMode, RevocationPolicy, ServiceId, SubjectType, UnknownSignIn, UpdateService,
+    WebhookMode,
+};
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
 use crate::state::ApiState;
@@ -28,6 +31,65 @@ pub(crate) struct Payload {
     logout_url: Option<String>,
     #[garde(url)]
     webhook_url: Option<String>,
+    #[garde(skip)]
+    response_mode: Option<ResponseMode>,
+    #[garde(skip)]
+    revocation_policy: Option<RevocationPolicy>,
+    #[garde(skip)]
+    webhook_mode: Option<WebhookMode>,
+    /// An explicit `null` turns the sign in webhook off.
+    #[garde(skip)]
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<WebhookMode>)]
+    sign_in_webhook: Option<Option<WebhookMode>>,
+    #[garde(skip)]
+    unknown_sign_in: Option<UnknownSignIn>,
+    /// An explicit `null` turns credential recovery off.
+    #[garde(skip)]
+    #[serde(default, deserialize_with = "present")]
+    recovery_claim: Option<Option<String>>,
+    /// An explicit `null` takes the service out of its SSO domain.
+    #[garde(skip)]
+    #[serde(default, deserialize_with = "present")]
+    sso_domain: Option<Option<String>>,
+    /// An explicit `null` turns the idle timeout off.
+    #[garde(inner(inner(range(min = 60))))]
+    #[serde(default, deserialize_with = "present")]
+    session_idle_timeout: Option<Option<i64>>,
+    #[garde(inner(range(min = 60)))]
+    session_lifetime: Option<i64>,
+    #[garde(skip)]
+    token_flow: Option<bool>,
+    /// An explicit `null` turns back-channel logout off.
+    #[garde(url)]
+    #[serde(default, deserialize_with = "present")]
+    backchannel_logout_uri: Option<Option<String>>,
+    /// An explicit `null` turns front-channel logout off.
+    #[garde(url)]
+    #[serde(default, deserialize_with = "present")]
+    frontchannel_logout_uri: Option<Option<String>>,
+    /// Switching changes what the service knows its existing users by.
+    #[garde(skip)]
+    subject_type: Option<SubjectType>,
+    /// An explicit `null` turns the consent page off.
+    #[garde(inner(inner(length(min = 1, max = 500))))]
+    #[serde(default, deserialize_with = "present")]
+    consent_purpose: Option<Option<String>>,
+    #[garde(url)]
+    #[serde(default, deserialize_with = "present")]
+    privacy_policy_url: Option<Option<String>>,
+    #[garde(url)]
+    #[serde(default, deserialize_with = "present")]
+    terms_url: Option<Option<String>>,
+}
+
+/// Tells a missing field apart from an explicit `null`.
+fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
 
 #[utoipa::path(
@@ -57,6 +119,22 @@ pub(crate) async fn update_handler(
                 .maybe_redirect_url(payload.redirect_url.clone())
                 .maybe_logout_url(payload.logout_url.clone())
                 .maybe_webhook_url(payload.webhook_url.clone())
+                .maybe_response_mode(payload.response_mode.clone())
+                .maybe_revocation_policy(payload.revocation_policy.clone())
+                .maybe_webhook_mode(payload.webhook_mode.clone())
+                .maybe_sign_in_webhook(payload.sign_in_webhook.clone())
+                .maybe_unknown_sign_in(payload.unknown_sign_in.clone())
+                .maybe_recovery_claim(payload.recovery_claim.clone())
+                .maybe_sso_domain(payload.sso_domain.clone())
+                .maybe_session_idle_timeout(payload.session_idle_timeout)
+                .maybe_session_lifetime(payload.session_lifetime)
+                .maybe_token_flow(payload.token_flow)
+                .maybe_backchannel_logout_uri(payload.backchannel_logout_uri.clone())
+                .maybe_frontchannel_logout_uri(payload.frontchannel_logout_uri.clone())
+                .maybe_subject_type(payload.subject_type.clone())
+                .maybe_consent_purpose(payload.consent_purpose.clone())
+                .maybe_privacy_policy_url(payload.privacy_policy_url.clone())
+                .maybe_terms_url(payload.terms_url.clone())
                 .build(),
         )
         .await?;
@@ -75,8 +153,9 @@ mod tests {
     use mist_common::env::Environment;
     use mist_db::{
         models::service::{Service, UpdateService},
-        repos::{keys::MockKeyRepo, services::MockServiceRepo},
+        repos::services::MockServiceRepo,
     };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
     use mockall::predicate::*;
     use tower::ServiceExt;
 
@@ -103,8 +182,9 @@ mod tests {
             env: Environment::default(),
             repos: Repos {
                 services: Arc::new(services),
-                keys: Arc::new(MockKeyRepo::new()),
+                ..Repos::mocked()
             },
+            webhooks: Arc::new(MockWebhookQueue::new()),
         });
 
         let response = app
@@ -0,0 +1,75 @@
+mod destroy;
+mod destroy_all;
+mod list;
+mod list_all;
+
+use axum::{routing, Router};
+use mist_common::Result;
+use mist_db::models::{
+    service::ServiceId,
+    session::{Session, SessionId},
+    user::UserId,
+};
+use utoipa::OpenApi;
+
+use crate::state::ApiState;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_all::list_all_handler,
+        list::list_handler,
+        destroy::destroy_handler,
+        destroy_all::destroy_all_handler
+    ),
+    components(schemas(SessionId, Session))
+)]
+pub(crate) struct Api;
+
+/// Users' signed in sessions. Revoking one signs the user out, and the service is sent
+/// a `session.revoked` webhook.
+pub(crate) fn router() -> Router<ApiState> {
+    Router::new()
+        .route(
+            "/services/:service_id/sessions",
+            routing::get(list_all::list_all_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/sessions",
+            routing::get(list::list_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/sessions",
+            routing::delete(destroy_all::destroy_all_handler),
+        )
+        .route(
+            "/services/:service_id/users/:user_id/sessions/:id",
+            routing::delete(destroy::destroy_handler),
+        )
+}
+
+/// End the session and let the service know.
+async fn revoke(
+    state: &ApiState,
+    webhook_url: &str,
+    service_id: &ServiceId,
+    user_id: &UserId,
+    id: &SessionId,
+) -> Result<Session> {
+    let session = state
+        .repos
+        .sessions
+        .destroy(service_id, user_id, id)
+        .await?;
+
+    state
+        .webhooks
+        .publish(
+            webhook_url,
+            "session.revoked",
+            &serde_json::to_value(&session)?,
+        )
+        .await?;
+
+    Ok(session)
+}
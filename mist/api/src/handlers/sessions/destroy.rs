@@ -0,0 +1,158 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, session::SessionId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{handlers::sessions::revoke, state::ApiState};
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+    id: SessionId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "Revoke session",
+    delete,
+    path = "/users/{user_id}/sessions/{id}",
+    params(PathParams),
+    responses(
+        (status = 200, body = Session),
+        (status = 404)
+    )
+)]
+pub(crate) async fn destroy_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    let session = revoke(
+        &state,
+        &service.webhook_url,
+        &path.service_id,
+        &path.user_id,
+        &path.id,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(session)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::{env::Environment, error::NotFound};
+    use mist_db::{
+        models::{service::Service, session::Session},
+        repos::{services::MockServiceRepo, sessions::MockSessionRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::sessions::router, state::Repos};
+
+    fn app(
+        services: MockServiceRepo,
+        sessions: MockSessionRepo,
+        webhooks: MockWebhookQueue,
+    ) -> axum::Router {
+        router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                sessions: Arc::new(sessions),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(webhooks),
+        })
+    }
+
+    fn request(service_id: ServiceId, user_id: UserId, id: SessionId) -> Result<Request<Body>> {
+        Ok(Request::builder()
+            .method(http::Method::DELETE)
+            .uri(format!(
+                "/services/{service_id}/users/{user_id}/sessions/{id}"
+            ))
+            .body(Body::from(()))?)
+    }
+
+    fn services() -> MockServiceRepo {
+        let mut services = MockServiceRepo::new();
+
+        services
+            .expect_get()
+            .returning(|_| Box::pin(ready(Ok(Service::default()))));
+
+        services
+    }
+
+    #[tokio::test]
+    async fn revokes() -> Result<()> {
+        let service_id = ServiceId::new();
+        let user_id = UserId::new();
+        let id = SessionId::new();
+
+        let mut sessions = MockSessionRepo::new();
+
+        sessions
+            .expect_destroy()
+            .with(eq(service_id), eq(user_id), eq(id))
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(Session::default()))));
+
+        let mut webhooks = MockWebhookQueue::new();
+
+        webhooks
+            .expect_publish()
+            .with(always(), eq("session.revoked"), always())
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Ok(()))));
+
+        let response = app(services(), sessions, webhooks)
+            .oneshot(request(service_id, user_id, id)?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_revokes_known_sessions() -> Result<()> {
+        let mut sessions = MockSessionRepo::new();
+
+        sessions
+            .expect_destroy()
+            .once()
+            .returning(|_, _, _| Box::pin(ready(Err(NotFound("session not found").into()))));
+
+        let mut webhooks = MockWebhookQueue::new();
+
+        webhooks.expect_publish().never();
+
+        let response = app(services(), sessions, webhooks)
+            .oneshot(request(ServiceId::new(), UserId::new(), SessionId::new())?)
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
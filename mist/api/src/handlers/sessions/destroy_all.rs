@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{handlers::sessions::revoke, state::ApiState};
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "Revoke all user sessions",
+    delete,
+    path = "/users/{user_id}/sessions",
+    params(PathParams),
+    responses(
+        (status = 200, body = Vec<Session>)
+    )
+)]
+pub(crate) async fn destroy_all_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    let sessions = state
+        .repos
+        .sessions
+        .list_by_user(&path.service_id, &path.user_id)
+        .await?;
+
+    let mut revoked = Vec::with_capacity(sessions.len());
+
+    for session in sessions {
+        revoked.push(
+            revoke(
+                &state,
+                &service.webhook_url,
+                &path.service_id,
+                &path.user_id,
+                &session.id,
+            )
+            .await?,
+        );
+    }
+
+    Ok((StatusCode::OK, Json(revoked)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::{service::Service, session::Session},
+        repos::{services::MockServiceRepo, sessions::MockSessionRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::sessions::router, state::Repos};
+
+    fn app(
+        services: MockServiceRepo,
+        sessions: MockSessionRepo,
+        webhooks: MockWebhookQueue,
+    ) -> axum::Router {
+        router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                sessions: Arc::new(sessions),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(webhooks),
+        })
+    }
+
+    #[tokio::test]
+    async fn revokes_every_session() -> Result<()> {
+        let service_id = ServiceId::new();
+        let user_id = UserId::new();
+
+        let mut services = MockServiceRepo::new();
+
+        services
+            .expect_get()
+            .returning(|_| Box::pin(ready(Ok(Service::default()))));
+
+        let mut sessions = MockSessionRepo::new();
+
+        sessions
+            .expect_list_by_user()
+            .with(eq(service_id), eq(user_id))
+            .once()
+            .returning(|_, _| Box::pin(ready(Ok(vec![Session::default(), Session::default()]))));
+
+        sessions
+            .expect_destroy()
+            .times(2)
+            .returning(|_, _, _| Box::pin(ready(Ok(Session::default()))));
+
+        let mut webhooks = MockWebhookQueue::new();
+
+        webhooks
+            .expect_publish()
+            .with(always(), eq("session.revoked"), always())
+            .times(2)
+            .returning(|_, _, _| Box::pin(ready(Ok(()))));
+
+        let response = app(services, sessions, webhooks)
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri(format!("/services/{service_id}/users/{user_id}/sessions"))
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::{service::ServiceId, user::UserId};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::state::ApiState;
+
+#[derive(Deserialize, IntoParams)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    user_id: UserId,
+}
+
+#[utoipa::path(
+    tags = ["Users"],
+    summary = "List user sessions",
+    get,
+    path = "/users/{user_id}/sessions",
+    params(PathParams),
+    responses(
+        (status = 200, body = Vec<Session>)
+    )
+)]
+pub(crate) async fn list_handler(
+    State(state): State<ApiState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let sessions = state
+        .repos
+        .sessions
+        .list_by_user(&path.service_id, &path.user_id)
+        .await?;
+
+    Ok(Json(sessions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::ready, sync::Arc};
+
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use mist_common::env::Environment;
+    use mist_db::{
+        models::session::Session,
+        repos::{services::MockServiceRepo, sessions::MockSessionRepo},
+    };
+    use mist_jobs::jobs::webhooks::MockWebhookQueue;
+    use mockall::predicate::*;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    use crate::{handlers::sessions::router, state::Repos};
+
+    fn app(
+        services: MockServiceRepo,
+        sessions: MockSessionRepo,
+        webhooks: MockWebhookQueue,
+    ) -> axum::Router {
+        router().with_state(ApiState {
+            env: Environment::default(),
+            repos: Repos {
+                services: Arc::new(services),
+                sessions: Arc::new(sessions),
+                ..Repos::mocked()
+            },
+            webhooks: Arc::new(webhooks),
+        })
+    }
+
+    #[tokio::test]
+    async fn lists() -> Result<()> {
+        let service_id = ServiceId::new();
+        let user_id = UserId::new();
+
+        let mut sessions = MockSessionRepo::new();
+
+        sessions
+            .expect_list_by_user()
+            .with(eq(service_id), eq(user_id))
+            .once()
+            .returning(|_, _| Box::pin(ready(Ok(vec![Session::default()]))));
+
+        let response = app(MockServiceRepo::new(), sessions, MockWebhookQueue::new())
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::GET)
+                    .uri(format!("/services/{service_id}/users/{user_id}/sessions"))
+                    .body(Body::from(()))?,
+            )
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+}
@@ -2,18 +2,58 @@ use std::sync::Arc;
 
 use axum::extract::FromRef;
 use mist_common::env::Environment;
-use mist_db::repos::{keys::KeyRepo, services::ServiceRepo};
+use mist_db::repos::{
+    credential_configurations::CredentialConfigurationRepo, credential_offers::CredentialOfferRepo,
+    identifiers::IdentifierRepo, keys::KeyRepo, recovery_methods::RecoveryMethodRepo,
+    redirect_uris::RedirectUriRepo, services::ServiceRepo, sessions::SessionRepo,
+    trusted_issuers::TrustedIssuerRepo,
+};
+use mist_jobs::jobs::WebhookQueue;
 
 #[derive(Clone)]
 pub(crate) struct Repos {
     pub(crate) services: Arc<dyn ServiceRepo>,
     pub(crate) keys: Arc<dyn KeyRepo>,
+    pub(crate) trusted_issuers: Arc<dyn TrustedIssuerRepo>,
+    pub(crate) credential_configurations: Arc<dyn CredentialConfigurationRepo>,
+    pub(crate) credential_offers: Arc<dyn CredentialOfferRepo>,
+    pub(crate) identifiers: Arc<dyn IdentifierRepo>,
+    pub(crate) recovery_methods: Arc<dyn RecoveryMethodRepo>,
+    pub(crate) sessions: Arc<dyn SessionRepo>,
+    pub(crate) redirect_uris: Arc<dyn RedirectUriRepo>,
 }
 
 #[derive(Clone)]
 pub(crate) struct ApiState {
     pub(crate) env: Environment,
     pub(crate) repos: Repos,
+    pub(crate) webhooks: Arc<dyn WebhookQueue>,
+}
+
+#[cfg(test)]
+impl Repos {
+    /// Mocks that expect nothing, for tests to override the repos they use.
+    pub(crate) fn mocked() -> Self {
+        use mist_db::repos::{
+            credential_configurations::MockCredentialConfigurationRepo,
+            credential_offers::MockCredentialOfferRepo, identifiers::MockIdentifierRepo,
+            keys::MockKeyRepo, recovery_methods::MockRecoveryMethodRepo,
+            redirect_uris::MockRedirectUriRepo, services::MockServiceRepo,
+            sessions::MockSessionRepo, trusted_issuers::MockTrustedIssuerRepo,
+        };
+
+        Self {
+            services: Arc::new(MockServiceRepo::new()),
+            keys: Arc::new(MockKeyRepo::new()),
+            trusted_issuers: Arc::new(MockTrustedIssuerRepo::new()),
+            credential_configurations: Arc::new(MockCredentialConfigurationRepo::new()),
+            credential_offers: Arc::new(MockCredentialOfferRepo::new()),
+            identifiers: Arc::new(MockIdentifierRepo::new()),
+            recovery_methods: Arc::new(MockRecoveryMethodRepo::new()),
+            sessions: Arc::new(MockSessionRepo::new()),
+            redirect_uris: Arc::new(MockRedirectUriRepo::new()),
+        }
+    }
 }
 
 impl FromRef<ApiState> for () {
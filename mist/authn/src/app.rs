@@ -8,7 +8,11 @@ use fred::{
 };
 use mist_common::{env::Environment, Result};
 use mist_db::repos::{
-    identifiers::PgIdentifierRepo, keys::PgKeyRepo, services::PgServiceRepo, users::PgUserRepo,
+    credential_configurations::PgCredentialConfigurationRepo,
+    credential_offers::PgCredentialOfferRepo, identifiers::PgIdentifierRepo, keys::PgKeyRepo,
+    recovery_methods::PgRecoveryMethodRepo, redirect_uris::PgRedirectUriRepo,
+    services::PgServiceRepo, sessions::RedisSessionRepo, sign_up_consents::PgSignUpConsentRepo,
+    sso_consents::PgSsoConsentRepo, trusted_issuers::PgTrustedIssuerRepo, users::PgUserRepo,
 };
 use sqlx::postgres::PgPoolOptions;
 use tower_cookies::CookieManagerLayer;
@@ -16,6 +20,7 @@ use tower_cookies::CookieManagerLayer;
 use crate::{
     handlers,
     state::{AuthnState, Repos},
+    utils::status_list::StatusLists,
 };
 
 pub async fn app(env: Environment) -> Router {
@@ -25,15 +30,25 @@ pub async fn app(env: Environment) -> Router {
         .await
         .unwrap();
 
+    let redis = create_redis_client(&env).await.unwrap();
+
     let repos = Repos {
         services: Arc::new(PgServiceRepo::new(postgres.clone())),
         keys: Arc::new(PgKeyRepo::new(postgres.clone())),
         users: Arc::new(PgUserRepo::new(postgres.clone())),
         identifiers: Arc::new(PgIdentifierRepo::new(postgres.clone())),
+        trusted_issuers: Arc::new(PgTrustedIssuerRepo::new(postgres.clone())),
+        credential_configurations: Arc::new(PgCredentialConfigurationRepo::new(postgres.clone())),
+        credential_offers: Arc::new(PgCredentialOfferRepo::new(postgres.clone())),
+        recovery_methods: Arc::new(PgRecoveryMethodRepo::new(postgres.clone())),
+        sso_consents: Arc::new(PgSsoConsentRepo::new(postgres.clone())),
+        sessions: Arc::new(RedisSessionRepo::new(redis.clone())),
+        redirect_uris: Arc::new(PgRedirectUriRepo::new(postgres.clone())),
+        sign_up_consents: Arc::new(PgSignUpConsentRepo::new(postgres.clone())),
     };
 
-    let redis = create_redis_client(&env).await.unwrap();
     let (nats, jetstream) = create_nas_client(&env).await.unwrap();
+    let status_lists = StatusLists::new(&env.resolver_url);
 
     Router::new()
         .nest("", handlers::router())
@@ -43,6 +58,7 @@ pub async fn app(env: Environment) -> Router {
             redis,
             nats,
             jetstream,
+            status_lists,
         })
         .layer(CookieManagerLayer::new())
 }
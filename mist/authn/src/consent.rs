@@ -0,0 +1,144 @@
+// Asking users before they share their data with a service.
+//
+// Services with a `consent_purpose` show users which fields they'll get, and why,
+// before the QR code to sign up, along with their privacy policy and terms. The QR
+// code only appears once the user agrees, and what they were shown is kept for
+// audit.
+// -------------------------------------------------------------------------------
+
+use axum::response::{IntoResponse, Response};
+use mist_common::Result;
+use mist_db::models::{definition::Field, service::Service, sign_up_consent::CreateSignUpConsent};
+
+use crate::{
+    session::{AuthAction, AuthSession, AuthState, SessionId},
+    state::AuthnState,
+    views,
+};
+
+/// Whether the user has to agree to share their data before they can scan.
+pub(crate) fn is_needed(service: &Service, session: &AuthSession) -> bool {
+    service.consent_purpose.is_some()
+        && !session.consented
+        && matches!(
+            session.state,
+            AuthState::Authenticating {
+                action: AuthAction::Up,
+                ..
+            }
+        )
+}
+
+/// Show the user what the service is asking for.
+pub(crate) async fn ask(state: &AuthnState, service: &Service) -> Result<Response> {
+    let fields = requested_fields(state, service).await?;
+
+    Ok(views::consent::view(service, &state.env.authn_url, &fields).into_response())
+}
+
+/// Keep a record of what the user agreed to.
+pub(crate) async fn record(
+    state: &AuthnState,
+    service: &Service,
+    session_id: &SessionId,
+    session: &AuthSession,
+) -> Result<()> {
+    let fields = requested_fields(state, service).await?;
+
+    let consent = state
+        .repos
+        .sign_up_consents
+        .create(
+            &CreateSignUpConsent::builder()
+                .user_id(session.user_id)
+                .session_id(*session_id)
+                .service_id(service.id)
+                .fields(fields.into_iter().map(|field| field.name).collect())
+                .purpose(service.consent_purpose.clone().unwrap_or_default())
+                .maybe_privacy_policy_url(service.privacy_policy_url.clone())
+                .maybe_terms_url(service.terms_url.clone())
+                .maybe_ip(session.client.ip.clone())
+                .maybe_user_agent(session.client.user_agent.clone())
+                .build(),
+        )
+        .await?;
+
+    tracing::info!(
+        target: "audit",
+        service_id = %service.id,
+        user_id = %session.user_id,
+        consent_id = %consent.id,
+        "sign up consented"
+    );
+
+    Ok(())
+}
+
+/// The fields in the service's default definition, which the wallet is asked for when
+/// signing up.
+async fn requested_fields(state: &AuthnState, service: &Service) -> Result<Vec<Field>> {
+    let profile = state
+        .repos
+        .services
+        .get_default_profile(&service.id)
+        .await?;
+
+    Ok(profile
+        .map(|profile| profile.value.0.fields)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(action: AuthAction) -> AuthSession {
+        AuthSession {
+            user_id: Default::default(),
+            service_id: Default::default(),
+            state: AuthState::Authenticating {
+                action,
+                refreshes: 0,
+            },
+            expires_at: 0,
+            idle_timeout: None,
+            client: Default::default(),
+            return_to: Default::default(),
+            device: false,
+            consented: false,
+            sid: Default::default(),
+        }
+    }
+
+    fn asks_first() -> Service {
+        Service {
+            consent_purpose: Some("To set up your account.".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_asks_before_signing_up() {
+        assert!(is_needed(&asks_first(), &session(AuthAction::Up)));
+        assert!(!is_needed(&asks_first(), &session(AuthAction::In)));
+        assert!(!is_needed(&Service::default(), &session(AuthAction::Up)));
+    }
+
+    #[test]
+    fn asks_when_signing_in_switches_to_signing_up() {
+        // As `handle_unknown` leaves a sign in with a wallet we don't know.
+        let switched = session(AuthAction::In).with_state(AuthState::Authenticating {
+            action: AuthAction::Up,
+            refreshes: 0,
+        });
+
+        assert!(is_needed(&asks_first(), &switched));
+
+        let consented = AuthSession {
+            consented: true,
+            ..switched
+        };
+
+        assert!(!is_needed(&asks_first(), &consented));
+    }
+}
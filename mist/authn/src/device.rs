@@ -0,0 +1,416 @@
+// Signing in on devices without a browser, like a CLI.
+//
+// The device asks `/{service}/device/code` for a device code and a short user code,
+// and tells the user to open `/{service}/device` somewhere else and enter the user
+// code. There they scan the usual QR code with their wallet. Meanwhile the device
+// polls `/{service}/token` with the device code, and gets tokens once the session
+// is signed in.
+//
+// The session belongs to the device: the browser only confirms it, and is told to
+// go back to the device instead of being sent on to the service.
+//
+// User codes are short enough to guess, so wrong ones are counted. A client that
+// enters too many is shut out for a while. Across the whole service, a flood of
+// wrong codes only slows everyone's attempts down, so no one client can lock
+// everyone else out.
+//
+// See: https://www.rfc-editor.org/rfc/rfc8628
+// -------------------------------------------------------------------------------
+
+use std::time::Duration;
+
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use fred::{
+    interfaces::KeysInterface,
+    types::{Expiration, SetOptions},
+};
+use mist_common::{redis::TypedRedis, Result};
+use mist_db::models::service::{Service, ServiceId};
+use openidconnect::CsrfToken;
+use rand::{rngs::OsRng, Rng};
+use serde::{Deserialize, Serialize};
+use tower_cookies::Cookies;
+
+use crate::{
+    session::{self, machine, AuthSession, AuthState, Client, SessionId},
+    state::AuthnState,
+    views,
+};
+
+pub(crate) const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// How long the user has to enter the code and sign in, in seconds.
+const DEVICE_CODE_EXPIRES_IN: i64 = 60 * 10;
+
+/// How long the device should wait between polls, in seconds.
+const INTERVAL: i64 = 5;
+
+/// Consonants only, so codes can't spell anything and are easy to read out.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc8628#section-6.1
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LENGTH: usize = 8;
+
+/// How many wrong user codes a client can enter before it's shut out until the
+/// window's over.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc8628#section-5.1
+const MAX_ATTEMPTS_PER_CLIENT: i64 = 10;
+/// How many wrong user codes the whole service can see before every attempt is
+/// held back by [`SLOW_DOWN`].
+const MAX_ATTEMPTS_PER_SERVICE: i64 = 1000;
+const SLOW_DOWN: Duration = Duration::from_secs(3);
+/// In seconds.
+const ATTEMPTS_WINDOW: i64 = 60 * 10;
+
+static DEVICE_CODE: TypedRedis<DeviceGrant> = TypedRedis::new("mist-device-code");
+/// User codes, without the dash and prefixed with their service, pointing at their
+/// device code.
+static USER_CODE: TypedRedis<String> = TypedRedis::new("mist-user-code");
+
+#[derive(Clone, Serialize, Deserialize)]
+struct DeviceGrant {
+    service_id: ServiceId,
+    /// The session, once the user has entered the code.
+    session_id: Option<SessionId>,
+    /// How long the device has to wait between polls, in seconds.
+    interval: i64,
+    /// As a unix timestamp.
+    polled_at: Option<i64>,
+    /// As a unix timestamp.
+    expires_at: i64,
+}
+
+/// See: https://www.rfc-editor.org/rfc/rfc8628#section-3.2
+#[derive(Serialize)]
+pub(crate) struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: String,
+    expires_in: i64,
+    interval: i64,
+}
+
+/// Where the device is at with signing in.
+pub(crate) enum Poll {
+    Pending,
+    /// The device is polling more often than it was told to.
+    SlowDown,
+    SignedIn(SessionId, Box<AuthSession>),
+    Denied,
+    Expired,
+    /// The device code isn't the service's.
+    Invalid,
+}
+
+/// Start signing in a device for the service.
+pub(crate) async fn start(state: &AuthnState, service: &Service) -> Result<DeviceAuthorization> {
+    let device_code = CsrfToken::new_random_len(32).secret().clone();
+    let expires_at = Utc::now().timestamp() + DEVICE_CODE_EXPIRES_IN;
+
+    DEVICE_CODE
+        .set(
+            &state.redis,
+            &device_code,
+            &DeviceGrant {
+                service_id: service.id,
+                session_id: None,
+                interval: INTERVAL,
+                polled_at: None,
+                expires_at,
+            },
+            Expiration::EXAT(expires_at),
+        )
+        .await?;
+
+    // Codes are short enough that two devices could end up with the same one.
+    let user_code = loop {
+        let user_code = user_code();
+
+        if USER_CODE
+            .set_nx(
+                &state.redis,
+                &user_code_key(service, &user_code),
+                &device_code,
+                Expiration::EXAT(expires_at),
+            )
+            .await?
+        {
+            break user_code;
+        }
+    };
+
+    let verification_uri = format!("{}/{}/device", state.env.authn_url, service.name);
+
+    Ok(DeviceAuthorization {
+        device_code,
+        verification_uri_complete: format!("{verification_uri}?user_code={user_code}"),
+        user_code: display(&user_code),
+        verification_uri,
+        expires_in: DEVICE_CODE_EXPIRES_IN,
+        interval: INTERVAL,
+    })
+}
+
+/// Hand the session to the device the user code was given to, using up the code.
+/// Returns false if there's no such device waiting on the service.
+pub(crate) async fn attach(
+    state: &AuthnState,
+    service: &Service,
+    user_code: &str,
+    session_id: &SessionId,
+) -> Result<bool> {
+    // Taken in one go, so only one session can ever get the code.
+    let Some(device_code) = USER_CODE
+        .take(&state.redis, &user_code_key(service, &normalize(user_code)))
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    // Polls update the grant too, so keep at it until the session lands on top.
+    loop {
+        let Ok(grant) = DEVICE_CODE.get(&state.redis, &device_code).await else {
+            return Ok(false);
+        };
+
+        if grant.session_id.is_some() {
+            return Ok(false);
+        }
+
+        let attached = DeviceGrant {
+            session_id: Some(*session_id),
+            ..grant.clone()
+        };
+
+        if DEVICE_CODE
+            .compare_and_set(
+                &state.redis,
+                &device_code,
+                &grant,
+                &attached,
+                Expiration::EXAT(grant.expires_at),
+            )
+            .await?
+        {
+            return Ok(true);
+        }
+    }
+}
+
+/// Whether the user code is one a device is waiting on, without using it up.
+pub(crate) async fn is_waiting(state: &AuthnState, service: &Service, user_code: &str) -> bool {
+    let Ok(device_code) = USER_CODE
+        .get(&state.redis, &user_code_key(service, &normalize(user_code)))
+        .await
+    else {
+        return false;
+    };
+
+    DEVICE_CODE
+        .get(&state.redis, &device_code)
+        .await
+        .is_ok_and(|grant| grant.session_id.is_none())
+}
+
+/// Whether the client has entered too many wrong codes for the service lately.
+///
+/// Clients are told apart by their address, so one we can't place is never shut out,
+/// but still counts towards the service's attempts.
+pub(crate) async fn is_locked_out(
+    state: &AuthnState,
+    service: &Service,
+    client: &Client,
+) -> Result<bool> {
+    let Some(key) = client_attempts_key(service, client) else {
+        return Ok(false);
+    };
+
+    let attempts = state.redis.get::<Option<i64>, _>(key).await?;
+
+    Ok(attempts.is_some_and(|attempts| attempts >= MAX_ATTEMPTS_PER_CLIENT))
+}
+
+/// Hold the attempt back if the service has seen too many wrong codes lately, which
+/// slows down guesses spread across many clients without shutting anyone out.
+pub(crate) async fn throttle(state: &AuthnState, service: &Service) -> Result<()> {
+    let attempts = state
+        .redis
+        .get::<Option<i64>, _>(service_attempts_key(service))
+        .await?;
+
+    if attempts.is_some_and(|attempts| attempts >= MAX_ATTEMPTS_PER_SERVICE) {
+        tokio::time::sleep(SLOW_DOWN).await;
+    }
+
+    Ok(())
+}
+
+/// Count a wrong code against the client and the service.
+pub(crate) async fn failed_attempt(
+    state: &AuthnState,
+    service: &Service,
+    client: &Client,
+) -> Result<()> {
+    let keys = [
+        Some(service_attempts_key(service)),
+        client_attempts_key(service, client),
+    ];
+
+    for key in keys.into_iter().flatten() {
+        // Start the window with the first attempt. Incrementing keeps the expiry.
+        state
+            .redis
+            .set::<(), _, _>(
+                &key,
+                0,
+                Some(Expiration::EX(ATTEMPTS_WINDOW)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await?;
+        state.redis.incr::<(), _>(&key).await?;
+    }
+
+    Ok(())
+}
+
+/// Check on the device's session. The device code is used up once the session has
+/// been signed in or rejected.
+pub(crate) async fn poll(state: &AuthnState, service: &Service, device_code: &str) -> Result<Poll> {
+    let Ok(polled) = DEVICE_CODE.get(&state.redis, device_code).await else {
+        return Ok(Poll::Expired);
+    };
+
+    if polled.service_id != service.id {
+        return Ok(Poll::Invalid);
+    }
+
+    // See: https://www.rfc-editor.org/rfc/rfc8628#section-3.5
+    let now = Utc::now().timestamp();
+    let too_soon = polled
+        .polled_at
+        .is_some_and(|at| now - at < polled.interval);
+    let grant = DeviceGrant {
+        polled_at: Some(now),
+        interval: match too_soon {
+            true => polled.interval + INTERVAL,
+            false => polled.interval,
+        },
+        ..polled.clone()
+    };
+
+    // The user may have just entered the code, in which case check again next time.
+    if !DEVICE_CODE
+        .compare_and_set(
+            &state.redis,
+            device_code,
+            &polled,
+            &grant,
+            Expiration::EXAT(grant.expires_at),
+        )
+        .await?
+    {
+        return Ok(Poll::Pending);
+    }
+
+    if too_soon {
+        return Ok(Poll::SlowDown);
+    }
+
+    let Some(session_id) = grant.session_id else {
+        return Ok(Poll::Pending);
+    };
+
+    let Ok(session) = machine::load(state, &session_id.to_string()).await else {
+        return Ok(Poll::Expired);
+    };
+
+    match session.state {
+        AuthState::Authenticated { .. } => {
+            DEVICE_CODE.del(&state.redis, device_code).await?;
+
+            Ok(Poll::SignedIn(session_id, Box::new(session)))
+        }
+        AuthState::Rejected { .. } => {
+            DEVICE_CODE.del(&state.redis, device_code).await?;
+
+            Ok(Poll::Denied)
+        }
+        AuthState::Expired => Ok(Poll::Expired),
+        _ => Ok(Poll::Pending),
+    }
+}
+
+/// The browser has done its part once the session is signed in, so take the session
+/// off it and send the user back to their device.
+pub(crate) fn finish(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service: &Service,
+    session_id: &SessionId,
+) -> Response {
+    cookies.remove(session::cookie(service, session_id, !state.env.development));
+
+    views::device::signed_in(service).into_response()
+}
+
+fn user_code_key(service: &Service, user_code: &str) -> String {
+    format!("{}-{user_code}", service.id)
+}
+
+fn service_attempts_key(service: &Service) -> String {
+    format!("mist-device-attempts-{}", service.id)
+}
+
+/// Clients are counted by the address [`Client`] settled on, which only comes from
+/// `x-forwarded-for` behind a trusted proxy, so it can't be made up to dodge the limit.
+fn client_attempts_key(service: &Service, client: &Client) -> Option<String> {
+    let ip = client.ip.as_ref()?;
+
+    Some(format!("mist-device-attempts-{}-{ip}", service.id))
+}
+
+fn user_code() -> String {
+    (0..USER_CODE_LENGTH)
+        .map(|_| USER_CODE_ALPHABET[OsRng.gen_range(0..USER_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Users type codes in however they like, so ignore case, dashes and spaces.
+fn normalize(user_code: &str) -> String {
+    user_code
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|char| char.to_ascii_uppercase())
+        .collect()
+}
+
+/// Split in two, so it's easier to read out.
+fn display(user_code: &str) -> String {
+    let (start, end) = user_code.split_at(USER_CODE_LENGTH / 2);
+
+    format!("{start}-{end}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_codes_are_easy_to_read() {
+        let code = user_code();
+
+        assert_eq!(code.len(), USER_CODE_LENGTH);
+        assert!(code.bytes().all(|char| USER_CODE_ALPHABET.contains(&char)));
+        assert_eq!(display("BCDFGHJK"), "BCDF-GHJK");
+    }
+
+    #[test]
+    fn user_codes_are_forgiving() {
+        assert_eq!(normalize(" bcdf-GHJK "), "BCDFGHJK");
+        assert_eq!(normalize(&display("BCDFGHJK")), "BCDFGHJK");
+    }
+}
@@ -0,0 +1,272 @@
+// Binding tokens to a key the service holds (DPoP).
+//
+// A service using the token-based flow can send a `DPoP` proof, a JWT signed with a
+// key of its own, when swapping a code or refresh token. The access token it gets is
+// then bound to the key's thumbprint (`cnf.jkt`), and is only any use alongside a
+// fresh proof from the same key: at `/userinfo`, with the `DPoP` scheme, and at
+// introspection. Refresh tokens are bound the same way.
+//
+// Each proof is only good for the request it was made for (`htm` and `htu`), for a
+// short while (`iat`), and only once (`jti`).
+//
+// See: https://www.rfc-editor.org/rfc/rfc9449
+// -------------------------------------------------------------------------------
+
+use base64::prelude::*;
+use chrono::Utc;
+use fred::types::Expiration;
+use http::{HeaderMap, Method, Uri};
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, Jwk},
+    Algorithm, DecodingKey, Validation,
+};
+use mist_common::{redis::TypedRedis, Result};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::state::AuthnState;
+
+const HEADER: &str = "DPoP";
+
+/// How far a proof's `iat` can be from now, either way, in seconds.
+const PROOF_MAX_AGE: i64 = 60;
+
+/// Only asymmetric keys prove anything.
+const ALGORITHMS: &[Algorithm] = &[
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::EdDSA,
+];
+
+/// Proofs that have already been used, by key thumbprint and `jti`.
+static USED_PROOF: TypedRedis<()> = TypedRedis::new("mist-dpop-proof");
+
+/// See: https://www.rfc-editor.org/rfc/rfc9449#section-4.2
+#[derive(Deserialize)]
+struct ProofClaims {
+    jti: String,
+    htm: String,
+    htu: String,
+    iat: i64,
+    /// The hash of the access token the proof is sent with.
+    ath: Option<String>,
+}
+
+pub(crate) enum Proof {
+    Missing,
+    Invalid,
+    /// The thumbprint of the key the proof was signed with.
+    Valid(String),
+}
+
+/// Check the request's proof, if it has one, using it up. Proofs sent with an access
+/// token have to be for that token.
+pub(crate) async fn check(
+    state: &AuthnState,
+    headers: &HeaderMap,
+    method: &Method,
+    uri: &Uri,
+    access_token: Option<&str>,
+) -> Result<Proof> {
+    let mut proofs = headers.get_all(HEADER).iter();
+
+    let (Some(proof), None) = (proofs.next(), proofs.next()) else {
+        return Ok(match headers.contains_key(HEADER) {
+            true => Proof::Invalid,
+            false => Proof::Missing,
+        });
+    };
+
+    let Ok(proof) = proof.to_str() else {
+        return Ok(Proof::Invalid);
+    };
+
+    let htu = format!("{}{}", state.env.authn_url, uri.path());
+
+    let Some((jkt, jti)) = read(proof, method, &htu, access_token, Utc::now().timestamp()) else {
+        return Ok(Proof::Invalid);
+    };
+
+    let unused = USED_PROOF
+        .set_nx(
+            &state.redis,
+            &format!("{jkt}:{jti}"),
+            &(),
+            Expiration::EX(PROOF_MAX_AGE * 2),
+        )
+        .await?;
+
+    Ok(match unused {
+        true => Proof::Valid(jkt),
+        false => Proof::Invalid,
+    })
+}
+
+/// The hash of an access token, as a proof's `ath`.
+pub(crate) fn token_hash(access_token: &str) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(access_token))
+}
+
+/// Verify the proof, returning its key's thumbprint and its `jti`.
+fn read(
+    proof: &str,
+    method: &Method,
+    htu: &str,
+    access_token: Option<&str>,
+    now: i64,
+) -> Option<(String, String)> {
+    let header = jsonwebtoken::decode_header(proof).ok()?;
+
+    if header.typ.as_deref() != Some("dpop+jwt") || !ALGORITHMS.contains(&header.alg) {
+        return None;
+    }
+
+    let jwk = header.jwk?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+
+    let claims =
+        jsonwebtoken::decode::<ProofClaims>(proof, &DecodingKey::from_jwk(&jwk).ok()?, &validation)
+            .ok()?
+            .claims;
+
+    let fresh = (now - claims.iat).abs() <= PROOF_MAX_AGE;
+    let for_token = claims.ath == access_token.map(token_hash);
+
+    if claims.htm != method.as_str() || claims.htu != htu || !fresh || !for_token {
+        return None;
+    }
+
+    Some((thumbprint(&jwk)?, claims.jti))
+}
+
+/// See: https://www.rfc-editor.org/rfc/rfc7638
+fn thumbprint(jwk: &Jwk) -> Option<String> {
+    // The required members only, in lexicographic order.
+    let members = match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(params) => {
+            json!({ "crv": params.curve, "kty": "EC", "x": params.x, "y": params.y })
+        }
+        AlgorithmParameters::RSA(params) => {
+            json!({ "e": params.e, "kty": "RSA", "n": params.n })
+        }
+        AlgorithmParameters::OctetKeyPair(params) => {
+            json!({ "crv": params.curve, "kty": "OKP", "x": params.x })
+        }
+        AlgorithmParameters::OctetKey(_) => return None,
+    };
+
+    Some(BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(members.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+    use rcgen::{KeyPair, PKCS_ECDSA_P256_SHA256};
+    use serde_json::Value;
+
+    use super::*;
+
+    const HTU: &str = "https://authn.example.com/userinfo";
+    const NOW: i64 = 1_700_000_000;
+
+    fn key() -> (KeyPair, Jwk) {
+        let key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        // Uncompressed: 0x04, then x and y.
+        let point = key.public_key_raw();
+        let jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": BASE64_URL_SAFE_NO_PAD.encode(&point[1..33]),
+            "y": BASE64_URL_SAFE_NO_PAD.encode(&point[33..]),
+        }))
+        .unwrap();
+
+        (key, jwk)
+    }
+
+    fn proof(key: &KeyPair, jwk: &Jwk, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.typ = Some("dpop+jwt".into());
+        header.jwk = Some(jwk.clone());
+
+        jsonwebtoken::encode(
+            &header,
+            &claims,
+            &EncodingKey::from_ec_pem(key.serialize_pem().as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn thumbprints_keys() {
+        // See: https://www.rfc-editor.org/rfc/rfc7638#section-3.1
+        let jwk = serde_json::from_value(json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29",
+        }))
+        .unwrap();
+
+        assert_eq!(
+            thumbprint(&jwk).unwrap(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn reads_proofs() {
+        let (key, jwk) = key();
+        let proof = proof(
+            &key,
+            &jwk,
+            json!({ "jti": "1", "htm": "GET", "htu": HTU, "iat": NOW, "ath": token_hash("token") }),
+        );
+
+        let (jkt, jti) = read(&proof, &Method::GET, HTU, Some("token"), NOW + 5).unwrap();
+
+        assert_eq!(jkt, thumbprint(&jwk).unwrap());
+        assert_eq!(jti, "1");
+    }
+
+    #[test]
+    fn rejects_proofs_for_other_requests() {
+        let (key, jwk) = key();
+        let proof = proof(
+            &key,
+            &jwk,
+            json!({ "jti": "1", "htm": "GET", "htu": HTU, "iat": NOW, "ath": token_hash("token") }),
+        );
+
+        assert!(read(&proof, &Method::POST, HTU, Some("token"), NOW).is_none());
+        assert!(read(
+            &proof,
+            &Method::GET,
+            "https://other.example.com",
+            Some("token"),
+            NOW
+        )
+        .is_none());
+        assert!(read(&proof, &Method::GET, HTU, Some("other"), NOW).is_none());
+        assert!(read(&proof, &Method::GET, HTU, None, NOW).is_none());
+        assert!(read(
+            &proof,
+            &Method::GET,
+            HTU,
+            Some("token"),
+            NOW + PROOF_MAX_AGE + 1
+        )
+        .is_none());
+    }
+}
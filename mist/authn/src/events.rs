@@ -1,11 +1,96 @@
-use derive_more::Display;
+use async_nats::Client;
+use serde::{Deserialize, Serialize};
 
-#[derive(Display)]
+use crate::session::{AuthAction, AuthState};
+
+/// Progress through the auth flow, as sent to the user's browser.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
 pub(crate) enum Event {
-    #[display("mist-redirect-after-auth")]
-    Redirect,
+    /// Waiting on a scan, with the action the current auth request is for.
+    Waiting {
+        action: AuthAction,
+    },
+    Scanned,
+    Verifying,
+    Registering,
+    SigningIn,
+    Authenticated,
+    Rejected {
+        code: String,
+        reason: String,
+    },
+    Expired,
+}
+
+impl From<&AuthState> for Event {
+    fn from(state: &AuthState) -> Self {
+        match state {
+            AuthState::Authenticating { action, .. } => Self::Waiting {
+                action: action.clone(),
+            },
+            AuthState::Scanned { .. } => Self::Scanned,
+            AuthState::Verifying { .. } => Self::Verifying,
+            AuthState::Registering { .. } | AuthState::Accepted => Self::Registering,
+            AuthState::SigningIn { .. } => Self::SigningIn,
+            AuthState::Authenticated { .. } => Self::Authenticated,
+            AuthState::Rejected { code, reason } => Self::Rejected {
+                code: code.clone(),
+                reason: reason.clone(),
+            },
+            AuthState::Expired => Self::Expired,
+        }
+    }
 }
 
-pub(crate) fn get_event_key(event: &Event, id: &str) -> String {
-    format!("{}.{}", event, id)
+pub(crate) fn get_event_key(session_id: &str) -> String {
+    format!("mist-auth-progress.{session_id}")
+}
+
+/// Let anyone waiting on the session know it's moved on.
+///
+/// Browsers can always fall back to polling, so failing to publish isn't fatal.
+pub(crate) async fn publish(nats: &Client, session_id: &str, event: &Event) {
+    let payload = match serde_json::to_vec(event) {
+        Ok(payload) => payload,
+        Err(err) => return tracing::error!(?err, "failed to serialize event"),
+    };
+
+    if let Err(err) = nats
+        .publish(get_event_key(session_id), payload.into())
+        .await
+    {
+        tracing::error!(?err, "failed to publish event");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn serializes_as_tagged_json() {
+        let rejected = Event::Rejected {
+            code: "under_age".into(),
+            reason: "You must be 18 or over.".into(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&rejected).unwrap(),
+            json!({ "status": "rejected", "code": "under_age", "reason": "You must be 18 or over." })
+        );
+        assert_eq!(
+            serde_json::to_value(Event::Scanned).unwrap(),
+            json!({ "status": "scanned" })
+        );
+        assert_eq!(
+            serde_json::to_value(Event::Waiting {
+                action: AuthAction::Up
+            })
+            .unwrap(),
+            json!({ "status": "waiting", "action": "up" })
+        );
+    }
 }
@@ -1,20 +1,82 @@
 mod complete_registration;
+mod continue_to_service;
+mod device_code;
+mod end_session;
+mod give_consent;
+mod introspect;
+mod issuance;
 mod kill_session;
+mod refresh_request;
+mod resume;
+mod sso_consent;
 mod start_auth;
-mod verify_response;
+mod status;
+mod token;
+mod userinfo;
+mod verify_device;
+pub(crate) mod verify_response;
 mod wait_for_completion;
 mod whoami;
 
-use axum::{routing, Router};
+use axum::{
+    response::{IntoResponse, Response},
+    routing, Json, Router,
+};
+use http::StatusCode;
+use mist_db::models::service::ServiceId;
+use serde::Deserialize;
+use serde_json::json;
 
 use crate::state::AuthnState;
 
+/// For endpoints the browser calls during the auth flow, which have to say whose
+/// session they mean.
+#[derive(Deserialize)]
+pub(crate) struct ServiceQuery {
+    pub(crate) service_id: ServiceId,
+}
+
 pub(crate) fn router() -> Router<AuthnState> {
     Router::new()
         .route("/:service_name/:action", routing::get(start_auth::handler))
         .route("/:service_name/out", routing::post(kill_session::handler))
+        .route("/:service_name/sso", routing::post(sso_consent::handler))
+        .route(
+            "/:service_name/consent",
+            routing::get(give_consent::get_handler).post(give_consent::post_handler),
+        )
+        .route(
+            "/:service_name/logout",
+            routing::get(end_session::get_handler).post(end_session::post_handler),
+        )
         .route("/waiting", routing::get(wait_for_completion::handler))
+        .route("/status", routing::get(status::handler))
+        .route("/refresh", routing::post(refresh_request::handler))
         .route("/auth", routing::post(verify_response::handler))
+        .route("/resume", routing::get(resume::handler))
+        .route("/continue", routing::get(continue_to_service::handler))
         .route("/complete", routing::post(complete_registration::handler))
-        .route("/whoami", routing::get(whoami::handler))
+        .route("/:service_name/whoami", routing::get(whoami::handler))
+        .route("/:service_name/token", routing::post(token::handler))
+        .route(
+            "/:service_name/device/code",
+            routing::post(device_code::handler),
+        )
+        .route(
+            "/:service_name/device",
+            routing::get(verify_device::get_handler).post(verify_device::post_handler),
+        )
+        .route(
+            "/:service_name/introspect",
+            routing::post(introspect::handler),
+        )
+        .route("/userinfo", routing::get(userinfo::handler))
+        .merge(issuance::router())
+}
+
+/// An OAuth error response.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc6749#section-5.2
+pub(crate) fn oauth_error(status: StatusCode, error: &str) -> Response {
+    (status, Json(json!({ "error": error }))).into_response()
 }
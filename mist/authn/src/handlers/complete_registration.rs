@@ -1,102 +1,90 @@
 use axum::{extract::State, response::IntoResponse, Json};
-use eyre::eyre;
-use fred::prelude::*;
-use http::StatusCode;
+use http::{HeaderMap, StatusCode};
 use mist_common::Result;
-use mist_db::models::{identifier::CreateIdentifier, user::CreateUser};
 use serde::Deserialize;
+use serde_json::{Map, Value};
 
 use crate::{
-    events::{get_event_key, Event},
-    session::{AuthSession, AuthState, SessionId, AUTH_SESSION},
+    registration,
+    session::{machine, AuthState, SessionId},
+    sign_in,
     state::AuthnState,
+    utils::client_auth,
 };
 
 #[derive(Deserialize)]
 pub(crate) struct Payload {
     session_id: SessionId,
+    /// Set when the service doesn't want to register, or sign in, the user.
+    rejection: Option<Rejection>,
+    /// Extra claims to attach to the user's session.
+    #[serde(default)]
+    claims: Map<String, Value>,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct Rejection {
+    /// A machine-readable code, like `under_age`.
+    code: String,
+    /// Shown to the user on the waiting page.
+    message: String,
+}
+
+/// The service's decision on a registration or sign-in it was asked about.
 pub(crate) async fn handler(
+    headers: HeaderMap,
     State(state): State<AuthnState>,
     Json(payload): Json<Payload>,
 ) -> Result<impl IntoResponse> {
     // Get session information.
     // ------------------------
 
-    let session = AUTH_SESSION
-        .get(&state.redis, &payload.session_id.to_string())
-        .await?;
-
-    let AuthState::Registering { identifier } = &session.state else {
-        return Ok(StatusCode::NOT_FOUND.into_response());
-    };
+    let session_id = payload.session_id.to_string();
+    let session = machine::load(&state, &session_id).await?;
 
-    // Create the user and associated DID if they don't already exist.
-    // ---------------------------------------------------------------
+    // Only the service the session is for gets to decide on it, or add claims to it.
+    // ------------------------------------------------------------------------------
 
-    let authenticating_session = AUTH_SESSION
-        .get(&state.redis, &payload.session_id.to_string())
-        .await?;
+    if !client_auth::is_service(&state, &session.service_id, &headers).await? {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
 
-    let existing = state.repos.identifiers.get_by_value(identifier).await;
+    match session.state {
+        AuthState::Registering { .. } => {}
+        AuthState::SigningIn { .. } => {
+            match payload.rejection {
+                Some(rejection) => {
+                    machine::reject(&state, &session_id, &rejection.code, &rejection.message)
+                        .await?
+                }
+                None => sign_in::accept(&state, &session_id, &session, payload.claims).await?,
+            }
 
-    if existing.is_ok() {
-        return Err(eyre!("user already exists").into());
+            return Ok(StatusCode::OK.into_response());
+        }
+        _ => return Ok(StatusCode::NOT_FOUND.into_response()),
     }
 
-    let user = state
-        .repos
-        .users
-        .create(
-            &CreateUser::builder()
-                .id(authenticating_session.user_id)
-                .service_id(authenticating_session.service_id)
-                .build(),
-        )
-        .await?;
-
-    let identifier = state
-        .repos
-        .identifiers
-        .create(
-            &CreateIdentifier::builder()
-                .value(identifier.clone())
-                .user_id(authenticating_session.user_id)
-                .build(),
-        )
-        .await?;
+    // Let the user know if the service turned them down.
+    // --------------------------------------------------
 
-    // Complete the registration process.
-    // ----------------------------------
+    if let Some(rejection) = payload.rejection {
+        let service = state.repos.services.get(&session.service_id).await?;
 
-    // Update the user session.
-    AUTH_SESSION
-        .set(
-            &state.redis,
-            &payload.session_id.to_string(),
-            &AuthSession {
-                service_id: authenticating_session.service_id,
-                user_id: user.id,
-                state: AuthState::Authenticated {
-                    identifier_id: identifier.id,
-                },
-            },
-            Expiration::EX(60 * 60 * 8),
+        registration::reject(
+            &state,
+            &service,
+            &session_id,
+            &session,
+            rejection.code,
+            rejection.message,
         )
         .await?;
 
-    // Send an event to the user's browser to let it know authentication is complete.
-    //
-    // This event will be picked up by an event listener in the browser listening to
-    // the `waiting_for_completion` handler.
-    state
-        .nats
-        .publish(
-            get_event_key(&Event::Redirect, &payload.session_id.to_string()),
-            "".into(),
-        )
-        .await?;
+        return Ok(StatusCode::OK.into_response());
+    }
+
+    registration::accept(&state, &session_id, &session, None, payload.claims).await?;
 
     Ok(StatusCode::OK.into_response())
 }
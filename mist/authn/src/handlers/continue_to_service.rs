@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Redirect},
+};
+use http::StatusCode;
+use mist_common::Result;
+use tower_cookies::Cookies;
+
+use crate::{
+    device,
+    handlers::ServiceQuery,
+    session::{self, machine, AuthState},
+    state::AuthnState,
+    utils::redirects,
+};
+
+/// Where the browser goes once the session is signed in, to be sent on to the service.
+pub(crate) async fn handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Query(query): Query<ServiceQuery>,
+) -> Result<impl IntoResponse> {
+    let Some(session_id) = session::from_cookies(&cookies, &query.service_id) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let Ok(session) = machine::load(&state, &session_id.to_string()).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    if !matches!(session.state, AuthState::Authenticated { .. }) {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    let service = state.repos.services.get(&session.service_id).await?;
+
+    if session.device {
+        return Ok(device::finish(&state, &cookies, &service, &session_id));
+    }
+
+    let url = redirects::after_sign_in(&state, &service, &session_id, &session).await?;
+
+    Ok(Redirect::to(&url).into_response())
+}
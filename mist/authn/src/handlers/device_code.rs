@@ -0,0 +1,25 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+use mist_common::Result;
+
+use super::oauth_error;
+use crate::{device, state::AuthnState};
+
+/// Start signing in on a device without a browser. Devices can't keep a secret, so
+/// this needs no API key.
+pub(crate) async fn handler(
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    if !service.token_flow {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "unauthorized_client"));
+    }
+
+    Ok(Json(device::start(&state, &service).await?).into_response())
+}
@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Form,
+};
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::redirect_uri::RedirectUriKind;
+use reqwest::Url;
+use serde::Deserialize;
+use tower_cookies::Cookies;
+
+use crate::{
+    logout,
+    session::{self, AUTH_SESSION, SID},
+    sso,
+    state::AuthnState,
+    tokens,
+    utils::{client_auth, redirects},
+};
+
+/// See: https://openid.net/specs/openid-connect-rpinitiated-1_0.html#RPLogout
+#[derive(Deserialize)]
+pub(crate) struct EndSessionParams {
+    /// An ID token we issued to the service, saying which session to end.
+    id_token_hint: Option<String>,
+    post_logout_redirect_uri: Option<String>,
+    /// Passed back to the service on the redirect.
+    state: Option<String>,
+}
+
+pub(crate) async fn get_handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    Query(params): Query<EndSessionParams>,
+) -> Result<Response> {
+    end_session(&state, &cookies, &service_name, params).await
+}
+
+pub(crate) async fn post_handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    Form(params): Form<EndSessionParams>,
+) -> Result<Response> {
+    end_session(&state, &cookies, &service_name, params).await
+}
+
+/// Log the user out of the service, and every other service they signed in to
+/// alongside it through SSO.
+async fn end_session(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service_name: &str,
+    params: EndSessionParams,
+) -> Result<Response> {
+    let service = state.repos.services.get_by_name(service_name).await?;
+
+    // Check the hint is an ID token we gave this service.
+    // ---------------------------------------------------
+
+    let hint = match &params.id_token_hint {
+        Some(token) => {
+            let key = client_auth::api_key(state, &service.id).await?;
+
+            match tokens::verify_id_token(state, &key, &service.id, token) {
+                Ok(claims) => Some(claims),
+                Err(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+            }
+        }
+        None => None,
+    };
+
+    // Only redirect somewhere the service registered, and only when it's the service
+    // asking.
+    // -------------------------------------------------------------------------------
+
+    let allowed = match &params.post_logout_redirect_uri {
+        Some(uri) if hint.is_some() => {
+            redirects::is_allowed(state, &service, &RedirectUriKind::PostLogout, uri).await?
+        }
+        _ => false,
+    };
+
+    let redirect_url = match &params.post_logout_redirect_uri {
+        Some(uri) if allowed => {
+            let mut url = Url::parse(uri)?;
+
+            if let Some(value) = &params.state {
+                url.query_pairs_mut().append_pair("state", value);
+            }
+
+            url.into()
+        }
+        Some(_) => return Ok(StatusCode::BAD_REQUEST.into_response()),
+        None => service.logout_url.clone(),
+    };
+
+    // End the service's sessions: the browser's, and the one the hint is for.
+    // -----------------------------------------------------------------------
+
+    let mut session_ids = Vec::from_iter(session::from_cookies(cookies, &service.id));
+
+    if let Some(hint) = &hint {
+        // The session may have already ended, in which case there's nothing to do.
+        match SID.get(&state.redis, &hint.sid).await {
+            Ok(session_id) if !session_ids.contains(&session_id) => session_ids.push(session_id),
+            Ok(_) => {}
+            Err(err) if err.is_not_found() => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut frontchannel_urls = vec![];
+
+    for session_id in session_ids {
+        let Ok(session) = AUTH_SESSION
+            .get(&state.redis, &session_id.to_string())
+            .await
+        else {
+            continue;
+        };
+
+        if session.service_id != service.id {
+            continue;
+        }
+
+        frontchannel_urls
+            .extend(logout::end(state, cookies, &service, &session_id, &session).await?);
+    }
+
+    // Then the sessions for services signed in to alongside it.
+    // ---------------------------------------------------------
+
+    for (sibling, session_id, session) in sso::sibling_sessions(state, cookies, &service).await? {
+        frontchannel_urls
+            .extend(logout::end(state, cookies, &sibling, &session_id, &session).await?);
+    }
+
+    Ok(logout::finish(&service, &frontchannel_urls, &redirect_url))
+}
@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect, Response},
+};
+use mist_common::Result;
+use tower_cookies::Cookies;
+
+use crate::{
+    consent,
+    session::{self, machine, AuthAction, AuthSession},
+    state::AuthnState,
+    utils::auth_request,
+    views,
+};
+
+/// Ask the user about the session they already have, which happens when signing in
+/// with a wallet we don't know switches it over to signing up.
+pub(crate) async fn get_handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+) -> Result<Response> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    let start_over =
+        Redirect::to(&format!("{}/{}/up", state.env.authn_url, service.name)).into_response();
+
+    let Some(session_id) = session::from_cookies(&cookies, &service.id) else {
+        return Ok(start_over);
+    };
+
+    match machine::load(&state, &session_id.to_string()).await {
+        Ok(session) if consent::is_needed(&service, &session) => {
+            consent::ask(&state, &service).await
+        }
+        _ => Ok(start_over),
+    }
+}
+
+/// The user agreed to share their data, so show them the QR code to sign up.
+pub(crate) async fn post_handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    let start_over =
+        Redirect::to(&format!("{}/{}/up", state.env.authn_url, service.name)).into_response();
+
+    let Some(session_id) = session::from_cookies(&cookies, &service.id) else {
+        return Ok(start_over);
+    };
+
+    let Ok(session) = machine::load(&state, &session_id.to_string()).await else {
+        return Ok(start_over);
+    };
+
+    // Anything else, like agreeing twice, means starting over.
+    if !consent::is_needed(&service, &session) {
+        return Ok(start_over);
+    }
+
+    consent::record(&state, &service, &session_id, &session).await?;
+
+    machine::replace(
+        &state,
+        &session_id.to_string(),
+        &session,
+        AuthSession {
+            consented: true,
+            ..session.clone()
+        },
+    )
+    .await?;
+
+    let request = auth_request::create(&state, &service, &session_id, &AuthAction::Up).await?;
+
+    Ok(
+        views::scan::view(&service, &state.env.authn_url, &AuthAction::Up, &request)
+            .into_response(),
+    )
+}
@@ -0,0 +1,141 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Form, Json,
+};
+use http::{HeaderMap, Method, StatusCode, Uri};
+use mist_common::Result;
+use mist_db::models::{service::Service, user::UserId};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::oauth_error;
+use crate::{
+    dpop::{self, Proof},
+    session::{machine, AuthSession, AuthState, SessionId},
+    state::AuthnState,
+    tokens,
+    utils::client_auth,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct IntrospectBody {
+    /// A session id, as in the service's cookie, or an access token.
+    token: String,
+}
+
+/// See: https://www.rfc-editor.org/rfc/rfc7662#section-2.2
+#[derive(Default, Serialize)]
+pub(crate) struct Response {
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    token_type: Option<&'static str>,
+    /// The user's id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<UserId>,
+    /// The DID the user signed in with, or under pairwise subjects, what stands in for
+    /// it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<String>,
+    /// When the token stops working if nothing else happens, as a unix timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<i64>,
+    /// Extra claims the service attached when signing the user in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    claims: Option<Map<String, Value>>,
+    /// The key the token is bound to, if it's bound to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cnf: Option<Confirmation>,
+}
+
+/// See: https://www.rfc-editor.org/rfc/rfc9449#section-6.2
+#[derive(Serialize)]
+pub(crate) struct Confirmation {
+    jkt: String,
+}
+
+/// Tell the service's backend whether a session or access token is still good, and
+/// who it's for, without needing the user's cookie. Only the service's own sessions
+/// are ever active, and asking doesn't count as activity on them.
+pub(crate) async fn handler(
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    Form(body): Form<IntrospectBody>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    if !client_auth::is_service(&state, &service.id, &headers).await? {
+        return Ok(oauth_error(StatusCode::UNAUTHORIZED, "invalid_client"));
+    }
+
+    // Work out what the token is.
+    // ---------------------------
+
+    let found = match body.token.parse::<SessionId>() {
+        Ok(session_id) => tokens::signed_in(&state, &session_id)
+            .await
+            .map(|session| ("session", machine::ends_at(&session), None, session)),
+        Err(_) => tokens::access_token(&state, &body.token)
+            .await
+            .map(|(access_token, session)| {
+                let expires_at = access_token.expires_at.min(machine::ends_at(&session));
+
+                ("access_token", expires_at, access_token.jkt, session)
+            }),
+    };
+
+    let Some((token_type, expires_at, jkt, session)) = found else {
+        return Ok(Json(Response::default()).into_response());
+    };
+
+    if session.service_id != service.id {
+        return Ok(Json(Response::default()).into_response());
+    }
+
+    // Tokens bound to a key are only any use with a proof from it.
+    if let Some(jkt) = &jkt {
+        let proof = dpop::check(&state, &headers, &Method::POST, &uri, Some(&body.token)).await?;
+
+        if !matches!(proof, Proof::Valid(proven) if proven == *jkt) {
+            return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_dpop_proof"));
+        }
+    }
+
+    let response = describe(&state, &service, token_type, expires_at, session).await?;
+
+    Ok(Json(Response {
+        cnf: jkt.map(|jkt| Confirmation { jkt }),
+        ..response
+    })
+    .into_response())
+}
+
+async fn describe(
+    state: &AuthnState,
+    service: &Service,
+    token_type: &'static str,
+    expires_at: i64,
+    session: AuthSession,
+) -> Result<Response> {
+    let AuthState::Authenticated {
+        identifier_id,
+        claims,
+    } = session.state
+    else {
+        return Ok(Response::default());
+    };
+
+    let identifier = state.repos.identifiers.get(&identifier_id).await?;
+
+    Ok(Response {
+        active: true,
+        token_type: Some(token_type),
+        sub: Some(session.user_id),
+        identifier: Some(service.subject(&identifier.value)?),
+        exp: Some(expires_at),
+        claims: Some(claims),
+        cnf: None,
+    })
+}
@@ -0,0 +1,47 @@
+mod authorize;
+mod credential;
+mod metadata;
+mod offer;
+mod token;
+
+use axum::{routing, Router};
+
+use super::oauth_error;
+use crate::state::AuthnState;
+
+pub(crate) fn router() -> Router<AuthnState> {
+    Router::new()
+        .route(
+            "/issuers/:service_id/.well-known/openid-credential-issuer",
+            routing::get(metadata::credential_issuer_handler),
+        )
+        .route(
+            "/.well-known/openid-credential-issuer/issuers/:service_id",
+            routing::get(metadata::credential_issuer_handler),
+        )
+        .route(
+            "/issuers/:service_id/.well-known/oauth-authorization-server",
+            routing::get(metadata::authorization_server_handler),
+        )
+        .route(
+            "/.well-known/oauth-authorization-server/issuers/:service_id",
+            routing::get(metadata::authorization_server_handler),
+        )
+        .route(
+            "/issuers/:service_id/offers/:offer_id",
+            routing::get(offer::view_handler),
+        )
+        .route(
+            "/issuers/:service_id/offers/:offer_id/offer",
+            routing::get(offer::offer_handler),
+        )
+        .route(
+            "/issuers/:service_id/authorize",
+            routing::get(authorize::handler),
+        )
+        .route("/issuers/:service_id/token", routing::post(token::handler))
+        .route(
+            "/issuers/:service_id/credential",
+            routing::post(credential::handler),
+        )
+}
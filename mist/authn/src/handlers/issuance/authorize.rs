@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Redirect},
+};
+use fred::types::Expiration;
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::{
+    credential_offer::{CredentialOfferId, OfferGrant},
+    redirect_uri::RedirectUriKind,
+    service::ServiceId,
+};
+use openidconnect::CsrfToken;
+use reqwest::Url;
+use serde::Deserialize;
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use super::oauth_error;
+use crate::{
+    issuance::{self, IssuanceCode, EXPIRES_IN, ISSUANCE_CODE},
+    session::{self, AuthState, AUTH_SESSION},
+    state::AuthnState,
+    utils::redirects,
+    views,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AuthorizeQuery {
+    response_type: String,
+    redirect_uri: String,
+    code_challenge: String,
+    code_challenge_method: String,
+    issuer_state: Option<String>,
+    state: Option<String>,
+}
+
+/// The authorization code flow, for offers only the user they're for can redeem.
+///
+/// The user proves who they are by already being signed in to the service with
+/// Mist, in the browser the wallet opened.
+pub(crate) async fn handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+    Query(query): Query<AuthorizeQuery>,
+) -> Result<impl IntoResponse> {
+    if query.response_type != "code" || query.code_challenge_method != "S256" {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    }
+
+    // We only issue what the service has offered, so the wallet has to tell us which offer.
+    let Some(offer_id) = query
+        .issuer_state
+        .as_deref()
+        .and_then(|id| Uuid::from_str(id).ok())
+        .map(CredentialOfferId)
+    else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    };
+
+    let offer = state.repos.credential_offers.get(&offer_id).await?;
+
+    let Ok(offer) = issuance::redeemable_offer(&state, &path.service_id, offer).await else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    };
+
+    if offer.grant_type != OfferGrant::AuthorizationCode {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "unauthorized_client"));
+    }
+
+    let service = state.repos.services.get(&path.service_id).await?;
+
+    // Only send codes back to wallets the service registered.
+    if !redirects::is_allowed(
+        &state,
+        &service,
+        &RedirectUriKind::Wallet,
+        &query.redirect_uri,
+    )
+    .await?
+    {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    }
+
+    // Make sure the offer is for the signed in user.
+    // ----------------------------------------------
+
+    let session = match session::from_cookies(&cookies, &service.id) {
+        Some(session_id) => AUTH_SESSION
+            .get(&state.redis, &session_id.to_string())
+            .await
+            .ok(),
+        None => None,
+    };
+
+    let Some(session) = session.filter(|session| {
+        matches!(session.state, AuthState::Authenticated { .. }) && session.service_id == service.id
+    }) else {
+        return Ok(views::sign_in_first::view(&service, &state.env.authn_url).into_response());
+    };
+
+    if offer.user_id != Some(session.user_id.0) {
+        return Ok(StatusCode::FORBIDDEN.into_response());
+    }
+
+    // Send the wallet back with a code it can exchange for an access token.
+    // ---------------------------------------------------------------------
+
+    let code = CsrfToken::new_random_len(32).secret().clone();
+
+    ISSUANCE_CODE
+        .set(
+            &state.redis,
+            &code,
+            &IssuanceCode {
+                offer_id: offer.id,
+                redirect_uri: query.redirect_uri.clone(),
+                code_challenge: query.code_challenge,
+            },
+            Expiration::EX(EXPIRES_IN),
+        )
+        .await?;
+
+    let mut redirect_uri = Url::parse(&query.redirect_uri)?;
+    redirect_uri.query_pairs_mut().append_pair("code", &code);
+
+    if let Some(state) = &query.state {
+        redirect_uri.query_pairs_mut().append_pair("state", state);
+    }
+
+    Ok(Redirect::to(redirect_uri.as_str()).into_response())
+}
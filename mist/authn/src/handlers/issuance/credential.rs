@@ -0,0 +1,215 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use base64::prelude::*;
+use chrono::{Duration, Utc};
+use eyre::{eyre, OptionExt};
+use http::{header, HeaderMap, StatusCode};
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use openidconnect::CsrfToken;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ssi::jwk::{Algorithm, JWK};
+
+use super::oauth_error;
+use crate::{
+    issuance::{self, IssuanceToken, IssuerKey, EXPIRES_IN, ISSUANCE_TOKEN},
+    state::AuthnState,
+    utils::did,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CredentialBody {
+    format: Option<String>,
+    credential_identifier: Option<String>,
+    credential_definition: Option<CredentialDefinition>,
+    proof: Option<Proof>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CredentialDefinition {
+    #[serde(rename = "type")]
+    types: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Proof {
+    proof_type: String,
+    jwt: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Response {
+    credential: String,
+    c_nonce: String,
+    c_nonce_expires_in: i64,
+}
+
+pub(crate) async fn handler(
+    headers: HeaderMap,
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+    Json(body): Json<CredentialBody>,
+) -> Result<impl IntoResponse> {
+    // Find the offer the access token was issued for.
+    // -----------------------------------------------
+
+    let Some(access_token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Ok(oauth_error(StatusCode::UNAUTHORIZED, "invalid_token"));
+    };
+
+    let Ok(token) = ISSUANCE_TOKEN.get(&state.redis, access_token).await else {
+        return Ok(oauth_error(StatusCode::UNAUTHORIZED, "invalid_token"));
+    };
+
+    let offer = state.repos.credential_offers.get(&token.offer_id).await?;
+    let configuration = state
+        .repos
+        .credential_configurations
+        .get(&offer.configuration_id.into())
+        .await?;
+
+    // Make sure they're asking for the credential we offered.
+    // -------------------------------------------------------
+
+    let is_offered_credential = body.format.as_deref().unwrap_or("jwt_vc_json") == "jwt_vc_json"
+        && body
+            .credential_identifier
+            .as_ref()
+            .is_none_or(|id| id == &configuration.name)
+        && body
+            .credential_definition
+            .as_ref()
+            .is_none_or(|definition| definition.types.contains(&configuration.name));
+
+    if !is_offered_credential {
+        return Ok(oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_credential_type",
+        ));
+    }
+
+    // Verify the wallet holds the key the credential will be bound to.
+    // ----------------------------------------------------------------
+
+    let issuer_url = issuance::issuer_url(&state.env.authn_url, &path.service_id);
+
+    let Some(proof) = body.proof.filter(|proof| proof.proof_type == "jwt") else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_proof"));
+    };
+
+    let Ok(holder) = verify_proof(&state, &proof.jwt, &issuer_url, &token).await else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_proof"));
+    };
+
+    // Issue the credential.
+    // ---------------------
+
+    // Offers can only be redeemed once, even if the wallet asks again.
+    let Ok(offer) = state.repos.credential_offers.redeem(&offer.id).await else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_token"));
+    };
+
+    ISSUANCE_TOKEN.del(&state.redis, access_token).await?;
+
+    let issuer = IssuerKey::preferred(&state, &path.service_id).await?;
+
+    let now = Utc::now();
+    let expires_at = configuration
+        .valid_for
+        .map(|seconds| now + Duration::seconds(seconds.into()));
+
+    let mut subject = offer.claims.0.clone();
+    subject.insert("id".into(), json!(holder));
+
+    let mut vc = json!({
+        "@context": ["https://www.w3.org/2018/credentials/v1"],
+        "id": format!("urn:uuid:{}", offer.id),
+        "type": ["VerifiableCredential", configuration.name],
+        "issuer": issuer.did,
+        "issuanceDate": now.to_rfc3339(),
+        "credentialSubject": subject,
+    });
+
+    let mut claims = json!({
+        "iss": issuer.did,
+        "sub": holder,
+        "jti": format!("urn:uuid:{}", offer.id),
+        "iat": now.timestamp(),
+        "nbf": now.timestamp(),
+    });
+
+    if let Some(expires_at) = expires_at {
+        vc["expirationDate"] = json!(expires_at.to_rfc3339());
+        claims["exp"] = json!(expires_at.timestamp());
+    }
+
+    claims["vc"] = vc;
+
+    let credential = ssi::jwt::encode_sign(Algorithm::EdDSA, &claims, &issuer.jwk)?;
+
+    Ok(Json(Response {
+        credential,
+        c_nonce: CsrfToken::new_random().secret().clone(),
+        c_nonce_expires_in: EXPIRES_IN,
+    })
+    .into_response())
+}
+
+/// Verify the proof of possession, and return the DID the credential should be bound to.
+///
+/// See: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html#name-jwt-proof-type
+async fn verify_proof(
+    state: &AuthnState,
+    jwt: &str,
+    issuer_url: &str,
+    token: &IssuanceToken,
+) -> Result<String> {
+    let (header, _) = ssi::jws::decode_unverified(jwt)?;
+
+    if header.type_.as_deref() != Some("openid4vci-proof+jwt") {
+        return Err(eyre!("unexpected proof type").into());
+    }
+
+    // The key is either a DID URL, or embedded as a JWK.
+    let (holder, key) = match (header.key_id, header.jwk) {
+        (Some(kid), None) => {
+            let key = did::verification_key(&state.env.resolver_url, &kid).await?;
+            let did = kid.split('#').next().unwrap_or(&kid).to_string();
+
+            (did, key)
+        }
+        (None, Some(jwk)) => {
+            let did = format!(
+                "did:jwk:{}",
+                BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&jwk.to_public())?)
+            );
+
+            (did, jwk)
+        }
+        _ => return Err(eyre!("proof must have exactly one of kid or jwk").into()),
+    };
+
+    let claims = ssi::jwt::decode_verify::<Value>(jwt, &JWK::to_public(&key))?;
+
+    let is_fresh = claims["iat"].as_i64().ok_or_eyre("proof is missing iat")?
+        > (Utc::now() - Duration::seconds(EXPIRES_IN)).timestamp();
+
+    if claims["aud"] != json!(issuer_url) || claims["nonce"] != json!(token.c_nonce) || !is_fresh {
+        return Err(eyre!("proof does not match the request").into());
+    }
+
+    Ok(holder)
+}
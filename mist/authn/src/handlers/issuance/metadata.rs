@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    issuance::{self, PRE_AUTHORIZED_CODE_GRANT},
+    state::AuthnState,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+pub(crate) async fn credential_issuer_handler(
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+    let issuer_url = issuance::issuer_url(&state.env.authn_url, &service.id);
+
+    let configurations = state
+        .repos
+        .credential_configurations
+        .list(&service.id, 100, 0)
+        .await?;
+
+    // Describe each kind of credential the service issues.
+    // -----------------------------------------------------
+
+    let configurations = configurations
+        .iter()
+        .map(|configuration| {
+            let claims = configuration
+                .claims
+                .iter()
+                .map(|claim| {
+                    (
+                        claim.name.clone(),
+                        json!({
+                            "mandatory": claim.required,
+                            "display": [{ "name": claim.display_name.as_ref().unwrap_or(&claim.name) }],
+                        }),
+                    )
+                })
+                .collect::<Map<String, Value>>();
+
+            (
+                configuration.name.clone(),
+                json!({
+                    "format": "jwt_vc_json",
+                    "scope": configuration.name,
+                    "cryptographic_binding_methods_supported": ["did", "jwk"],
+                    "credential_signing_alg_values_supported": ["EdDSA"],
+                    "proof_types_supported": {
+                        "jwt": { "proof_signing_alg_values_supported": ["ES256", "ES256K", "EdDSA"] }
+                    },
+                    "credential_definition": {
+                        "type": ["VerifiableCredential", configuration.name],
+                        "credentialSubject": claims,
+                    },
+                    "display": [{ "name": configuration.display_name }],
+                }),
+            )
+        })
+        .collect::<Map<String, Value>>();
+
+    Ok(Json(json!({
+        "credential_issuer": issuer_url,
+        "credential_endpoint": format!("{issuer_url}/credential"),
+        "display": [{ "name": service.name }],
+        "credential_configurations_supported": configurations,
+    })))
+}
+
+/// We're our own authorization server, so wallets can get tokens from us directly.
+pub(crate) async fn authorization_server_handler(
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get(&path.service_id).await?;
+    let issuer_url = issuance::issuer_url(&state.env.authn_url, &service.id);
+
+    Ok(Json(json!({
+        "issuer": issuer_url,
+        "authorization_endpoint": format!("{issuer_url}/authorize"),
+        "token_endpoint": format!("{issuer_url}/token"),
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code", PRE_AUTHORIZED_CODE_GRANT],
+        "code_challenge_methods_supported": ["S256"],
+        "pre-authorized_grant_anonymous_access_supported": true,
+    })))
+}
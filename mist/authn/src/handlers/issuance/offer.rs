@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::{
+    credential_offer::{CredentialOfferId, OfferGrant},
+    service::ServiceId,
+};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    issuance::{self, PRE_AUTHORIZED_CODE_GRANT},
+    state::AuthnState,
+    utils::qr,
+    views,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+    offer_id: CredentialOfferId,
+}
+
+/// Show the offer as a QR code for the user to scan with their wallet.
+pub(crate) async fn view_handler(
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let offer = state.repos.credential_offers.get(&path.offer_id).await?;
+
+    let Ok(offer) = issuance::redeemable_offer(&state, &path.service_id, offer).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let service = state.repos.services.get(&path.service_id).await?;
+    let configuration = state
+        .repos
+        .credential_configurations
+        .get(&offer.configuration_id.into())
+        .await?;
+
+    let offer_uri = format!(
+        "{}/offers/{}/offer",
+        issuance::issuer_url(&state.env.authn_url, &service.id),
+        offer.id
+    );
+    let url = Url::parse_with_params(
+        "openid-credential-offer://",
+        &[("credential_offer_uri", offer_uri)],
+    )?;
+
+    Ok(views::offer::view(
+        &service,
+        &configuration.display_name,
+        &qr::png_base64(url.as_str())?,
+        url.as_str(),
+    )
+    .into_response())
+}
+
+/// The credential offer itself, fetched by the wallet.
+pub(crate) async fn offer_handler(
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+) -> Result<impl IntoResponse> {
+    let offer = state.repos.credential_offers.get(&path.offer_id).await?;
+
+    let Ok(offer) = issuance::redeemable_offer(&state, &path.service_id, offer).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let configuration = state
+        .repos
+        .credential_configurations
+        .get(&offer.configuration_id.into())
+        .await?;
+
+    let grants = match offer.grant_type {
+        OfferGrant::PreAuthorizedCode => json!({
+            PRE_AUTHORIZED_CODE_GRANT: { "pre-authorized_code": offer.pre_authorized_code }
+        }),
+        OfferGrant::AuthorizationCode => json!({
+            "authorization_code": { "issuer_state": offer.id }
+        }),
+    };
+
+    Ok(Json(json!({
+        "credential_issuer": issuance::issuer_url(&state.env.authn_url, &path.service_id),
+        "credential_configuration_ids": [configuration.name],
+        "grants": grants,
+    }))
+    .into_response())
+}
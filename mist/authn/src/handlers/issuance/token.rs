@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Form, Json,
+};
+use base64::prelude::*;
+use fred::types::Expiration;
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::service::ServiceId;
+use openidconnect::CsrfToken;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::oauth_error;
+use crate::{
+    issuance::{
+        self, IssuanceToken, EXPIRES_IN, ISSUANCE_CODE, ISSUANCE_TOKEN, PRE_AUTHORIZED_CODE_GRANT,
+    },
+    state::AuthnState,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct PathParams {
+    service_id: ServiceId,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TokenBody {
+    grant_type: String,
+    #[serde(rename = "pre-authorized_code")]
+    pre_authorized_code: Option<String>,
+    code: Option<String>,
+    code_verifier: Option<String>,
+    redirect_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Response {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: i64,
+    c_nonce: String,
+    c_nonce_expires_in: i64,
+}
+
+pub(crate) async fn handler(
+    State(state): State<AuthnState>,
+    Path(path): Path<PathParams>,
+    Form(body): Form<TokenBody>,
+) -> Result<impl IntoResponse> {
+    // Work out which offer the wallet is redeeming.
+    // ---------------------------------------------
+
+    let offer_id = match body.grant_type.as_str() {
+        PRE_AUTHORIZED_CODE_GRANT => {
+            let Some(code) = &body.pre_authorized_code else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+            };
+
+            let Ok(offer) = state
+                .repos
+                .credential_offers
+                .get_by_pre_authorized_code(code)
+                .await
+            else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+            };
+
+            offer.id
+        }
+        "authorization_code" => {
+            let (Some(code), Some(verifier)) = (&body.code, &body.code_verifier) else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+            };
+
+            // Take the code, so it can only ever be redeemed once.
+            let Some(issued) = ISSUANCE_CODE.take(&state.redis, code).await? else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+            };
+
+            // PKCE, since wallets are public clients.
+            let challenge = BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+            if challenge != issued.code_challenge
+                || body.redirect_uri.as_ref() != Some(&issued.redirect_uri)
+            {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+            }
+
+            issued.offer_id
+        }
+        _ => {
+            return Ok(oauth_error(
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+            ))
+        }
+    };
+
+    let offer = state.repos.credential_offers.get(&offer_id).await?;
+
+    if issuance::redeemable_offer(&state, &path.service_id, offer)
+        .await
+        .is_err()
+    {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+    }
+
+    // Hand out an access token for the credential endpoint.
+    // -----------------------------------------------------
+
+    let access_token = CsrfToken::new_random_len(32).secret().clone();
+    let c_nonce = CsrfToken::new_random().secret().clone();
+
+    ISSUANCE_TOKEN
+        .set(
+            &state.redis,
+            &access_token,
+            &IssuanceToken {
+                offer_id,
+                c_nonce: c_nonce.clone(),
+            },
+            Expiration::EX(EXPIRES_IN),
+        )
+        .await?;
+
+    Ok(Json(Response {
+        access_token,
+        token_type: "Bearer",
+        expires_in: EXPIRES_IN,
+        c_nonce,
+        c_nonce_expires_in: EXPIRES_IN,
+    })
+    .into_response())
+}
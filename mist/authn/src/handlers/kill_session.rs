@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Path, State},
     response::{IntoResponse, Redirect},
 };
 use eyre::OptionExt;
@@ -7,23 +7,40 @@ use mist_common::Result;
 use tower_cookies::Cookies;
 
 use crate::{
-    session::{AUTH_SESSION, COOKIE_KEY},
+    logout,
+    session::{self, AUTH_SESSION},
     state::AuthnState,
 };
 
 pub(crate) async fn handler(
     cookies: Cookies,
     State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let mut cookie = cookies.get(COOKIE_KEY).ok_or_eyre("no cookie")?;
-    let session_id = cookie.value().to_string();
+    let service = state.repos.services.get_by_name(&service_name).await?;
 
-    cookie.make_removal();
+    // Only this service's session ends, even if it was started through SSO.
+    let session_id = session::from_cookies(&cookies, &service.id).ok_or_eyre("no cookie")?;
 
-    let session = AUTH_SESSION.get(&state.redis, &session_id).await?;
-    let service = state.repos.services.get(&session.service_id).await?;
+    let Ok(session) = AUTH_SESSION
+        .get(&state.redis, &session_id.to_string())
+        .await
+    else {
+        // It's already over, so just tidy up the browser.
+        cookies.remove(session::cookie(
+            &service,
+            &session_id,
+            !state.env.development,
+        ));
 
-    AUTH_SESSION.del(&state.redis, &session_id).await?;
+        return Ok(Redirect::to(&service.logout_url).into_response());
+    };
 
-    Ok(Redirect::to(&service.logout_url))
+    let frontchannel_url = logout::end(&state, &cookies, &service, &session_id, &session).await?;
+
+    Ok(logout::finish(
+        &service,
+        frontchannel_url.as_slice(),
+        &service.logout_url,
+    ))
 }
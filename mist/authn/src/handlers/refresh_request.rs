@@ -0,0 +1,75 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+use mist_common::Result;
+use tower_cookies::Cookies;
+
+use crate::{
+    consent,
+    handlers::ServiceQuery,
+    session::{self, machine, AuthSession, AuthState},
+    state::AuthnState,
+    utils::auth_request,
+};
+
+/// Replace an expired auth request with a fresh one, keeping the same session.
+pub(crate) async fn handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Query(query): Query<ServiceQuery>,
+) -> Result<impl IntoResponse> {
+    let Some(session_id) = session::from_cookies(&cookies, &query.service_id) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let Ok(session) = machine::load(&state, &session_id.to_string()).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    // Once the user has scanned a code, there's nothing left to refresh.
+    let AuthState::Authenticating { action, refreshes } = session.state.clone() else {
+        return Ok(StatusCode::CONFLICT.into_response());
+    };
+
+    // Don't keep showing new codes to someone who's walked away.
+    if refreshes >= state.env.auth_request_refreshes {
+        machine::transition(
+            &state,
+            &session_id.to_string(),
+            &session,
+            AuthState::Expired,
+        )
+        .await?;
+
+        return Ok(StatusCode::GONE.into_response());
+    }
+
+    let service = state.repos.services.get(&session.service_id).await?;
+
+    // There's no code to show until the user has agreed to share their data. The page
+    // takes this as its cue to ask them.
+    if consent::is_needed(&service, &session) {
+        return Ok(StatusCode::FORBIDDEN.into_response());
+    }
+
+    machine::replace(
+        &state,
+        &session_id.to_string(),
+        &session,
+        AuthSession {
+            expires_at: auth_request::session_expires_at(&state),
+            ..session.with_state(AuthState::Authenticating {
+                action: action.clone(),
+                refreshes: refreshes + 1,
+            })
+        },
+    )
+    .await?;
+
+    let request = auth_request::create(&state, &service, &session_id, &action).await?;
+
+    Ok(Json(request).into_response())
+}
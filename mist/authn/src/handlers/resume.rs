@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Redirect},
+};
+use http::StatusCode;
+use mist_common::Result;
+use serde::Deserialize;
+use tower_cookies::Cookies;
+
+use crate::{
+    consent, device,
+    session::{self, machine, AuthState, RESUME_CODE},
+    state::AuthnState,
+    utils::{auth_request, redirects},
+    views,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct ResumeQuery {
+    code: String,
+}
+
+pub(crate) async fn handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Query(query): Query<ResumeQuery>,
+) -> Result<impl IntoResponse> {
+    // Exchange the code for the session it was issued for.
+    // -----------------------------------------------------
+
+    // Taken in one go, so the code can only ever be used once.
+    let Some(session_id) = RESUME_CODE.take(&state.redis, &query.code).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let session = machine::load(&state, &session_id.to_string()).await?;
+
+    let service = state.repos.services.get(&session.service_id).await?;
+
+    // The wallet may have opened this in a different browser than the one that
+    // started the flow, so (re)attach the session to this one.
+    cookies.add(session::cookie(
+        &service,
+        &session_id,
+        !state.env.development,
+    ));
+
+    // Send the user on their way, or wait with them if the service is still
+    // deciding what to do with them.
+    match &session.state {
+        AuthState::Authenticated { .. } if session.device => {
+            Ok(device::finish(&state, &cookies, &service, &session_id))
+        }
+        AuthState::Authenticated { .. } => Ok(Redirect::to(
+            &redirects::after_sign_in(&state, &service, &session_id, &session).await?,
+        )
+        .into_response()),
+        // Signing in with a wallet we didn't know switched the session to signing up,
+        // so the wallet needs a new request, once the user agrees to share their data.
+        AuthState::Authenticating { action, .. } => {
+            if consent::is_needed(&service, &session) {
+                return consent::ask(&state, &service).await;
+            }
+
+            let request = auth_request::create(&state, &service, &session_id, action).await?;
+
+            Ok(views::scan::view(&service, &state.env.authn_url, action, &request).into_response())
+        }
+        _ => Ok(views::resume::view(&service, &state.env.authn_url).into_response()),
+    }
+}
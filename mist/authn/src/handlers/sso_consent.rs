@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Redirect},
+    Form,
+};
+use http::StatusCode;
+use mist_common::Result;
+use tower_cookies::Cookies;
+
+use crate::{
+    session::{Client, ReturnTo},
+    sso,
+    state::AuthnState,
+    utils::redirects,
+};
+
+/// The user agreed to be signed in through a sibling service.
+pub(crate) async fn handler(
+    cookies: Cookies,
+    client: Client,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    Form(return_to): Form<ReturnTo>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    // The form came back through the browser, so check it again.
+    if !redirects::is_allowed_return(&state, &service, &return_to).await? {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    // The sibling's session may have ended while they were deciding.
+    let Some(sibling) = sso::find_sibling(&state, &cookies, &service).await? else {
+        return Ok(
+            Redirect::to(&format!("{}/{}/in", state.env.authn_url, service.name)).into_response(),
+        );
+    };
+
+    sso::consent(&state, &service, &sibling).await?;
+
+    sso::sign_in(&state, &cookies, &service, &sibling, client, return_to).await
+}
@@ -1,31 +1,24 @@
-use std::{io::Cursor, str::FromStr};
-
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
 };
-use base64::prelude::*;
-use dif_presentation_exchange::PresentationDefinition;
 use fred::types::Expiration;
-use image::{ImageFormat, Luma};
-use mist_common::{crypto::decrypt_service_key, Result};
-use mist_db::models::{key::KeyKind, user::UserId};
-use openidconnect::{
-    core::{CoreClient, CoreResponseType},
-    AuthUrl, AuthenticationFlow, ClientId, CsrfToken, IssuerUrl, JsonWebKeySet, RedirectUrl, Scope,
-};
-use qrcode::QrCode;
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::{recovery_method::RecoveryKind, user::UserId};
 use serde::Deserialize;
-use serde_json::json;
-use tower_cookies::{
-    cookie::{time::Duration, SameSite},
-    Cookie, Cookies,
-};
+use tower_cookies::Cookies;
 
 use crate::{
-    session::{AuthAction, AuthSession, AuthState, SessionId, AUTH_SESSION, COOKIE_KEY},
+    consent,
+    recovery::{RECOVERY_GRANT, RECOVERY_GRANT_EXPIRES_IN},
+    session::{
+        self, machine, AuthAction, AuthSession, AuthState, Client, ReturnTo, SessionId,
+        AUTH_SESSION,
+    },
+    sso,
     state::AuthnState,
-    utils::oidc,
+    utils::{auth_request, redirects},
     views,
 };
 
@@ -35,167 +28,192 @@ pub(crate) struct CreatePath {
     action: AuthAction,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct StartQuery {
+    /// An approval code, for recovering an account.
+    code: Option<String>,
+    /// Scan a wallet, even if the user could be signed in through SSO.
+    #[serde(default)]
+    skip_sso: bool,
+    /// Where to send the user once they're signed in, if not the service's
+    /// `redirect_url`. It has to be registered for the service.
+    redirect_uri: Option<String>,
+    /// Passed back to the service on the redirect.
+    state: Option<String>,
+}
+
 pub(crate) async fn handler(
     cookies: Cookies,
+    client: Client,
     State(state): State<AuthnState>,
     Path(path): Path<CreatePath>,
+    Query(query): Query<StartQuery>,
 ) -> Result<impl IntoResponse> {
-    // Get the services' token key for signing the state and nonce.
-    // ------------------------------------------------------------
-
     let service = state.repos.services.get_by_name(&path.service_name).await?;
 
-    let service_key = state
-        .repos
-        .keys
-        .preferred(&service.id, &KeyKind::Token)
-        .await?;
+    let return_to = ReturnTo {
+        redirect_uri: query.redirect_uri,
+        state: query.state,
+    };
+
+    if !redirects::is_allowed_return(&state, &service, &return_to).await? {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    let current = match session::from_cookies(&cookies, &service.id) {
+        Some(session_id) => machine::load(&state, &session_id.to_string())
+            .await
+            .ok()
+            .map(|session| (session, session_id)),
+        None => None,
+    };
+
+    // Linking another wallet needs someone to link it to.
+    // ---------------------------------------------------
+
+    let user_id = match (&path.action, &current) {
+        (AuthAction::Link, Some((session, _)))
+            if matches!(session.state, AuthState::Authenticated { .. })
+                && session.service_id == service.id =>
+        {
+            session.user_id
+        }
+        (AuthAction::Link, _) => return Ok(StatusCode::UNAUTHORIZED.into_response()),
+        _ => UserId::new(),
+    };
 
-    let service_key = decrypt_service_key(&state.env.master_key, &service_key.value)?;
+    // Sign in through a sibling service, if the user is signed in to one. Signing up
+    // always takes a scan, so the service gets to hear about new users as usual.
+    // --------------------------------------------------------------------------------
+
+    let signed_in = current
+        .as_ref()
+        .is_some_and(|(session, _)| matches!(session.state, AuthState::Authenticated { .. }));
+
+    if path.action == AuthAction::In && !signed_in && !query.skip_sso {
+        if let Some(sibling) = sso::find_sibling(&state, &cookies, &service).await? {
+            if !sso::has_consented(&state, &service, &sibling).await? {
+                return Ok(views::sso_consent::view(
+                    &service,
+                    &sibling.service,
+                    &state.env.authn_url,
+                    &return_to,
+                )
+                .into_response());
+            }
+
+            return sso::sign_in(&state, &cookies, &service, &sibling, client, return_to).await;
+        }
+    }
 
     // Get or create a session for the user.
     // -------------------------------------
 
     let redis_client = state.redis.clone();
-    let session_id = match cookies.get(COOKIE_KEY) {
-        Some(cookie) => Some(
+    let reused = current
+        // Start over if the session failed, or was still waiting on a scan anyway. Linking
+        // and recovering always get a session of their own.
+        .filter(|(session, _)| {
+            !matches!(path.action, AuthAction::Link | AuthAction::Recover)
+                && !matches!(
+                    session.state,
+                    AuthState::Authenticating { .. }
+                        | AuthState::Rejected { .. }
+                        | AuthState::Expired
+                )
+        });
+
+    let (session_id, session) = match reused {
+        // Send the user back wherever the service asked this time.
+        Some((mut session, session_id)) => {
+            session.return_to = return_to;
+
             AUTH_SESSION
-                .get(&redis_client, cookie.value())
-                .await
-                .ok()
-                .and_then(|_| SessionId::from_str(cookie.value()).ok()),
-        ),
-        None => None,
-    }
-    .flatten();
+                .set(
+                    &redis_client,
+                    &session_id.to_string(),
+                    &session,
+                    machine::expiration(&session),
+                )
+                .await?;
 
-    let session_id = match session_id {
-        Some(value) => value,
+            (session_id, session)
+        }
         None => {
             let session_id = SessionId::new();
-            let user_id = UserId::new();
+            let session = AuthSession {
+                service_id: service.id,
+                user_id,
+                state: AuthState::Authenticating {
+                    action: path.action.clone(),
+                    refreshes: 0,
+                },
+                expires_at: auth_request::session_expires_at(&state),
+                idle_timeout: None,
+                client,
+                return_to,
+                device: false,
+                consented: false,
+                sid: session::new_sid(),
+            };
 
             AUTH_SESSION
                 .set(
                     &redis_client,
                     &session_id.to_string(),
-                    &AuthSession {
-                        service_id: service.id,
-                        user_id,
-                        state: AuthState::Authenticating {
-                            action: path.action.clone(),
-                        },
-                    },
-                    Expiration::EX(60 * 5),
+                    &session,
+                    machine::expiration(&session),
                 )
                 .await?;
 
-            cookies.add(
-                Cookie::build((COOKIE_KEY, session_id.to_string()))
-                    .secure(!state.env.development)
-                    .http_only(true)
-                    .same_site(SameSite::Strict)
-                    .max_age(Duration::hours(8))
-                    .path("/")
-                    .build(),
-            );
-
-            session_id
+            cookies.add(session::cookie(
+                &service,
+                &session_id,
+                !state.env.development,
+            ));
+
+            (session_id, session)
         }
     };
 
-    // Create a presentation from the service's default profile definition.
-    // --------------------------------------------------------------------
+    // Tie an approved recovery to the session, if the user was sent one.
+    // ------------------------------------------------------------------
 
-    let fields = match path.action {
-        AuthAction::Up => {
-            let profile = state
-                .repos
-                .services
-                .get_default_profile(&service.id)
-                .await?;
+    if let (AuthAction::Recover, Some(code)) = (&path.action, &query.code) {
+        let approval = state
+            .repos
+            .recovery_methods
+            .find(&service.id, &RecoveryKind::Approval, code)
+            .await;
 
-            if let Some(profile) = profile {
-                let fields = profile
-                    .value
-                    .fields
-                    .iter()
-                    .map(|f| json!({ "id": heck::AsSnakeCase(f.name.clone()).to_string(), "name": f.name, "constraints": {} }))
-                    .collect::<Vec<_>>();
-
-                fields
-            } else {
-                vec![json!({ "id": "skip", "name": "Skip", "constraints": {} })]
-            }
+        if approval.is_err() {
+            return Ok(StatusCode::NOT_FOUND.into_response());
         }
-        AuthAction::In => {
-            // Ideally, we send no fields for signing in, but Sphereon seems to require we send _something_.
-            //
-            // TODO: Remove this when I can.
-            vec![json!({ "id": "skip", "name": "Skip", "constraints": {} })]
-        }
-    };
 
-    let presentation = serde_json::from_value::<PresentationDefinition>(json!({
-        "id": "registration-data",
-        "input_descriptors": fields
-    }))?;
-
-    // Create the authorization URL and render it as a QR code.
-    // --------------------------------------------------------
-
-    let oidc_client = CoreClient::new(
-        ClientId::new(service.name.clone()),
-        None,
-        // Placeholder, not used for SIOP.
-        IssuerUrl::new("https://not.needed".into())?,
-        // Only the prefix matters for SIOP.
-        AuthUrl::new("siopv2://authenticate".into())?,
-        None,
-        None,
-        JsonWebKeySet::default(),
-    );
-
-    let state_sk = service_key.clone();
-    let nonce_sk = service_key.clone();
-
-    let redirect_url = RedirectUrl::new(format!("{}/auth", state.env.authn_url))?;
-
-    let (authorize_url, _, _) = oidc_client
-        .set_redirect_uri(redirect_url)
-        .authorize_url(
-            AuthenticationFlow::<CoreResponseType>::Hybrid(vec![
-                CoreResponseType::IdToken,
-                CoreResponseType::Extension("vp_token".into()),
-            ]),
-            move || {
-                oidc::created_signed_state(&state_sk, CsrfToken::new_random().secret(), &session_id)
-                    .unwrap()
-            },
-            move || {
-                oidc::create_signed_nonce(&nonce_sk, openidconnect::Nonce::new_random().secret())
-                    .unwrap()
-            },
-        )
-        .add_scope(Scope::new("vp_token".into()))
-        .add_extra_param("response_mode", "post")
-        .add_extra_param("id_token_type", "subject_signed_id_token")
-        .add_extra_param(
-            "presentation_definition",
-            &serde_json::to_string::<PresentationDefinition>(&presentation)?,
-        )
-        .url();
-
-    // Render the authorization URL as a QR code.
-    let code = QrCode::new(authorize_url.as_str())?;
-    let image = code.render::<Luma<u8>>().max_dimensions(500, 500).build();
-    let mut buf = Vec::new();
-    let mut cursor = Cursor::new(&mut buf);
-    image.write_to(&mut cursor, ImageFormat::Png)?;
-    let encoded = BASE64_STANDARD.encode(buf);
+        RECOVERY_GRANT
+            .set(
+                &state.redis,
+                &session_id.to_string(),
+                code,
+                Expiration::EX(RECOVERY_GRANT_EXPIRES_IN),
+            )
+            .await?;
+    }
+
+    // Ask the user before they share their data, if the service wants that.
+    // ----------------------------------------------------------------------
+
+    if consent::is_needed(&service, &session) {
+        return consent::ask(&state, &service).await;
+    }
+
+    // Create the auth request and render it as a QR code.
+    // --------------------------------------------------
+
+    let request = auth_request::create(&state, &service, &session_id, &path.action).await?;
 
     // Render the view.
-    Ok(views::scan::view(&service, &state.env.authn_url, &encoded))
+    Ok(views::scan::view(&service, &state.env.authn_url, &path.action, &request).into_response())
 
     // -----------------------------------------------------------------------------
     // Once the user has responded to our auth request, we will continue the process
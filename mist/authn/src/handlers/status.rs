@@ -0,0 +1,32 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use http::StatusCode;
+use mist_common::Result;
+use tower_cookies::Cookies;
+
+use crate::{
+    events::Event,
+    handlers::ServiceQuery,
+    session::{self, machine},
+    state::AuthnState,
+};
+
+/// Where the session is in the auth flow, for browsers that can't use `/waiting`.
+pub(crate) async fn handler(
+    cookies: Cookies,
+    State(state): State<AuthnState>,
+    Query(query): Query<ServiceQuery>,
+) -> Result<impl IntoResponse> {
+    let Some(session_id) = session::from_cookies(&cookies, &query.service_id) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let Ok(session) = machine::load(&state, &session_id.to_string()).await else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    Ok(Json(Event::from(&session.state)).into_response())
+}
@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use http::{HeaderMap, Method, StatusCode, Uri};
+use mist_common::Result;
+use mist_db::models::service::Service;
+use serde::Deserialize;
+
+use super::oauth_error;
+use crate::{
+    device::{self, Poll},
+    dpop::{self, Proof},
+    session::machine,
+    state::AuthnState,
+    tokens::{self, AUTH_CODE, REFRESH_TOKEN, REFRESH_TOKEN_JKT},
+    utils::client_auth,
+};
+
+#[derive(Deserialize)]
+pub(crate) struct TokenBody {
+    grant_type: String,
+    code: Option<String>,
+    refresh_token: Option<String>,
+    device_code: Option<String>,
+}
+
+/// Swap a code or refresh token for new tokens. Only the service's backend can call
+/// this, with its API key, except for devices, which can't keep a secret.
+pub(crate) async fn handler(
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+    Form(body): Form<TokenBody>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    if !service.token_flow {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "unauthorized_client"));
+    }
+
+    // Bind the tokens to the service's key, if it sent a proof of one.
+    let jkt = match dpop::check(&state, &headers, &Method::POST, &uri, None).await? {
+        Proof::Missing => None,
+        Proof::Invalid => return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_dpop_proof")),
+        Proof::Valid(jkt) => Some(jkt),
+    };
+
+    // Find the session being asked about.
+    // -----------------------------------
+
+    let (grants, grant) = match body.grant_type.as_str() {
+        "authorization_code" => (&AUTH_CODE, &body.code),
+        "refresh_token" => (&REFRESH_TOKEN, &body.refresh_token),
+        device::GRANT_TYPE => {
+            return poll_device(&state, &service, &body.device_code, jkt.as_deref()).await
+        }
+        _ => {
+            return Ok(oauth_error(
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+            ))
+        }
+    };
+
+    let Some(grant) = grant else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    };
+
+    let found = tokens::session(&state, grants, grant)
+        .await
+        .filter(|(_, session)| session.service_id == service.id);
+
+    // Devices get refresh tokens too, and can't keep the API key any more than they
+    // can keep those.
+    let public = found.as_ref().is_some_and(|(_, session)| session.device);
+
+    if !public && !client_auth::is_service(&state, &service.id, &headers).await? {
+        return Ok(oauth_error(StatusCode::UNAUTHORIZED, "invalid_client"));
+    }
+
+    // Refresh tokens bound to a key need a proof from the same one.
+    let bound = REFRESH_TOKEN_JKT.get(&state.redis, grant).await.ok();
+
+    if bound.is_some() && bound != jkt {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_dpop_proof"));
+    }
+
+    // Use up the code or refresh token. Only the request that takes it gets tokens, so
+    // it can't be swapped twice at once.
+    let taken = grants.take(&state.redis, grant).await?;
+    REFRESH_TOKEN_JKT.del(&state.redis, grant).await?;
+
+    let (Some((session_id, session)), Some(_)) = (found, taken) else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_grant"));
+    };
+
+    // Refreshing means the user is still around.
+    if body.grant_type == "refresh_token" {
+        machine::keep_alive(&state, &session_id.to_string(), &session).await?;
+    }
+
+    let tokens = tokens::issue(&state, &session_id, &session, jkt.as_deref()).await?;
+
+    Ok(Json(tokens).into_response())
+}
+
+/// Tell the device whether the user has signed in yet, and hand it tokens once they
+/// have.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc8628#section-3.5
+async fn poll_device(
+    state: &AuthnState,
+    service: &Service,
+    device_code: &Option<String>,
+    jkt: Option<&str>,
+) -> Result<Response> {
+    let Some(device_code) = device_code else {
+        return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+    };
+
+    let error = match device::poll(state, service, device_code).await? {
+        Poll::SignedIn(session_id, session) => {
+            return Ok(
+                Json(tokens::issue(state, &session_id, &session, jkt).await?).into_response(),
+            )
+        }
+        Poll::Pending => "authorization_pending",
+        Poll::SlowDown => "slow_down",
+        Poll::Denied => "access_denied",
+        Poll::Expired => "expired_token",
+        Poll::Invalid => "invalid_grant",
+    };
+
+    Ok(oauth_error(StatusCode::BAD_REQUEST, error))
+}
@@ -0,0 +1,52 @@
+use axum::{extract::State, response::IntoResponse};
+use http::{header::WWW_AUTHENTICATE, HeaderMap, Method, StatusCode, Uri};
+use mist_common::Result;
+
+use super::whoami;
+use crate::{
+    dpop::{self, Proof},
+    state::AuthnState,
+    tokens,
+};
+
+/// Like `/whoami`, but for services using the token-based flow, with an access token
+/// in place of the cookie.
+pub(crate) async fn handler(
+    State(state): State<AuthnState>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<impl IntoResponse> {
+    let Some((scheme, token)) = headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+    else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let Some((access_token, session)) = tokens::access_token(&state, token).await else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    // Tokens bound to a key are only any use with a proof from it.
+    // See: https://www.rfc-editor.org/rfc/rfc9449#section-7.1
+    match (&access_token.jkt, scheme) {
+        (None, "Bearer") => {}
+        (Some(jkt), "DPoP") => {
+            let proof = dpop::check(&state, &headers, &Method::GET, &uri, Some(token)).await?;
+
+            if !matches!(proof, Proof::Valid(proven) if proven == *jkt) {
+                return Ok((
+                    StatusCode::UNAUTHORIZED,
+                    [(WWW_AUTHENTICATE, r#"DPoP error="invalid_dpop_proof""#)],
+                )
+                    .into_response());
+            }
+        }
+        _ => return Ok(StatusCode::UNAUTHORIZED.into_response()),
+    }
+
+    let service = state.repos.services.get(&session.service_id).await?;
+
+    whoami::describe(&state, &service, &access_token.session_id, session).await
+}
@@ -0,0 +1,127 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Form,
+};
+use http::StatusCode;
+use mist_common::Result;
+use mist_db::models::user::UserId;
+use serde::Deserialize;
+use tower_cookies::Cookies;
+
+use crate::{
+    device,
+    session::{self, machine, AuthAction, AuthSession, AuthState, Client, SessionId, AUTH_SESSION},
+    state::AuthnState,
+    utils::auth_request,
+    views,
+};
+
+const INVALID_CODE: &str =
+    "That code isn't valid, or has expired. Check your device for a new one.";
+const TOO_MANY_ATTEMPTS: &str = "Too many wrong codes. Wait a few minutes and try again.";
+
+#[derive(Deserialize)]
+pub(crate) struct DeviceQuery {
+    user_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DeviceForm {
+    user_code: String,
+}
+
+/// Where the device sends the user to enter its code.
+pub(crate) async fn get_handler(
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    Query(query): Query<DeviceQuery>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    Ok(views::device::enter_code(
+        &service,
+        &state.env.authn_url,
+        query.user_code.as_deref(),
+        None,
+    ))
+}
+
+/// Sign in for the device the code was given to, with the usual QR code.
+pub(crate) async fn post_handler(
+    cookies: Cookies,
+    client: Client,
+    State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
+    Form(form): Form<DeviceForm>,
+) -> Result<impl IntoResponse> {
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    let invalid = |error| {
+        views::device::enter_code(
+            &service,
+            &state.env.authn_url,
+            Some(&form.user_code),
+            Some(error),
+        )
+        .into_response()
+    };
+
+    // Don't let anyone guess their way to someone else's device.
+    if device::is_locked_out(&state, &service, &client).await? {
+        return Ok((StatusCode::TOO_MANY_REQUESTS, invalid(TOO_MANY_ATTEMPTS)).into_response());
+    }
+
+    device::throttle(&state, &service).await?;
+
+    if !device::is_waiting(&state, &service, &form.user_code).await {
+        device::failed_attempt(&state, &service, &client).await?;
+
+        return Ok(invalid(INVALID_CODE));
+    }
+
+    let session_id = SessionId::new();
+    let session = AuthSession {
+        service_id: service.id,
+        user_id: UserId::new(),
+        state: AuthState::Authenticating {
+            action: AuthAction::In,
+            refreshes: 0,
+        },
+        expires_at: auth_request::session_expires_at(&state),
+        idle_timeout: None,
+        client,
+        return_to: Default::default(),
+        device: true,
+        consented: false,
+        sid: session::new_sid(),
+    };
+
+    AUTH_SESSION
+        .set(
+            &state.redis,
+            &session_id.to_string(),
+            &session,
+            machine::expiration(&session),
+        )
+        .await?;
+
+    // Someone else may have used the code in the meantime.
+    if !device::attach(&state, &service, &form.user_code, &session_id).await? {
+        return Ok(invalid(INVALID_CODE));
+    }
+
+    // The page follows the session through the cookie, like any other sign in.
+    cookies.add(session::cookie(
+        &service,
+        &session_id,
+        !state.env.development,
+    ));
+
+    let request = auth_request::create(&state, &service, &session_id, &AuthAction::In).await?;
+
+    Ok(
+        views::scan::view(&service, &state.env.authn_url, &AuthAction::In, &request)
+            .into_response(),
+    )
+}
@@ -1,25 +1,33 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
-use axum::{extract::State, response::IntoResponse, Form};
+use axum::{extract::State, response::IntoResponse, Form, Json};
 use chrono::Utc;
 use eyre::{eyre, OptionExt};
 use fred::prelude::*;
 use http::StatusCode;
 use mist_common::{crypto::decrypt_service_key, Result};
-use mist_db::models::{key::KeyKind, service::Service, user::UserId};
+use mist_db::models::{
+    identifier::CreateIdentifier,
+    key::KeyKind,
+    service::{ResponseMode, Service, UnknownSignIn, WebhookMode},
+    user::UserId,
+};
 use mist_jobs::jobs;
-use openidconnect::core::CoreIdTokenClaims;
+use openidconnect::{core::CoreIdTokenClaims, CsrfToken};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
-use ssi::{did::VerificationMethod, did_resolve::ResolutionResult, jwk::JWK, vc::OneOrMany};
+use serde_json::Value;
+use ssi::{jwk::JWK, vc::OneOrMany};
 
 use crate::{
-    events::{get_event_key, Event},
-    session::{AuthAction, AuthSession, AuthState, SessionId, AUTH_SESSION},
+    recovery,
+    registration::{self, Decision},
+    session::{machine, AuthAction, AuthSession, AuthState, SessionId, RESUME_CODE},
+    sign_in::{self, SignInData},
     state::AuthnState,
     utils::{
-        oidc,
-        sphereon::{SphereonCredentialWrapper, SphereonTokenWrapper},
+        auth_request, did, issuers, jwe,
+        oidc::{self, TokenKind},
+        sphereon::SphereonTokenWrapper,
     },
 };
 
@@ -27,10 +35,33 @@ use crate::{
 pub(crate) struct RegistrationData {
     pub(crate) id: UserId,
     pub(crate) identifier: String,
-    pub(crate) profile: Map<String, Value>,
+    pub(crate) profile: BTreeMap<String, Claim>,
     pub(crate) session_id: SessionId,
 }
 
+/// A profile claim, along with who vouched for it.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Claim {
+    pub(crate) value: Value,
+    pub(crate) issuer: String,
+}
+
+/// The wallet's presentation, and what it has to be bound to.
+struct Presentation<'a> {
+    /// The wallet's authentication key, which signs the presentation.
+    jwk: &'a JWK,
+    did: &'a str,
+    /// The nonce from the request the wallet is responding to.
+    nonce: &'a str,
+    vp_token: &'a str,
+}
+
+#[derive(Serialize)]
+pub(crate) struct LinkedData {
+    pub(crate) id: UserId,
+    pub(crate) identifier: String,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct VerifyBody {
     state: String,
@@ -38,22 +69,51 @@ pub(crate) struct VerifyBody {
     vp_token: String,
 }
 
+#[derive(Serialize)]
+pub(crate) struct Response {
+    redirect_uri: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ResponseBody {
+    /// Sent with `direct_post.jwt`, where the whole response is a JWE.
+    Encrypted { response: String },
+    /// Sent with `post`, where the response parameters are sent as-is.
+    Plain(VerifyBody),
+}
+
 pub(crate) async fn handler(
     State(state): State<AuthnState>,
-    Form(body): Form<VerifyBody>,
+    Form(body): Form<ResponseBody>,
 ) -> Result<impl IntoResponse> {
+    // Decrypt the response if needed.
+    // -------------------------------
+
+    let (body, response_mode) = match body {
+        ResponseBody::Plain(body) => (body, ResponseMode::Post),
+        ResponseBody::Encrypted { response } => {
+            let Some(decrypted) = jwe::decrypt_response(&state.redis, &response).await? else {
+                return Ok(StatusCode::BAD_REQUEST.into_response());
+            };
+
+            (
+                serde_json::from_slice::<VerifyBody>(&decrypted)?,
+                ResponseMode::DirectPostJwt,
+            )
+        }
+    };
+
     // Get the user's session data.
     // ----------------------------
 
-    let parts = body.state.split(':').collect::<Vec<&str>>();
+    // The state is only trusted once we've checked it with the service's key, below.
+    let session_id = oidc::decode_unverified(&body.state)?.session_id;
+    let received_session_id = session_id.to_string();
 
-    let [received_state, received_session_id, received_signature] = parts.as_slice() else {
-        return Err(eyre!("state does not match expected structure").into());
-    };
+    let session = machine::load(&state, &received_session_id).await?;
 
-    let session = AUTH_SESSION.get(&state.redis, received_session_id).await?;
-
-    let AuthState::Authenticating { action } = &session.state else {
+    let AuthState::Authenticating { action, .. } = session.state.clone() else {
         return Ok(StatusCode::NOT_FOUND.into_response());
     };
 
@@ -62,6 +122,11 @@ pub(crate) async fn handler(
 
     let service = state.repos.services.get(&session.service_id).await?;
 
+    // Don't let a wallet fall back to a plain response when we asked for an encrypted one.
+    if response_mode != service.response_mode {
+        return Err(eyre!("unexpected response mode").into());
+    }
+
     let service_key = state
         .repos
         .keys
@@ -73,16 +138,94 @@ pub(crate) async fn handler(
     // Verify that the state returned by the user matches the one we sent.
     // -------------------------------------------------------------------
 
-    let expected_signature = oidc::sign_state(
-        &service_key,
-        received_state,
-        &SessionId::from_str(received_session_id)?,
-    )?;
+    let state_claims = oidc::verify(&service_key, &body.state, TokenKind::State)?;
 
-    if received_signature != &expected_signature {
+    if state_claims.service_id != service.id || state_claims.action != action {
         return Err(eyre!("state does not match").into());
     }
 
+    // Each state can only be used once, so a captured response can't be replayed.
+    oidc::consume(&state.redis, &state_claims).await?;
+
+    // Check what the wallet sent us.
+    // -------------------------------
+
+    let session = machine::transition(
+        &state,
+        &received_session_id,
+        &session,
+        AuthState::Scanned {
+            action: action.clone(),
+        },
+    )
+    .await?;
+
+    let session = machine::transition(
+        &state,
+        &received_session_id,
+        &session,
+        AuthState::Verifying {
+            action: action.clone(),
+        },
+    )
+    .await?;
+
+    // Don't leave the session stuck if the response turns out to be no good.
+    if let Err(err) = verify(
+        &state,
+        &service,
+        &service_key,
+        &session_id,
+        &session,
+        &action,
+        &body,
+    )
+    .await
+    {
+        tracing::warn!(session_id = received_session_id, %err, "rejected wallet response");
+
+        // The error is for us, not the user, so keep it in the logs.
+        machine::reject(
+            &state,
+            &received_session_id,
+            "invalid_response",
+            "We couldn't verify the response from your wallet, please try again.",
+        )
+        .await?;
+
+        return Err(err);
+    }
+
+    // Give the wallet somewhere to send the user next.
+    // ------------------------------------------------
+    //
+    // On the same device, the wallet opens this in a browser, which may not be the one
+    // that started the flow. The code lets us find the session again.
+
+    let code = CsrfToken::new_random_len(32).secret().clone();
+
+    RESUME_CODE
+        .set(&state.redis, &code, &session_id, Expiration::EX(60 * 5))
+        .await?;
+
+    Ok(Json(Response {
+        redirect_uri: format!("{}/resume?code={code}", state.env.authn_url),
+    })
+    .into_response())
+}
+
+/// Verify the wallet's ID token and credentials, and move the session on.
+async fn verify(
+    state: &AuthnState,
+    service: &Service,
+    service_key: &[u8],
+    session_id: &SessionId,
+    session: &AuthSession,
+    action: &AuthAction,
+    body: &VerifyBody,
+) -> Result<()> {
+    let received_session_id = session_id.to_string();
+
     // Get some useful information from the JWT header.
     // ------------------------------------------------
 
@@ -92,89 +235,14 @@ pub(crate) async fn handler(
     // The `kid` in this case is the user's DID.
     let did = header.kid.ok_or_eyre("header missing kid")?;
 
-    // Resolve the DID.
-    // ----------------
-
-    let document = reqwest::get(format!(
-        "{resolver_host}/{did}",
-        resolver_host = &state.env.resolver_url
-    ))
-    .await?
-    .json::<ResolutionResult>()
-    .await?
-    .did_document
-    .ok_or_eyre("no document")?;
-
-    // Get the verification method to use for authentication.
-    // ------------------------------------------------------
-
-    let verif_methods = document
-        .verification_method
-        .ok_or_eyre("document is missing verification methods")?;
-    let auth_methods = document
-        .authentication
-        .ok_or_eyre("document is missing authentication property")?;
-
-    // Get the first auth method for convenience.
-    let first = auth_methods
-        .first()
-        .ok_or_eyre("document is missing an auth method")?;
-
-    let method = match first {
-        // If the verification method is a DID URL, we need to resolve that DID and find the
-        // verification method where ID = DID URL.
-        VerificationMethod::DIDURL(url) => {
-            let other_document = reqwest::get(format!(
-                "{resolver_host}/{url}",
-                resolver_host = &state.env.resolver_url
-            ))
-            .await?
-            .json::<ResolutionResult>()
-            .await?
-            .did_document
-            .ok_or_eyre("no document")?;
-
-            &other_document
-                .verification_method
-                .ok_or_eyre("other document is missing verification methods")?
-                .iter()
-                .filter_map(|m| {
-                    if let VerificationMethod::Map(v) = m {
-                        Some(v)
-                    } else {
-                        None
-                    }
-                })
-                .find(|m| m.id == url.to_string())
-                .ok_or_eyre("could not find verification method")?
-                .clone()
-        }
-        // If the verification method is a DID URL fragment, we need to find the verification
-        // method where ID = fargment.
-        VerificationMethod::RelativeDIDURL(url) => &verif_methods
-            .iter()
-            .filter_map(|m| {
-                if let VerificationMethod::Map(v) = m {
-                    Some(v)
-                } else {
-                    None
-                }
-            })
-            .find(|m| m.id == url.to_string())
-            .ok_or_eyre("could not find verification method")?
-            .clone(),
-        // In the case where it's a map, we can just return it directly.
-        VerificationMethod::Map(method) => method,
-    };
+    // Resolve the DID and get the key to use for authentication.
+    // ------------------------------------------------------------
+
+    let jwk = &did::authentication_key(&state.env.resolver_url, &did).await?;
 
     // Verify and decode the ID Token by using the public key from the found verification method.
     // ------------------------------------------------------------------------------------------
 
-    let jwk = method
-        .public_key_jwk
-        .as_ref()
-        .ok_or_eyre("could not get public jwk")?;
-
     // Ensure that the public key is for verification. I _think_ this should be
     // `verify`, but Sphereon sends `sig`.
     //
@@ -193,40 +261,59 @@ pub(crate) async fn handler(
     // Verify that the nonce in the ID token matches the one we sent.
     // --------------------------------------------------------------
 
-    let parts = decoded_id_token
+    let received_nonce = decoded_id_token
         .nonce()
         .ok_or_eyre("could not find nonce")?
-        .secret()
-        .split(':')
-        .collect::<Vec<&str>>();
-    let (received_nonce, received_signature) = parts
-        .first()
-        .zip(parts.get(1))
-        .ok_or_eyre("nonce does not match expected structure")?;
+        .secret();
 
-    let expected_signature = oidc::sign_nonce(&service_key, received_nonce)?;
+    let nonce_claims = oidc::verify(service_key, received_nonce, TokenKind::Nonce)?;
 
-    if received_signature != &expected_signature {
+    if &nonce_claims.session_id != session_id
+        || nonce_claims.service_id != service.id
+        || &nonce_claims.action != action
+    {
         return Err(eyre!("invalid nonce").into());
     }
 
+    oidc::consume(&state.redis, &nonce_claims).await?;
+
+    let presentation = Presentation {
+        jwk,
+        did: &did,
+        nonce: received_nonce,
+        vp_token: &body.vp_token,
+    };
+
     match action {
         AuthAction::Up => {
-            handle_up(
-                &state,
-                received_session_id,
-                &session,
-                &service,
-                jwk,
+            handle_up(state, &received_session_id, session, service, &presentation).await?
+        }
+        AuthAction::In => {
+            handle_in(state, &received_session_id, session, service, &presentation).await?
+        }
+        AuthAction::Link => {
+            handle_link(state, &received_session_id, session, service, &did).await?
+        }
+        AuthAction::Recover => {
+            // Only needed for credential recovery, which not every service allows.
+            let profile = match service.recovery_claim {
+                Some(_) => read_profile(state, service, &presentation).await?,
+                None => BTreeMap::new(),
+            };
+
+            recovery::recover(
+                state,
+                &received_session_id,
+                session,
+                service,
                 &did,
-                &body.vp_token,
+                &profile,
             )
             .await?
         }
-        AuthAction::In => handle_in(&state, &service, received_session_id, &did).await?,
     }
 
-    Ok(StatusCode::OK.into_response())
+    Ok(())
 }
 
 async fn handle_up(
@@ -234,100 +321,355 @@ async fn handle_up(
     session_id: &str,
     session: &AuthSession,
     service: &Service,
-    jwk: &JWK,
-    did: &str,
-    vp_token: &str,
+    presentation: &Presentation<'_>,
 ) -> Result<()> {
-    // Get their profile data from the received VCs.
-    // --------------------------------------------
-
-    let decoded_vp_token = ssi::jwt::decode_verify::<SphereonTokenWrapper>(vp_token, jwk)?;
-    let credential = decoded_vp_token.verifiable_credential[0].clone();
-    let decoded_credential = ssi::jwt::decode_unverified::<SphereonCredentialWrapper>(&credential)?;
-
-    let OneOrMany::One(subject) = decoded_credential.vc.credential_subject else {
-        return Err(eyre!("multiple subjects").into());
-    };
+    let did = presentation.did;
+    let profile = read_profile(state, service, presentation).await?;
 
-    let profile = subject.property_set.ok_or_eyre("no property set")?;
-    let json_map = profile.into_iter().collect::<Map<String, Value>>();
+    // Remember the credential they can recover their account with, if there is one.
+    let recovery = service
+        .recovery_claim
+        .as_ref()
+        .and_then(|name| Some((name, profile.get(name)?)))
+        .map(|(name, claim)| recovery::fingerprint(name, claim))
+        .transpose()?;
 
     // Send user data to the services' webhook endpoint so they can create the user on their end.
     // ------------------------------------------------------------------------------------------
 
-    AUTH_SESSION
-        .set(
-            &state.redis,
-            session_id,
-            &AuthSession {
-                service_id: session.service_id,
-                user_id: session.user_id,
-                state: AuthState::Registering {
-                    identifier: did.into(),
-                },
-            },
-            Expiration::EX(60 * 5),
-        )
-        .await?;
-
-    jobs::Webhook::publish(
-        &state.jetstream,
-        &service.webhook_url,
-        "registration",
-        &RegistrationData {
-            id: session.user_id,
+    let session = machine::transition(
+        state,
+        session_id,
+        session,
+        AuthState::Registering {
             identifier: did.into(),
-            profile: json_map,
-            session_id: SessionId::from_str(session_id)?,
+            recovery,
         },
     )
     .await?;
 
+    let data = RegistrationData {
+        id: session.user_id,
+        identifier: service.subject(did)?,
+        profile,
+        session_id: SessionId::from_str(session_id)?,
+    };
+
+    match service.webhook_mode {
+        WebhookMode::Async => {
+            jobs::Webhook::publish(
+                &state.jetstream,
+                &service.webhook_url,
+                "registration",
+                &data,
+            )
+            .await?;
+        }
+        // The service answers right away, so there's no waiting on `/complete`.
+        WebhookMode::Sync => {
+            let decision = jobs::Webhook::call::<_, Decision>(
+                &service.webhook_url,
+                "registration",
+                &data,
+                registration::WEBHOOK_TIMEOUT,
+            )
+            .await;
+
+            match decision {
+                Ok(Decision::Accept {
+                    external_id,
+                    claims,
+                }) => {
+                    registration::accept(state, session_id, &session, external_id, claims).await?
+                }
+                Ok(Decision::Reject { code, reason }) => {
+                    registration::reject(state, service, session_id, &session, code, reason).await?
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "registration webhook failed");
+
+                    machine::reject(
+                        state,
+                        session_id,
+                        "webhook_failed",
+                        "We couldn't finish setting up your account, please try again later.",
+                    )
+                    .await?
+                }
+            }
+        }
+    }
+
     Ok(())
 
     // -------------------------------------------------------------------------------------------
-    // When the server responds, we'll continue the process in the `complete_registration` handler.
+    // With the `async` webhook mode, we'll continue the process in the `complete_registration`
+    // handler when the server responds.
 }
 
-async fn handle_in(
+/// Read the claims from the wallet's credentials, checking each was issued to the
+/// wallet by someone the service trusts, and is still valid.
+async fn read_profile(
     state: &AuthnState,
     service: &Service,
+    presentation: &Presentation<'_>,
+) -> Result<BTreeMap<String, Claim>> {
+    // Work out which issuers we trust for this service's profile.
+    // ----------------------------------------------------------
+
+    let definition = state
+        .repos
+        .services
+        .get_default_profile(&service.id)
+        .await?;
+
+    let trusted = state
+        .repos
+        .trusted_issuers
+        .applicable(&service.id, definition.map(|d| d.id))
+        .await?;
+
+    // Get their profile data from the received VCs.
+    // --------------------------------------------
+
+    let decoded_vp_token =
+        ssi::jwt::decode_verify::<Value>(presentation.vp_token, presentation.jwk)?;
+
+    // Make sure it was made for this request, so one sent elsewhere can't be replayed.
+    let audience = match &decoded_vp_token["aud"] {
+        Value::Array(audience) => audience.iter().any(|aud| *aud == *service.name),
+        aud => *aud == *service.name,
+    };
+
+    if decoded_vp_token["nonce"].as_str() != Some(presentation.nonce) || !audience {
+        return Err(eyre!("presentation was not made for this request").into());
+    }
+
+    let decoded_vp_token = serde_json::from_value::<SphereonTokenWrapper>(decoded_vp_token)?;
+
+    let mut profile = BTreeMap::new();
+
+    for credential in &decoded_vp_token.verifiable_credential {
+        let verified =
+            issuers::verify_credential(&state.env.resolver_url, credential, &trusted).await?;
+
+        // Someone else's credential says nothing about this wallet's user.
+        if verified.subject.as_deref() != Some(presentation.did) {
+            return Err(eyre!("credential was not issued to the presenting wallet").into());
+        }
+
+        // Make sure the credential hasn't been revoked or suspended.
+        if let Some(status) = &verified.credential.credential_status {
+            state
+                .status_lists
+                .ensure_valid(status, &verified.issuer, &service.revocation_policy)
+                .await?;
+        }
+
+        let OneOrMany::One(subject) = verified.credential.credential_subject else {
+            return Err(eyre!("multiple subjects").into());
+        };
+
+        let properties = subject.property_set.ok_or_eyre("no property set")?;
+
+        profile.extend(properties.into_iter().map(|(name, value)| {
+            (
+                name,
+                Claim {
+                    value,
+                    issuer: verified.issuer.clone(),
+                },
+            )
+        }));
+    }
+
+    Ok(profile)
+}
+
+async fn handle_in(
+    state: &AuthnState,
     session_id: &str,
-    did: &str,
+    session: &AuthSession,
+    service: &Service,
+    presentation: &Presentation<'_>,
 ) -> Result<()> {
+    let did = presentation.did;
+
     // Get the existing uer ID via their DID.
     // --------------------------------------
 
-    let identifier = state.repos.identifiers.get_by_value(did).await?;
+    // Only the service's own users count: the same wallet is someone else elsewhere.
+    let identifier = match state
+        .repos
+        .identifiers
+        .get_by_value_for_service(&service.id, did)
+        .await
+    {
+        Ok(identifier) => identifier,
+        Err(err) if err.is_not_found() => {
+            return handle_unknown(state, session_id, session, service).await;
+        }
+        Err(err) => return Err(err),
+    };
     let user = state.repos.users.get(&identifier.user_id).await?;
 
-    // Complete the authentication process.
-    // ------------------------------------
+    // Complete the authentication process, unless the service wants a say.
+    // --------------------------------------------------------------------
 
-    // Update user session.
-    AUTH_SESSION
-        .set(
-            &state.redis,
+    let Some(mode) = &service.sign_in_webhook else {
+        machine::replace(
+            state,
             session_id,
-            &AuthSession {
-                service_id: service.id,
+            session,
+            AuthSession {
                 user_id: user.id,
-                state: AuthState::Authenticated {
+                ..session.with_state(AuthState::Authenticated {
                     identifier_id: identifier.id,
-                },
+                    claims: Default::default(),
+                })
             },
-            Expiration::EX(60 * 60 * 8),
         )
         .await?;
 
-    // Send an event to the user's browser to let it know authentication is complete.
-    //
-    // This event will be picked up by an event listener in the browser listening to
-    // the `waiting_for_completion` handler.
-    state
-        .nats
-        .publish(get_event_key(&Event::Redirect, session_id), "".into())
+        return Ok(());
+    };
+
+    // Ask the service, passing along any step-up claims.
+    // --------------------------------------------------
+
+    let claims = read_profile(state, service, presentation).await?;
+
+    let session = machine::replace(
+        state,
+        session_id,
+        session,
+        AuthSession {
+            user_id: user.id,
+            ..session.with_state(AuthState::SigningIn {
+                identifier_id: identifier.id,
+            })
+        },
+    )
+    .await?;
+
+    let data = SignInData {
+        id: user.id,
+        identifier: service.subject(did)?,
+        claims,
+        session_id: SessionId::from_str(session_id)?,
+    };
+
+    sign_in::ask(state, service, mode, session_id, &session, &data).await?;
+
+    Ok(())
+}
+
+/// Deal with someone signing in with a wallet we've never seen.
+async fn handle_unknown(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+    service: &Service,
+) -> Result<()> {
+    match service.unknown_sign_in {
+        UnknownSignIn::Reject => {
+            machine::reject(
+                state,
+                session_id,
+                "no_account",
+                "No account was found for this wallet, try signing up instead.",
+            )
+            .await?
+        }
+        // Ask for the service's profile with a fresh auth request, which the waiting
+        // page swaps in when it sees the session has gone back to `Authenticating`.
+        UnknownSignIn::Register => {
+            machine::replace(
+                state,
+                session_id,
+                session,
+                AuthSession {
+                    expires_at: auth_request::session_expires_at(state),
+                    ..session.with_state(AuthState::Authenticating {
+                        action: AuthAction::Up,
+                        refreshes: 0,
+                    })
+                },
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the wallet to the user who started linking it.
+async fn handle_link(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+    service: &Service,
+    did: &str,
+) -> Result<()> {
+    // A DID can only belong to one of the service's users.
+    // -----------------------------------------------------
+
+    match state
+        .repos
+        .identifiers
+        .get_by_value_for_service(&service.id, did)
+        .await
+    {
+        Ok(_) => {
+            machine::reject(
+                state,
+                session_id,
+                "already_linked",
+                "This wallet is already linked to an account.",
+            )
+            .await?;
+
+            return Ok(());
+        }
+        Err(err) if err.is_not_found() => {}
+        Err(err) => return Err(err),
+    }
+
+    let identifier = state
+        .repos
+        .identifiers
+        .create(
+            &CreateIdentifier::builder()
+                .value(did)
+                .user_id(session.user_id)
+                .build(),
+        )
         .await?;
 
+    // Sign them in with the new wallet, and let the service know about it.
+    // --------------------------------------------------------------------
+
+    machine::transition(
+        state,
+        session_id,
+        session,
+        AuthState::Authenticated {
+            identifier_id: identifier.id,
+            claims: Default::default(),
+        },
+    )
+    .await?;
+
+    jobs::Webhook::publish(
+        &state.jetstream,
+        &service.webhook_url,
+        "identifier.linked",
+        &LinkedData {
+            id: session.user_id,
+            identifier: service.subject(did)?,
+        },
+    )
+    .await?;
+
     Ok(())
 }
@@ -1,7 +1,7 @@
-use std::{convert::Infallible, sync::Arc};
+use std::convert::Infallible;
 
 use axum::{
-    extract::State,
+    extract::{Query, State},
     response::{
         sse::{Event as ServerEvent, KeepAlive},
         Sse,
@@ -15,38 +15,42 @@ use tower_cookies::Cookies;
 
 use crate::{
     events::{get_event_key, Event},
-    session::COOKIE_KEY,
+    handlers::ServiceQuery,
+    session::{self, machine},
     state::AuthnState,
 };
 
 pub(crate) async fn handler(
     cookies: Cookies,
     State(state): State<AuthnState>,
+    Query(query): Query<ServiceQuery>,
 ) -> Result<Sse<impl Stream<Item = std::result::Result<ServerEvent, Infallible>>>> {
-    let cookie = cookies.get(COOKIE_KEY).ok_or_eyre("no cookie")?;
+    let session_id = session::from_cookies(&cookies, &query.service_id)
+        .ok_or_eyre("no cookie")?
+        .to_string();
 
     let (tx, rx) = mpsc::channel(100);
-    let tx_arc = Arc::new(tx);
 
-    let mut subscription = state
-        .nats
-        .subscribe(get_event_key(&Event::Redirect, cookie.value()))
-        .await?;
+    // Subscribe before looking at the session, so we can't miss anything in between.
+    let mut subscription = state.nats.subscribe(get_event_key(&session_id)).await?;
+
+    // The session may have moved on before the browser connected, so start with
+    // where it is now.
+    let session = machine::load(&state, &session_id).await?;
+    let current = ServerEvent::default().json_data(Event::from(&session.state))?;
+
+    tx.send(Ok(current)).await?;
 
     tokio::spawn(async move {
-        while (subscription.next().await).is_some() {
-            let tx_clone = Arc::clone(&tx_arc);
-
-            // When a message is received, we send a server-sent event to the user's
-            // browser, which will trigger the client to redirect to the services
-            // callback URL.
-            tokio::spawn(async move {
-                let event = ServerEvent::default().data("ready");
-
-                if tx_clone.send(Ok(event)).await.is_err() {
-                    tracing::error!("failed to send message to channel");
-                }
-            });
+        // Each message is an `Event` as JSON, which the browser uses to update the page
+        // and, once authenticated, redirect to the service's callback URL.
+        while let Some(message) = subscription.next().await {
+            let event = ServerEvent::default().data(String::from_utf8_lossy(&message.payload));
+
+            if tx.send(Ok(event)).await.is_err() {
+                // The browser has gone away.
+                break;
+            }
         }
     });
 
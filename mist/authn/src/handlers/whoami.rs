@@ -1,42 +1,76 @@
-use axum::{extract::State, response::IntoResponse};
-use eyre::OptionExt;
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+};
 use http::StatusCode;
 use mist_common::Result;
-use mist_db::models::user::UserId;
+use mist_db::models::{service::Service, user::UserId};
 use serde::Serialize;
+use serde_json::{Map, Value};
 use tower_cookies::Cookies;
 
 use crate::{
-    session::{AuthState, AUTH_SESSION, COOKIE_KEY},
+    session::{self, machine, AuthSession, AuthState, SessionId, AUTH_SESSION},
     state::AuthnState,
 };
 
 #[derive(Serialize)]
-pub(crate) struct Response {
+pub(crate) struct WhoAmI {
     id: UserId,
+    /// The DID the user signed in with, or under pairwise subjects, what stands in for
+    /// it.
     identifier: String,
+    /// Extra claims the service attached when signing the user in.
+    claims: Map<String, Value>,
 }
 
 pub(crate) async fn handler(
     cookies: Cookies,
     State(state): State<AuthnState>,
+    Path(service_name): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let cookie = cookies.get(COOKIE_KEY).ok_or_eyre("no cookie")?;
+    let service = state.repos.services.get_by_name(&service_name).await?;
+
+    let Some(session_id) = session::from_cookies(&cookies, &service.id) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
 
-    let Ok(session) = AUTH_SESSION.get(&state.redis, cookie.value()).await else {
+    let Ok(session) = AUTH_SESSION
+        .get(&state.redis, &session_id.to_string())
+        .await
+    else {
         return Ok(StatusCode::NOT_FOUND.into_response());
     };
 
-    let AuthState::Authenticated { identifier_id } = session.state else {
+    describe(&state, &service, &session_id, session).await
+}
+
+/// Who the session is signed in as, counting this as activity on it.
+pub(crate) async fn describe(
+    state: &AuthnState,
+    service: &Service,
+    session_id: &SessionId,
+    session: AuthSession,
+) -> Result<Response> {
+    machine::keep_alive(state, &session_id.to_string(), &session).await?;
+
+    let AuthState::Authenticated {
+        identifier_id,
+        claims,
+    } = session.state
+    else {
         return Ok(StatusCode::NOT_FOUND.into_response());
     };
 
+    state.repos.sessions.touch(session_id).await?;
+
     let user = state.repos.users.get(&session.user_id).await?;
     let identifier = state.repos.identifiers.get(&identifier_id).await?;
 
-    Ok(serde_json::to_string(&Response {
+    Ok(serde_json::to_string(&WhoAmI {
         id: user.id,
-        identifier: identifier.value,
+        identifier: service.subject(&identifier.value)?,
+        claims,
     })?
     .into_response())
 }
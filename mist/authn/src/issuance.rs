@@ -0,0 +1,142 @@
+// State for issuing credentials over OpenID4VCI.
+//
+// Each service is its own credential issuer (and authorization server), at
+// `{authn_url}/issuers/{service_id}`, signing credentials with its issuer key.
+//
+// See: https://openid.net/specs/openid-4-verifiable-credential-issuance-1_0.html
+// -------------------------------------------------------------------------------
+
+use ed25519_dalek::SigningKey;
+use eyre::eyre;
+use mist_common::{crypto::decrypt_service_key, redis::TypedRedis, Result};
+use mist_db::models::{
+    credential_offer::{CredentialOffer, CredentialOfferId},
+    key::KeyKind,
+    service::ServiceId,
+};
+use serde::{Deserialize, Serialize};
+use ssi::jwk::{Base64urlUInt, OctetParams, Params, JWK};
+
+use crate::state::AuthnState;
+
+pub(crate) const PRE_AUTHORIZED_CODE_GRANT: &str =
+    "urn:ietf:params:oauth:grant-type:pre-authorized_code";
+
+/// How long codes, access tokens and nonces are valid for, in seconds.
+pub(crate) const EXPIRES_IN: i64 = 60 * 5;
+
+/// An authorization code handed to the wallet after the user signs in.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IssuanceCode {
+    pub(crate) offer_id: CredentialOfferId,
+    pub(crate) redirect_uri: String,
+    pub(crate) code_challenge: String,
+}
+
+/// An access token for the credential endpoint.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IssuanceToken {
+    pub(crate) offer_id: CredentialOfferId,
+    pub(crate) c_nonce: String,
+}
+
+pub(crate) static ISSUANCE_CODE: TypedRedis<IssuanceCode> = TypedRedis::new("mist-issuance-code");
+pub(crate) static ISSUANCE_TOKEN: TypedRedis<IssuanceToken> =
+    TypedRedis::new("mist-issuance-token");
+
+pub(crate) fn issuer_url(authn_url: &str, service_id: &ServiceId) -> String {
+    format!("{authn_url}/issuers/{service_id}")
+}
+
+/// Get the offer, as long as it belongs to the service and can still be redeemed.
+pub(crate) async fn redeemable_offer(
+    state: &AuthnState,
+    service_id: &ServiceId,
+    offer: CredentialOffer,
+) -> Result<CredentialOffer> {
+    let configuration = state
+        .repos
+        .credential_configurations
+        .get(&offer.configuration_id.into())
+        .await?;
+
+    if configuration.service_id != service_id.0
+        || offer.redeemed_at.is_some()
+        || offer.expires_at < chrono::Utc::now()
+    {
+        return Err(eyre!("offer can not be redeemed").into());
+    }
+
+    Ok(offer)
+}
+
+/// The key a service signs its credentials with.
+pub(crate) struct IssuerKey {
+    pub(crate) did: String,
+    pub(crate) jwk: JWK,
+}
+
+impl IssuerKey {
+    /// Derive the service's current Ed25519 issuer key, identified by a `did:key`.
+    pub(crate) async fn preferred(state: &AuthnState, service_id: &ServiceId) -> Result<Self> {
+        let key = state
+            .repos
+            .keys
+            .preferred(service_id, &KeyKind::Issuer)
+            .await?;
+
+        let secret = decrypt_service_key(&state.env.master_key, &key.value)?;
+
+        Self::from_secret(&secret)
+    }
+
+    fn from_secret(secret: &[u8]) -> Result<Self> {
+        let signing_key = SigningKey::from_bytes(
+            secret
+                .try_into()
+                .map_err(|_| eyre!("issuer key has the wrong length"))?,
+        );
+        let public_key = signing_key.verifying_key().to_bytes();
+
+        // Multicodec prefix for an Ed25519 public key.
+        let fingerprint = multibase::encode(
+            multibase::Base::Base58Btc,
+            [&[0xed, 0x01], public_key.as_slice()].concat(),
+        );
+        let did = format!("did:key:{fingerprint}");
+
+        let mut jwk = JWK::from(Params::OKP(OctetParams {
+            curve: "Ed25519".into(),
+            public_key: Base64urlUInt(public_key.to_vec()),
+            private_key: Some(Base64urlUInt(secret.to_vec())),
+        }));
+        jwk.key_id = Some(format!("{did}#{fingerprint}"));
+
+        Ok(Self { did, jwk })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssi::jwk::Algorithm;
+
+    use super::*;
+
+    #[test]
+    fn derives_did_key() -> Result<()> {
+        // The secret key from RFC 8032, test 1.
+        let key = IssuerKey::from_secret(&hex::decode(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        )?)?;
+
+        assert_eq!(
+            key.did,
+            "did:key:z6MktwupdmLXVVqTzCw4i46r4uGyosGXRnR3XjN4Zq7oMMsw"
+        );
+
+        let jwt = ssi::jwt::encode_sign(Algorithm::EdDSA, &serde_json::json!({}), &key.jwk)?;
+        ssi::jwt::decode_verify::<serde_json::Value>(&jwt, &key.jwk.to_public())?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,17 @@
 pub mod app;
+mod consent;
+mod device;
+mod dpop;
 mod events;
 mod handlers;
+mod issuance;
+mod logout;
+mod recovery;
+mod registration;
 mod session;
+mod sign_in;
+mod sso;
 mod state;
+mod tokens;
 mod utils;
 mod views;
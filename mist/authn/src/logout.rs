@@ -0,0 +1,149 @@
+// Logging users out, and telling services about it.
+//
+// Ending a session tells its service in whichever ways it's registered for:
+//
+// - Back-channel: a logout token, signed (HS256) with the service's API key, is
+//   POSTed to its `backchannel_logout_uri` through the job runner.
+// - Front-channel: the signed out page loads its `frontchannel_logout_uri` in a
+//   hidden iframe, with `iss` and `sid`, so it can clear its own cookies.
+//
+// Logging out from a service (RP-initiated logout) also ends the browser's sessions
+// for the other services in its SSO domain, as they were all signed in together.
+//
+// See: https://openid.net/specs/openid-connect-backchannel-1_0.html
+// See: https://openid.net/specs/openid-connect-frontchannel-1_0.html
+// -------------------------------------------------------------------------------
+
+use axum::response::{IntoResponse, Redirect, Response};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use mist_common::Result;
+use mist_db::models::{
+    service::{Service, ServiceId},
+    user::UserId,
+};
+use mist_jobs::jobs::BackChannelLogout;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+use crate::{
+    session::{self, AuthSession, AuthState, SessionId, AUTH_SESSION, SID},
+    state::AuthnState,
+    utils::client_auth,
+    views,
+};
+
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// How long services have to act on a logout token, in seconds.
+const LOGOUT_TOKEN_EXPIRES_IN: i64 = 60 * 2;
+
+/// See: https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+#[derive(Serialize)]
+struct LogoutTokenClaims {
+    iss: String,
+    sub: UserId,
+    aud: ServiceId,
+    iat: i64,
+    exp: i64,
+    jti: Uuid,
+    sid: String,
+    events: Map<String, Value>,
+}
+
+/// End the session, take it off the browser, and let its service know over the
+/// back-channel. Returns the service's front-channel logout page, if it has one.
+pub(crate) async fn end(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service: &Service,
+    session_id: &SessionId,
+    session: &AuthSession,
+) -> Result<Option<String>> {
+    cookies.remove(session::cookie(service, session_id, !state.env.development));
+
+    // Sessions that never finished signing in aren't indexed, which is fine.
+    if let Err(err) = state
+        .repos
+        .sessions
+        .destroy(&service.id, &session.user_id, session_id)
+        .await
+    {
+        if !err.is_not_found() {
+            return Err(err);
+        }
+    }
+
+    AUTH_SESSION
+        .del(&state.redis, &session_id.to_string())
+        .await?;
+    SID.del(&state.redis, &session.sid).await?;
+
+    // Services only know about sessions that got as far as signing in.
+    let signed_in = matches!(session.state, AuthState::Authenticated { .. });
+
+    if let (Some(url), true) = (&service.backchannel_logout_uri, signed_in) {
+        let logout_token = logout_token(state, service, session).await?;
+
+        BackChannelLogout::publish(&state.jetstream, url, &logout_token).await?;
+    }
+
+    Ok(frontchannel_url(state, service, session))
+}
+
+/// Send the user on, after loading any front-channel logout pages.
+pub(crate) fn finish(
+    service: &Service,
+    frontchannel_urls: &[String],
+    redirect_url: &str,
+) -> Response {
+    if frontchannel_urls.is_empty() {
+        return Redirect::to(redirect_url).into_response();
+    }
+
+    views::logged_out::view(service, frontchannel_urls, redirect_url).into_response()
+}
+
+fn frontchannel_url(
+    state: &AuthnState,
+    service: &Service,
+    session: &AuthSession,
+) -> Option<String> {
+    let mut url = Url::parse(service.frontchannel_logout_uri.as_ref()?).ok()?;
+
+    url.query_pairs_mut()
+        .append_pair("iss", &state.env.authn_url)
+        .append_pair("sid", &session.sid);
+
+    Some(url.into())
+}
+
+async fn logout_token(
+    state: &AuthnState,
+    service: &Service,
+    session: &AuthSession,
+) -> Result<String> {
+    let now = Utc::now().timestamp();
+    let claims = LogoutTokenClaims {
+        iss: state.env.authn_url.clone(),
+        sub: session.user_id,
+        aud: service.id,
+        iat: now,
+        exp: now + LOGOUT_TOKEN_EXPIRES_IN,
+        jti: Uuid::new_v4(),
+        sid: session.sid.clone(),
+        events: Map::from_iter([(BACKCHANNEL_LOGOUT_EVENT.to_string(), json!({}))]),
+    };
+
+    let mut header = Header::new(Algorithm::HS256);
+    header.typ = Some("logout+jwt".into());
+
+    Ok(jsonwebtoken::encode(
+        &header,
+        &claims,
+        &EncodingKey::from_secret(&client_auth::api_key(state, &service.id).await?),
+    )?)
+}
@@ -0,0 +1,242 @@
+// Account recovery, for users who've lost their wallet.
+//
+// The user scans with their new wallet, presenting one of:
+//
+// - the credential they registered with, reissued to the new wallet, if the service
+//   has a `recovery_claim`,
+// - a DID the service set aside for them through the API, as the new wallet,
+// - or nothing, having opened an approval link the service sent them.
+//
+// However they do it, the new wallet's DID is added to their existing account, and
+// the method is used up.
+// -------------------------------------------------------------------------------
+
+use std::collections::BTreeMap;
+
+use mist_common::{redis::TypedRedis, Result};
+use mist_db::models::{
+    identifier::CreateIdentifier,
+    recovery_method::{RecoveryKind, RecoveryMethod},
+    service::Service,
+    user::UserId,
+};
+use mist_jobs::jobs;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    handlers::verify_response::Claim,
+    session::{machine, AuthSession, AuthState, SessionId},
+    state::AuthnState,
+};
+
+/// Approval codes, by the recovery session they were opened in.
+pub(crate) static RECOVERY_GRANT: TypedRedis<String> = TypedRedis::new("mist-recovery-grant");
+
+/// How long an opened approval link stays tied to its session, in seconds.
+pub(crate) const RECOVERY_GRANT_EXPIRES_IN: i64 = 60 * 60;
+
+#[derive(Serialize)]
+pub(crate) struct RecoveredData {
+    id: UserId,
+    identifier: String,
+    method: RecoveryKind,
+    session_id: SessionId,
+}
+
+/// Identifies a credential without storing the claim itself.
+pub(crate) fn fingerprint(name: &str, claim: &Claim) -> Result<String> {
+    let bytes = serde_json::to_vec(&(name, &claim.issuer, &claim.value))?;
+
+    Ok(hex::encode(Sha256::digest(bytes)))
+}
+
+/// Add the new wallet to whichever account it can recover, and sign the user in.
+pub(crate) async fn recover(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+    service: &Service,
+    did: &str,
+    profile: &BTreeMap<String, Claim>,
+) -> Result<()> {
+    // Wallets that already have an account can just sign in.
+    // --------------------------------------------------------
+
+    match state
+        .repos
+        .identifiers
+        .get_by_value_for_service(&service.id, did)
+        .await
+    {
+        Ok(_) => {
+            machine::reject(
+                state,
+                session_id,
+                "already_registered",
+                "This wallet already has an account, sign in instead.",
+            )
+            .await?;
+
+            return Ok(());
+        }
+        Err(err) if err.is_not_found() => {}
+        Err(err) => return Err(err),
+    }
+
+    // Work out whose account it is.
+    // -----------------------------
+
+    // Every method is single-use, so a leaked credential or code can't be used to
+    // take the account over again later.
+    let method = match find_method(state, session_id, service, did, profile).await? {
+        Some(method) => found(state.repos.recovery_methods.take(&method.id).await)?,
+        None => None,
+    };
+
+    let Some(method) = method else {
+        machine::reject(
+            state,
+            session_id,
+            "no_recovery",
+            "We couldn't find an account to recover with this wallet.",
+        )
+        .await?;
+
+        return Ok(());
+    };
+
+    // Add the wallet to the account and sign them in.
+    // -----------------------------------------------
+
+    let identifier = state
+        .repos
+        .identifiers
+        .create(
+            &CreateIdentifier::builder()
+                .value(did)
+                .user_id(method.user_id)
+                .build(),
+        )
+        .await?;
+
+    machine::replace(
+        state,
+        session_id,
+        session,
+        AuthSession {
+            user_id: method.user_id,
+            ..session.with_state(AuthState::Authenticated {
+                identifier_id: identifier.id,
+                claims: Default::default(),
+            })
+        },
+    )
+    .await?;
+
+    // Keep a record of it, and let the service know.
+    // ----------------------------------------------
+
+    tracing::info!(
+        target: "audit",
+        service_id = %service.id,
+        user_id = %method.user_id,
+        method = ?method.kind,
+        identifier = did,
+        "account recovered"
+    );
+
+    jobs::Webhook::publish(
+        &state.jetstream,
+        &service.webhook_url,
+        "user.recovered",
+        &RecoveredData {
+            id: method.user_id,
+            identifier: service.subject(did)?,
+            method: method.kind,
+            session_id: session_id.parse()?,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn find_method(
+    state: &AuthnState,
+    session_id: &str,
+    service: &Service,
+    did: &str,
+    profile: &BTreeMap<String, Claim>,
+) -> Result<Option<RecoveryMethod>> {
+    let methods = &state.repos.recovery_methods;
+
+    // An approval, from the link the user opened.
+    if let Ok(code) = RECOVERY_GRANT.get(&state.redis, session_id).await {
+        RECOVERY_GRANT.del(&state.redis, session_id).await?;
+
+        return found(
+            methods
+                .find(&service.id, &RecoveryKind::Approval, &code)
+                .await,
+        );
+    }
+
+    // A DID set aside ahead of time.
+    if let Some(method) = found(methods.find(&service.id, &RecoveryKind::Did, did).await)? {
+        return Ok(Some(method));
+    }
+
+    // The credential they registered with.
+    let claim = service
+        .recovery_claim
+        .as_ref()
+        .and_then(|name| Some((name, profile.get(name)?)));
+
+    match claim {
+        Some((name, claim)) => {
+            let fingerprint = fingerprint(name, claim)?;
+
+            found(
+                methods
+                    .find(&service.id, &RecoveryKind::Credential, &fingerprint)
+                    .await,
+            )
+        }
+        None => Ok(None),
+    }
+}
+
+fn found<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.is_not_found() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn fingerprints_depend_on_the_issuer() -> Result<()> {
+        let claim = |issuer: &str| Claim {
+            value: json!("jane@example.com"),
+            issuer: issuer.into(),
+        };
+
+        assert_eq!(
+            fingerprint("email", &claim("did:example:issuer"))?,
+            fingerprint("email", &claim("did:example:issuer"))?
+        );
+        assert_ne!(
+            fingerprint("email", &claim("did:example:issuer"))?,
+            fingerprint("email", &claim("did:example:someone-else"))?
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+// Finishing registrations, once the service has decided what to do with them.
+//
+// The decision either comes back through `/complete`, sent with the service's API
+// key, or, for services using the `sync` webhook mode, in the webhook's response.
+// Sign ins use the same decisions, see `sign_in`.
+// -------------------------------------------------------------------------------
+
+use std::time::Duration;
+
+use eyre::eyre;
+use mist_common::Result;
+use mist_db::models::{
+    identifier::{CreateIdentifier, Identifier},
+    recovery_method::{CreateRecoveryMethod, RecoveryKind},
+    service::Service,
+    user::{CreateUser, User, UserId},
+};
+use mist_jobs::jobs;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    session::{machine, AuthSession, AuthState, SessionId},
+    state::AuthnState,
+};
+
+/// How long to wait on a `sync` webhook before giving up on the registration.
+pub(crate) const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a `sync` webhook responds with.
+#[derive(Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub(crate) enum Decision {
+    Accept {
+        /// The service's own id for the user. Ignored for sign ins.
+        external_id: Option<String>,
+        /// Extra claims to attach to the user's session.
+        #[serde(default)]
+        claims: Map<String, Value>,
+    },
+    Reject {
+        #[serde(default = "default_code")]
+        code: String,
+        reason: String,
+    },
+}
+
+fn default_code() -> String {
+    "rejected".into()
+}
+
+#[derive(Serialize)]
+pub(crate) struct RejectedData {
+    id: UserId,
+    identifier: String,
+    session_id: SessionId,
+    code: String,
+    message: String,
+}
+
+/// Create the user and sign them in.
+pub(crate) async fn accept(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+    external_id: Option<String>,
+    claims: Map<String, Value>,
+) -> Result<()> {
+    let AuthState::Registering {
+        identifier,
+        recovery,
+    } = &session.state
+    else {
+        return Err(eyre!("session is not registering").into());
+    };
+
+    // Create the user and associated DID if they don't already exist.
+    // ---------------------------------------------------------------
+
+    let existing = state
+        .repos
+        .identifiers
+        .get_by_value_for_service(&session.service_id, identifier)
+        .await;
+
+    if existing.is_ok() {
+        machine::reject(
+            state,
+            session_id,
+            "already_registered",
+            "You already have an account.",
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    // Claim the registration first, so if the decision arrives twice, or the session
+    // moves on in the meantime, nothing gets created.
+    let accepted = machine::transition(state, session_id, session, AuthState::Accepted).await?;
+
+    let (user, identifier) = match create(state, session, identifier, external_id).await {
+        Ok(created) => created,
+        Err(err) => {
+            tracing::error!(%err, "couldn't create registered user");
+
+            machine::reject(
+                state,
+                session_id,
+                "registration_failed",
+                "We couldn't finish setting up your account, please try again later.",
+            )
+            .await?;
+
+            return Err(err);
+        }
+    };
+
+    // Let them recover their account with the credential they registered with.
+    // -------------------------------------------------------------------------
+
+    if let Some(fingerprint) = recovery {
+        let created = state
+            .repos
+            .recovery_methods
+            .create(
+                &CreateRecoveryMethod::builder()
+                    .kind(RecoveryKind::Credential)
+                    .value(fingerprint)
+                    .service_id(session.service_id)
+                    .user_id(user.id)
+                    .build(),
+            )
+            .await;
+
+        // Most likely someone else registered with the same credential, in which case
+        // it can't tell them apart anyway.
+        if let Err(err) = created {
+            tracing::warn!(%err, "couldn't save recovery credential");
+        }
+    }
+
+    // Complete the registration process.
+    // ----------------------------------
+
+    machine::replace(
+        state,
+        session_id,
+        &accepted,
+        AuthSession {
+            user_id: user.id,
+            ..accepted.with_state(AuthState::Authenticated {
+                identifier_id: identifier.id,
+                claims,
+            })
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn create(
+    state: &AuthnState,
+    session: &AuthSession,
+    identifier: &str,
+    external_id: Option<String>,
+) -> Result<(User, Identifier)> {
+    let user = state
+        .repos
+        .users
+        .create(
+            &CreateUser::builder()
+                .id(session.user_id)
+                .service_id(session.service_id)
+                .maybe_external_id(external_id)
+                .build(),
+        )
+        .await?;
+
+    let identifier = state
+        .repos
+        .identifiers
+        .create(
+            &CreateIdentifier::builder()
+                .value(identifier)
+                .user_id(session.user_id)
+                .build(),
+        )
+        .await?;
+
+    Ok((user, identifier))
+}
+
+/// Turn the user away, and let the service know it happened.
+pub(crate) async fn reject(
+    state: &AuthnState,
+    service: &Service,
+    session_id: &str,
+    session: &AuthSession,
+    code: String,
+    message: String,
+) -> Result<()> {
+    let AuthState::Registering { identifier, .. } = &session.state else {
+        return Err(eyre!("session is not registering").into());
+    };
+
+    machine::reject(state, session_id, &code, &message).await?;
+
+    jobs::Webhook::publish(
+        &state.jetstream,
+        &service.webhook_url,
+        "registration.rejected",
+        &RejectedData {
+            id: session.user_id,
+            identifier: service.subject(identifier)?,
+            session_id: session_id.parse()?,
+            code,
+            message,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reads_claims() {
+        let accepted =
+            serde_json::from_value(json!({ "decision": "accept", "claims": { "plan": "pro" } }));
+
+        assert!(matches!(
+            accepted,
+            Ok(Decision::Accept { external_id: None, claims }) if claims["plan"] == "pro"
+        ));
+    }
+
+    #[test]
+    fn reads_decisions() {
+        let accepted =
+            serde_json::from_value(json!({ "decision": "accept", "external_id": "user_123" }));
+        let rejected = serde_json::from_value(json!({ "decision": "reject", "reason": "Sorry!" }));
+
+        assert!(matches!(
+            accepted,
+            Ok(Decision::Accept { external_id: Some(id), claims }) if id == "user_123" && claims.is_empty()
+        ));
+        assert!(matches!(
+            rejected,
+            Ok(Decision::Reject { code, reason }) if code == "rejected" && reason == "Sorry!"
+        ));
+    }
+}
@@ -1,40 +1,306 @@
-use derive_more::{AsRef, Display, From, FromStr, Into};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+};
+use http::request::Parts;
 use mist_common::redis::TypedRedis;
-use mist_db::models::{identifier::IdentifierId, service::ServiceId, user::UserId};
+pub use mist_db::models::session::SessionId;
+use mist_db::{
+    models::{
+        identifier::IdentifierId,
+        service::{Service, ServiceId},
+        user::UserId,
+    },
+    repos::sessions::AUTH_SESSION_PREFIX,
+};
+use openidconnect::CsrfToken;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use serde_json::{Map, Value};
+use tower_cookies::{
+    cookie::{time::Duration, SameSite},
+    Cookie, Cookies,
+};
 
-pub(crate) const COOKIE_KEY: &str = "mist";
+use crate::state::AuthnState;
 
-#[derive(Default, Debug, Display, Serialize, Deserialize, AsRef, From, FromStr, Into)]
-pub struct SessionId(pub Uuid);
+pub(crate) mod machine;
 
-impl SessionId {
-    pub fn new() -> Self {
-        Self(Uuid::new_v4())
-    }
-}
+pub(crate) const COOKIE_KEY: &str = "mist";
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct AuthSession {
     pub(crate) user_id: UserId,
     pub(crate) service_id: ServiceId,
     pub(crate) state: AuthState,
+    /// When the user has to have finished authenticating by, as a unix timestamp. Once
+    /// they're signed in, when the session ends however active they are.
+    pub(crate) expires_at: i64,
+    /// Once signed in, how long the session lasts without being used, in seconds.
+    #[serde(default)]
+    pub(crate) idle_timeout: Option<i64>,
+    #[serde(default)]
+    pub(crate) client: Client,
+    #[serde(default)]
+    pub(crate) return_to: ReturnTo,
+    /// Started for a device signing in with a user code, which gets the tokens. The
+    /// browser only confirms the session.
+    #[serde(default)]
+    pub(crate) device: bool,
+    /// The user agreed to share their data with a service that asks first.
+    #[serde(default)]
+    pub(crate) consented: bool,
+    /// Names the session to its service, in ID and logout tokens. Unlike the session
+    /// ID, it can't be used as the session's cookie.
+    #[serde(default)]
+    pub(crate) sid: String,
+}
+
+/// Where the service asked for the user to be sent once they're signed in.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct ReturnTo {
+    /// One of the service's registered redirect URIs, in place of its `redirect_url`.
+    pub(crate) redirect_uri: Option<String>,
+    /// Handed back to the service untouched.
+    pub(crate) state: Option<String>,
+}
+
+/// The browser that started the session.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Client {
+    pub(crate) user_agent: Option<String>,
+    pub(crate) ip: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[async_trait]
+impl FromRequestParts<AuthnState> for Client {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AuthnState,
+    ) -> Result<Self, Self::Rejection> {
+        let connected = Option::<ConnectInfo<SocketAddr>>::from_request_parts(parts, state)
+            .await?
+            .map(|ConnectInfo(address)| address.ip());
+        let header = |name| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let user_agent = header("user-agent");
+        let ip = connected
+            .map(|ip| forwarded_for(ip, header("x-forwarded-for"), &state.env.trusted_proxies));
+
+        Ok(Self {
+            user_agent,
+            ip: ip.map(|ip| ip.to_string()),
+        })
+    }
+}
+
+/// Who a request came from, given the address it was received from.
+///
+/// Anyone can send `x-forwarded-for`, so hops are only believed while they were
+/// added by a trusted proxy. Walking back from the nearest, the first hop that
+/// isn't one of those is the client.
+fn forwarded_for(connected: IpAddr, header: Option<String>, trusted: &[IpAddr]) -> IpAddr {
+    let mut client = connected;
+
+    let Some(header) = header else {
+        return client;
+    };
+
+    for hop in header.rsplit(',') {
+        if !trusted.contains(&client) {
+            break;
+        }
+
+        match hop.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+
+    client
+}
+
+impl AuthSession {
+    pub(crate) fn with_state(&self, state: AuthState) -> Self {
+        Self {
+            state,
+            ..self.clone()
+        }
+    }
+}
+
+/// Where the session is in the auth flow. See [`machine`] for how it moves between them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum AuthState {
-    Authenticating { action: AuthAction },
-    Registering { identifier: String },
-    Authenticated { identifier_id: IdentifierId },
+    /// Waiting for the user to scan the QR code.
+    Authenticating {
+        action: AuthAction,
+        /// How many times the auth request has been replaced after expiring.
+        refreshes: u32,
+    },
+    /// The wallet has responded.
+    Scanned {
+        action: AuthAction,
+    },
+    /// Checking the wallet's response.
+    Verifying {
+        action: AuthAction,
+    },
+    /// Waiting for the service to finish registering the user.
+    Registering {
+        identifier: String,
+        /// Fingerprint of the credential the user can recover their account with.
+        #[serde(default)]
+        recovery: Option<String>,
+    },
+    /// The service accepted the registration, and the user is being created.
+    Accepted,
+    /// Waiting for the service to let the user in.
+    SigningIn {
+        identifier_id: IdentifierId,
+    },
+    Authenticated {
+        identifier_id: IdentifierId,
+        /// Extra claims the service attached when letting the user in.
+        #[serde(default)]
+        claims: Map<String, Value>,
+    },
+    Rejected {
+        /// A machine-readable code, like `under_age`.
+        code: String,
+        /// Why, in a way that can be shown to the user.
+        reason: String,
+    },
+    Expired,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum AuthAction {
     Up,
     In,
+    /// Add another wallet to a signed in user.
+    Link,
+    /// Get back into an account after losing its wallet.
+    Recover,
 }
 
-pub(crate) static AUTH_SESSION: TypedRedis<AuthSession> = TypedRedis::new("mist-auth");
+impl AuthAction {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::In => "in",
+            Self::Link => "link",
+            Self::Recover => "recover",
+        }
+    }
+}
+
+pub(crate) static AUTH_SESSION: TypedRedis<AuthSession> = TypedRedis::new(AUTH_SESSION_PREFIX);
+
+/// One-time codes handed to the wallet so it can bring the user's browser back to
+/// their session, even if it opens the link somewhere the cookie isn't set.
+pub(crate) static RESUME_CODE: TypedRedis<SessionId> = TypedRedis::new("mist-resume");
+
+/// Signed in sessions by their `sid`, for services naming them in ID token hints.
+pub(crate) static SID: TypedRedis<SessionId> = TypedRedis::new("mist-sid");
+
+/// A fresh `sid` for a new session.
+pub(crate) fn new_sid() -> String {
+    CsrfToken::new_random_len(32).secret().clone()
+}
+
+/// Each service gets a cookie of its own, so signing in to one never touches another's
+/// session.
+pub(crate) fn cookie_key(service_id: &ServiceId) -> String {
+    format!("{COOKIE_KEY}-{service_id}")
+}
+
+/// The cookie is set before the user signs in, so it outlives the session by the time
+/// they have to do that. Redis decides when the session actually ends.
+pub(crate) fn cookie(service: &Service, session_id: &SessionId, secure: bool) -> Cookie<'static> {
+    Cookie::build((cookie_key(&service.id), session_id.to_string()))
+        .secure(secure)
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .max_age(Duration::seconds(
+            service.session_lifetime + machine::EXPIRES_IN,
+        ))
+        .path("/")
+        .build()
+}
+
+/// The service's session in this browser, if it has one.
+pub(crate) fn from_cookies(cookies: &Cookies, service_id: &ServiceId) -> Option<SessionId> {
+    cookies
+        .get(&cookie_key(service_id))
+        .and_then(|cookie| cookie.value().parse().ok())
+}
+
+/// Every service's session in this browser.
+pub(crate) fn all_from_cookies(cookies: &Cookies) -> Vec<(ServiceId, SessionId)> {
+    cookies
+        .list()
+        .iter()
+        .filter_map(|cookie| {
+            let service_id = cookie.name().strip_prefix(&format!("{COOKIE_KEY}-"))?;
+
+            Some((
+                ServiceId(service_id.parse().ok()?),
+                cookie.value().parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn only_trusts_forwarded_for_from_trusted_proxies() {
+        let header = Some("1.1.1.1, 2.2.2.2".to_string());
+        let proxies = [ip("10.0.0.1")];
+
+        assert_eq!(
+            forwarded_for(ip("3.3.3.3"), header.clone(), &proxies),
+            ip("3.3.3.3")
+        );
+        assert_eq!(
+            forwarded_for(ip("10.0.0.1"), header.clone(), &proxies),
+            ip("2.2.2.2")
+        );
+        assert_eq!(
+            forwarded_for(ip("10.0.0.1"), None, &proxies),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn walks_back_through_trusted_proxies() {
+        let header = Some("1.1.1.1, 2.2.2.2, 10.0.0.2".to_string());
+        let proxies = [ip("10.0.0.1"), ip("10.0.0.2")];
+
+        assert_eq!(
+            forwarded_for(ip("10.0.0.1"), header, &proxies),
+            ip("2.2.2.2")
+        );
+    }
+}
@@ -0,0 +1,421 @@
+// The auth session state machine.
+//
+//   Authenticating → Scanned → Verifying → Registering → Accepted → Authenticated
+//                                     ├─→ SigningIn ──────────────→ Authenticated
+//                                     └───────────────────────────→ Authenticated
+//
+// Signing in with a wallet we don't know can also send the session back to
+// `Authenticating`, to sign up instead.
+//
+// Any unfinished state can also become `Rejected` or `Expired`. Finished states
+// are final, so starting over means starting a new session.
+//
+// Every transition is a compare-and-set against the session we read, so two
+// requests racing on the same session can't both move it forward.
+// -------------------------------------------------------------------------------
+
+use chrono::Utc;
+use eyre::eyre;
+use fred::types::Expiration;
+use mist_common::Result;
+use mist_db::models::{service::Service, session::CreateSession};
+
+use super::{AuthAction, AuthSession, AuthState, AUTH_SESSION, SID};
+use crate::{
+    events::{self, Event},
+    state::AuthnState,
+};
+
+/// How long the user has to finish authenticating, in seconds.
+pub(crate) const EXPIRES_IN: i64 = 60 * 5;
+
+/// How long failed sessions are kept around, so the browser can find out what happened.
+const FAILED_EXPIRES_IN: i64 = 60 * 5;
+
+impl AuthState {
+    pub(crate) fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            Self::Authenticated { .. } | Self::Rejected { .. } | Self::Expired
+        )
+    }
+
+    pub(crate) fn can_become(&self, next: &AuthState) -> bool {
+        match (self, next) {
+            // Refreshing the auth request.
+            (Self::Authenticating { .. }, Self::Authenticating { .. })
+            | (Self::Authenticating { .. }, Self::Scanned { .. })
+            | (Self::Scanned { .. }, Self::Verifying { .. })
+            | (
+                Self::Verifying {
+                    action: AuthAction::In,
+                },
+                Self::Authenticating {
+                    action: AuthAction::Up,
+                    ..
+                },
+            )
+            | (
+                Self::Verifying { .. },
+                Self::Registering { .. } | Self::SigningIn { .. } | Self::Authenticated { .. },
+            )
+            | (Self::Registering { .. }, Self::Accepted)
+            | (Self::Accepted | Self::SigningIn { .. }, Self::Authenticated { .. }) => true,
+            (current, Self::Rejected { .. } | Self::Expired) => !current.is_finished(),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Authenticating { .. } => "authenticating",
+            Self::Scanned { .. } => "scanned",
+            Self::Verifying { .. } => "verifying",
+            Self::Registering { .. } => "registering",
+            Self::Accepted => "accepted",
+            Self::SigningIn { .. } => "signing_in",
+            Self::Authenticated { .. } => "authenticated",
+            Self::Rejected { .. } => "rejected",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+/// How long Redis should keep the session for.
+pub(crate) fn expiration(session: &AuthSession) -> Expiration {
+    match session.state {
+        AuthState::Authenticated { .. } => Expiration::EXAT(ends_at(session)),
+        AuthState::Rejected { .. } | AuthState::Expired => Expiration::EX(FAILED_EXPIRES_IN),
+        _ => Expiration::EXAT(session.expires_at + FAILED_EXPIRES_IN),
+    }
+}
+
+/// When a signed in session ends if it isn't used again: once it's been idle for too
+/// long, but never past its absolute lifetime.
+pub(crate) fn ends_at(session: &AuthSession) -> i64 {
+    match session.idle_timeout {
+        Some(idle_timeout) => (Utc::now().timestamp() + idle_timeout).min(session.expires_at),
+        None => session.expires_at,
+    }
+}
+
+/// Start the session's lifetime as it's signed in, following the service's policy.
+pub(crate) fn signed_in(session: AuthSession, service: &Service) -> AuthSession {
+    AuthSession {
+        expires_at: Utc::now().timestamp() + service.session_lifetime,
+        idle_timeout: service.session_idle_timeout,
+        ..session
+    }
+}
+
+/// Push back a signed in session's idle timeout, as the user is still around.
+pub(crate) async fn keep_alive(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+) -> Result<()> {
+    if !matches!(session.state, AuthState::Authenticated { .. }) || session.idle_timeout.is_none() {
+        return Ok(());
+    }
+
+    let ends_at = ends_at(session);
+
+    AUTH_SESSION
+        .expire_at(&state.redis, session_id, ends_at)
+        .await?;
+    state
+        .repos
+        .sessions
+        .keep_alive(&session_id.parse()?, ends_at)
+        .await?;
+
+    Ok(())
+}
+
+/// Get the session, expiring it first if the user ran out of time.
+pub(crate) async fn load(state: &AuthnState, session_id: &str) -> Result<AuthSession> {
+    let session = AUTH_SESSION.get(&state.redis, session_id).await?;
+
+    if session.state.is_finished() || session.expires_at > Utc::now().timestamp() {
+        return Ok(session);
+    }
+
+    match transition(state, session_id, &session, AuthState::Expired).await {
+        Ok(session) => Ok(session),
+        // Someone else moved it on first, so go with whatever they did.
+        Err(_) => Ok(AUTH_SESSION.get(&state.redis, session_id).await?),
+    }
+}
+
+/// Move the session to the next state, as long as that's allowed and nobody else
+/// has changed it since it was read.
+///
+/// Anyone waiting on the session is sent an [`Event`] for the new state.
+pub(crate) async fn transition(
+    state: &AuthnState,
+    session_id: &str,
+    current: &AuthSession,
+    next: AuthState,
+) -> Result<AuthSession> {
+    replace(state, session_id, current, current.with_state(next)).await
+}
+
+/// Like [`transition`], but for when more than the state changes.
+pub(crate) async fn replace(
+    state: &AuthnState,
+    session_id: &str,
+    current: &AuthSession,
+    next: AuthSession,
+) -> Result<AuthSession> {
+    let (from, to) = (current.state.name(), next.state.name());
+
+    if !current.state.can_become(&next.state) {
+        tracing::warn!(session_id, from, to, "illegal auth session transition");
+
+        return Err(eyre!("auth session can not go from {from} to {to}").into());
+    }
+
+    let next = match next.state {
+        AuthState::Authenticated { .. } => {
+            let service = state.repos.services.get(&next.service_id).await?;
+
+            signed_in(next, &service)
+        }
+        _ => next,
+    };
+
+    let replaced = AUTH_SESSION
+        .compare_and_set(&state.redis, session_id, current, &next, expiration(&next))
+        .await?;
+
+    if !replaced {
+        tracing::warn!(
+            session_id,
+            from,
+            to,
+            "auth session changed during transition"
+        );
+
+        return Err(eyre!("auth session changed during transition").into());
+    }
+
+    if matches!(next.state, AuthState::Authenticated { .. }) {
+        record(state, session_id, &next).await?;
+    }
+
+    events::publish(&state.nats, session_id, &Event::from(&next.state)).await;
+
+    Ok(next)
+}
+
+/// Index a session that's just been signed in, so it can be listed and revoked.
+pub(crate) async fn record(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+) -> Result<()> {
+    state
+        .repos
+        .sessions
+        .create(
+            &CreateSession::builder()
+                .id(session_id.parse()?)
+                .service_id(session.service_id)
+                .user_id(session.user_id)
+                .maybe_user_agent(session.client.user_agent.clone())
+                .maybe_ip(session.client.ip.clone())
+                .ends_at(ends_at(session))
+                .build(),
+        )
+        .await?;
+
+    SID.set(
+        &state.redis,
+        &session.sid,
+        &session_id.parse()?,
+        Expiration::EXAT(session.expires_at),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Reject the session with a code and reason, unless it's already finished.
+pub(crate) async fn reject(
+    state: &AuthnState,
+    session_id: &str,
+    code: &str,
+    reason: &str,
+) -> Result<()> {
+    let session = AUTH_SESSION.get(&state.redis, session_id).await?;
+
+    if session.state.is_finished() {
+        return Ok(());
+    }
+
+    transition(
+        state,
+        session_id,
+        &session,
+        AuthState::Rejected {
+            code: code.into(),
+            reason: reason.into(),
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authenticated(expires_in: i64, idle_timeout: Option<i64>) -> AuthSession {
+        AuthSession {
+            user_id: Default::default(),
+            service_id: Default::default(),
+            state: AuthState::Authenticated {
+                identifier_id: Default::default(),
+                claims: Default::default(),
+            },
+            expires_at: Utc::now().timestamp() + expires_in,
+            idle_timeout,
+            client: Default::default(),
+            return_to: Default::default(),
+            device: false,
+            consented: false,
+            sid: Default::default(),
+        }
+    }
+
+    #[test]
+    fn idle_sessions_end_early() {
+        let session = authenticated(60 * 60, Some(60));
+
+        assert!(ends_at(&session) <= Utc::now().timestamp() + 60);
+    }
+
+    #[test]
+    fn activity_never_outlasts_the_lifetime() {
+        let session = authenticated(60, Some(60 * 60));
+
+        assert_eq!(ends_at(&session), session.expires_at);
+
+        let session = authenticated(60, None);
+
+        assert_eq!(ends_at(&session), session.expires_at);
+    }
+
+    #[test]
+    fn follows_the_flow() {
+        let action = AuthAction::Up;
+        let flow = [
+            AuthState::Authenticating {
+                action: action.clone(),
+                refreshes: 0,
+            },
+            AuthState::Scanned {
+                action: action.clone(),
+            },
+            AuthState::Verifying { action },
+            AuthState::Registering {
+                identifier: "did:example:123".into(),
+                recovery: None,
+            },
+            AuthState::Accepted,
+            AuthState::Authenticated {
+                identifier_id: Default::default(),
+                claims: Default::default(),
+            },
+        ];
+
+        for pair in flow.windows(2) {
+            assert!(pair[0].can_become(&pair[1]));
+            assert!(!pair[1].can_become(&pair[0]));
+        }
+    }
+
+    #[test]
+    fn sign_ins_can_wait_on_the_service() {
+        let signing_in = AuthState::SigningIn {
+            identifier_id: Default::default(),
+        };
+
+        assert!(AuthState::Verifying {
+            action: AuthAction::In
+        }
+        .can_become(&signing_in));
+        assert!(signing_in.can_become(&AuthState::Authenticated {
+            identifier_id: Default::default(),
+            claims: Default::default(),
+        }));
+        assert!(!signing_in.can_become(&AuthState::Registering {
+            identifier: "did:example:123".into(),
+            recovery: None,
+        }));
+    }
+
+    #[test]
+    fn unknown_sign_ins_can_switch_to_sign_up() {
+        let verifying = AuthState::Verifying {
+            action: AuthAction::In,
+        };
+
+        assert!(verifying.can_become(&AuthState::Authenticating {
+            action: AuthAction::Up,
+            refreshes: 0,
+        }));
+        assert!(!verifying.can_become(&AuthState::Authenticating {
+            action: AuthAction::In,
+            refreshes: 0,
+        }));
+        assert!(!AuthState::Verifying {
+            action: AuthAction::Up
+        }
+        .can_become(&AuthState::Authenticating {
+            action: AuthAction::Up,
+            refreshes: 0,
+        }));
+    }
+
+    #[test]
+    fn can_not_skip_verification() {
+        let authenticating = AuthState::Authenticating {
+            action: AuthAction::In,
+            refreshes: 0,
+        };
+
+        assert!(!authenticating.can_become(&AuthState::Authenticated {
+            identifier_id: Default::default(),
+            claims: Default::default(),
+        }));
+        assert!(authenticating.can_become(&AuthState::Authenticating {
+            action: AuthAction::In,
+            refreshes: 1,
+        }));
+        assert!(!authenticating.can_become(&AuthState::Verifying {
+            action: AuthAction::In,
+        }));
+    }
+
+    #[test]
+    fn finished_states_are_final() {
+        let rejected = AuthState::Rejected {
+            code: "nope".into(),
+            reason: "Nope.".into(),
+        };
+
+        assert!(AuthState::Verifying {
+            action: AuthAction::In
+        }
+        .can_become(&rejected));
+        assert!(!rejected.can_become(&AuthState::Expired));
+        assert!(!AuthState::Expired.can_become(&rejected));
+        assert!(!AuthState::Authenticated {
+            identifier_id: Default::default(),
+            claims: Default::default(),
+        }
+        .can_become(&AuthState::Expired));
+    }
+}
@@ -0,0 +1,107 @@
+// Letting services have a say in sign ins.
+//
+// Services with a `sign_in_webhook` are asked before each sign in, and can turn the
+// user away (say, for a suspended account) or attach extra claims to the session.
+// Like registrations, `async` services answer through `/complete`, and `sync`
+// services answer in the webhook's response.
+// -------------------------------------------------------------------------------
+
+use std::collections::BTreeMap;
+
+use eyre::eyre;
+use mist_common::Result;
+use mist_db::models::{
+    service::{Service, WebhookMode},
+    user::UserId,
+};
+use mist_jobs::jobs;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    handlers::verify_response::Claim,
+    registration::{Decision, WEBHOOK_TIMEOUT},
+    session::{machine, AuthSession, AuthState, SessionId},
+    state::AuthnState,
+};
+
+#[derive(Serialize)]
+pub(crate) struct SignInData {
+    pub(crate) id: UserId,
+    pub(crate) identifier: String,
+    /// Any step-up claims the wallet presented with the sign in.
+    pub(crate) claims: BTreeMap<String, Claim>,
+    pub(crate) session_id: SessionId,
+}
+
+/// Ask the service about a session that's waiting on it to sign the user in.
+pub(crate) async fn ask(
+    state: &AuthnState,
+    service: &Service,
+    mode: &WebhookMode,
+    session_id: &str,
+    session: &AuthSession,
+    data: &SignInData,
+) -> Result<()> {
+    match mode {
+        WebhookMode::Async => {
+            jobs::Webhook::publish(&state.jetstream, &service.webhook_url, "sign_in", data).await?;
+        }
+        WebhookMode::Sync => {
+            let decision = jobs::Webhook::call::<_, Decision>(
+                &service.webhook_url,
+                "sign_in",
+                data,
+                WEBHOOK_TIMEOUT,
+            )
+            .await;
+
+            match decision {
+                Ok(Decision::Accept { claims, .. }) => {
+                    accept(state, session_id, session, claims).await?
+                }
+                Ok(Decision::Reject { code, reason }) => {
+                    machine::reject(state, session_id, &code, &reason).await?
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "sign in webhook failed");
+
+                    machine::reject(
+                        state,
+                        session_id,
+                        "webhook_failed",
+                        "We couldn't sign you in, please try again later.",
+                    )
+                    .await?
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sign the user in, with whatever claims the service wants attached.
+pub(crate) async fn accept(
+    state: &AuthnState,
+    session_id: &str,
+    session: &AuthSession,
+    claims: Map<String, Value>,
+) -> Result<()> {
+    let AuthState::SigningIn { identifier_id } = session.state else {
+        return Err(eyre!("session is not signing in").into());
+    };
+
+    machine::transition(
+        state,
+        session_id,
+        session,
+        AuthState::Authenticated {
+            identifier_id,
+            claims,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+// Single sign-on between sibling services.
+//
+// Services sharing an `sso_domain` can sign a user in with a session the browser
+// already has for one of the others, without them scanning again. Users belong to
+// a single service, so this only works for users the service already has with the
+// same wallet: anyone else signs up, or in, by scanning as usual. The first time,
+// the user is asked whether they're happy to be signed in that way, and their answer
+// is kept so they aren't asked again.
+//
+// Services with a `sign_in_webhook` are still asked, just like any other sign in.
+// -------------------------------------------------------------------------------
+
+use axum::response::{IntoResponse, Redirect, Response};
+use mist_common::Result;
+use mist_db::models::{identifier::IdentifierId, service::Service, user::UserId};
+use tower_cookies::Cookies;
+
+use crate::{
+    session::{self, machine, AuthSession, AuthState, Client, ReturnTo, SessionId, AUTH_SESSION},
+    sign_in::{self, SignInData},
+    state::AuthnState,
+    utils::{auth_request, redirects},
+    views,
+};
+
+/// The service's user, found through the wallet they're signed in with on a sibling
+/// service.
+pub(crate) struct Sibling {
+    pub(crate) service: Service,
+    pub(crate) user_id: UserId,
+    pub(crate) identifier_id: IdentifierId,
+}
+
+/// Sessions this browser has for other services in the same SSO domain.
+pub(crate) async fn sibling_sessions(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service: &Service,
+) -> Result<Vec<(Service, SessionId, AuthSession)>> {
+    let Some(domain) = &service.sso_domain else {
+        return Ok(vec![]);
+    };
+
+    let mut siblings = vec![];
+
+    for (service_id, session_id) in session::all_from_cookies(cookies) {
+        if service_id == service.id {
+            continue;
+        }
+
+        let Ok(session) = AUTH_SESSION
+            .get(&state.redis, &session_id.to_string())
+            .await
+        else {
+            continue;
+        };
+
+        // The cookie name is the browser's word, the session's is ours.
+        if session.service_id != service_id {
+            continue;
+        }
+
+        let Ok(sibling) = state.repos.services.get(&service_id).await else {
+            continue;
+        };
+
+        if sibling.sso_domain.as_ref() == Some(domain) {
+            siblings.push((sibling, session_id, session));
+        }
+    }
+
+    Ok(siblings)
+}
+
+/// Find a signed in session this browser has for another service in the same SSO
+/// domain, with a wallet the service has a user for.
+pub(crate) async fn find_sibling(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service: &Service,
+) -> Result<Option<Sibling>> {
+    for (sibling, _, session) in sibling_sessions(state, cookies, service).await? {
+        let AuthState::Authenticated { identifier_id, .. } = session.state else {
+            continue;
+        };
+
+        let did = state.repos.identifiers.get(&identifier_id).await?.value;
+
+        let identifier = match state
+            .repos
+            .identifiers
+            .get_by_value_for_service(&service.id, &did)
+            .await
+        {
+            Ok(identifier) => identifier,
+            Err(err) if err.is_not_found() => continue,
+            Err(err) => return Err(err),
+        };
+
+        return Ok(Some(Sibling {
+            service: sibling,
+            user_id: identifier.user_id,
+            identifier_id: identifier.id,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Start a session for the service from the sibling's, attach it to the browser, and
+/// send the user on to the service, or wait with them while it decides.
+pub(crate) async fn sign_in(
+    state: &AuthnState,
+    cookies: &Cookies,
+    service: &Service,
+    sibling: &Sibling,
+    client: Client,
+    return_to: ReturnTo,
+) -> Result<Response> {
+    let session_id = SessionId::new();
+    let session = AuthSession {
+        service_id: service.id,
+        user_id: sibling.user_id,
+        state: match service.sign_in_webhook {
+            Some(_) => AuthState::SigningIn {
+                identifier_id: sibling.identifier_id,
+            },
+            None => AuthState::Authenticated {
+                identifier_id: sibling.identifier_id,
+                claims: Default::default(),
+            },
+        },
+        expires_at: auth_request::session_expires_at(state),
+        idle_timeout: None,
+        client,
+        return_to,
+        device: false,
+        consented: false,
+        sid: session::new_sid(),
+    };
+    let session = match session.state {
+        AuthState::Authenticated { .. } => machine::signed_in(session, service),
+        _ => session,
+    };
+
+    AUTH_SESSION
+        .set(
+            &state.redis,
+            &session_id.to_string(),
+            &session,
+            machine::expiration(&session),
+        )
+        .await?;
+
+    cookies.add(session::cookie(
+        service,
+        &session_id,
+        !state.env.development,
+    ));
+
+    let Some(mode) = &service.sign_in_webhook else {
+        machine::record(state, &session_id.to_string(), &session).await?;
+
+        let url = redirects::after_sign_in(state, service, &session_id, &session).await?;
+
+        return Ok(Redirect::to(&url).into_response());
+    };
+
+    let identifier = state.repos.identifiers.get(&sibling.identifier_id).await?;
+    let data = SignInData {
+        id: sibling.user_id,
+        identifier: service.subject(&identifier.value)?,
+        claims: Default::default(),
+        session_id,
+    };
+
+    sign_in::ask(
+        state,
+        service,
+        mode,
+        &session_id.to_string(),
+        &session,
+        &data,
+    )
+    .await?;
+
+    Ok(views::resume::view(service, &state.env.authn_url).into_response())
+}
+
+/// Remember that the user is happy to be signed in to the service through SSO.
+pub(crate) async fn consent(
+    state: &AuthnState,
+    service: &Service,
+    sibling: &Sibling,
+) -> Result<()> {
+    state
+        .repos
+        .sso_consents
+        .create(&sibling.user_id, &service.id)
+        .await
+}
+
+/// Whether the user has agreed to be signed in to the service through SSO before.
+pub(crate) async fn has_consented(
+    state: &AuthnState,
+    service: &Service,
+    sibling: &Sibling,
+) -> Result<bool> {
+    state
+        .repos
+        .sso_consents
+        .exists(&sibling.user_id, &service.id)
+        .await
+}
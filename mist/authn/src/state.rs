@@ -4,15 +4,29 @@ use async_nats::{jetstream::Context, Client};
 use fred::prelude::RedisClient;
 use mist_common::env::Environment;
 use mist_db::repos::{
-    identifiers::IdentifierRepo, keys::KeyRepo, services::ServiceRepo, users::UserRepo,
+    credential_configurations::CredentialConfigurationRepo, credential_offers::CredentialOfferRepo,
+    identifiers::IdentifierRepo, keys::KeyRepo, recovery_methods::RecoveryMethodRepo,
+    redirect_uris::RedirectUriRepo, services::ServiceRepo, sessions::SessionRepo,
+    sign_up_consents::SignUpConsentRepo, sso_consents::SsoConsentRepo,
+    trusted_issuers::TrustedIssuerRepo, users::UserRepo,
 };
 
+use crate::utils::status_list::StatusLists;
+
 #[derive(Clone)]
 pub(crate) struct Repos {
     pub(crate) keys: Arc<dyn KeyRepo>,
     pub(crate) services: Arc<dyn ServiceRepo>,
     pub(crate) users: Arc<dyn UserRepo>,
     pub(crate) identifiers: Arc<dyn IdentifierRepo>,
+    pub(crate) trusted_issuers: Arc<dyn TrustedIssuerRepo>,
+    pub(crate) credential_configurations: Arc<dyn CredentialConfigurationRepo>,
+    pub(crate) credential_offers: Arc<dyn CredentialOfferRepo>,
+    pub(crate) recovery_methods: Arc<dyn RecoveryMethodRepo>,
+    pub(crate) sso_consents: Arc<dyn SsoConsentRepo>,
+    pub(crate) sessions: Arc<dyn SessionRepo>,
+    pub(crate) redirect_uris: Arc<dyn RedirectUriRepo>,
+    pub(crate) sign_up_consents: Arc<dyn SignUpConsentRepo>,
 }
 
 #[derive(Clone)]
@@ -22,4 +36,5 @@ pub(crate) struct AuthnState {
     pub(crate) redis: RedisClient,
     pub(crate) nats: Client,
     pub(crate) jetstream: Context,
+    pub(crate) status_lists: StatusLists,
 }
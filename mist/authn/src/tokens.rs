@@ -0,0 +1,211 @@
+// Tokens for services using the token-based flow.
+//
+// Instead of reading the browser's cookie through `/whoami`, a service with
+// `token_flow` on is sent back with a one-time code. Its backend swaps the code at
+// `/{service}/token` for an access token, which reads `/userinfo`, and a refresh
+// token, which gets new tokens for as long as the session lasts. It also gets an ID
+// token, signed (HS256) with its API key, to hand back as `id_token_hint` when
+// logging the user out.
+//
+// Every token points at the session it was issued for, so they all stop working as
+// soon as the session ends. Refresh tokens are rotated on every use. Services can
+// also bind their tokens to a key of their own, see `dpop`.
+// -------------------------------------------------------------------------------
+
+use chrono::Utc;
+use fred::types::Expiration;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use mist_common::{redis::TypedRedis, Result};
+use mist_db::models::{service::ServiceId, user::UserId};
+use openidconnect::CsrfToken;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    session::{machine, AuthSession, AuthState, SessionId, AUTH_SESSION},
+    state::AuthnState,
+    utils::client_auth,
+};
+
+/// How long the service has to swap a code for tokens, in seconds.
+const CODE_EXPIRES_IN: i64 = 60;
+
+/// How long access tokens are valid for, in seconds.
+const ACCESS_TOKEN_EXPIRES_IN: i64 = 60 * 15;
+
+pub(crate) static AUTH_CODE: TypedRedis<SessionId> = TypedRedis::new("mist-auth-code");
+pub(crate) static ACCESS_TOKEN: TypedRedis<AccessToken> = TypedRedis::new("mist-access-token");
+pub(crate) static REFRESH_TOKEN: TypedRedis<SessionId> = TypedRedis::new("mist-refresh-token");
+/// The thumbprint of the key a refresh token is bound to, if it's bound to one.
+pub(crate) static REFRESH_TOKEN_JKT: TypedRedis<String> = TypedRedis::new("mist-refresh-token-jkt");
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AccessToken {
+    pub(crate) session_id: SessionId,
+    /// As a unix timestamp.
+    pub(crate) expires_at: i64,
+    /// The thumbprint of the key the token is bound to, if it's bound to one.
+    #[serde(default)]
+    pub(crate) jkt: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Tokens {
+    access_token: String,
+    token_type: &'static str,
+    expires_in: i64,
+    refresh_token: String,
+    id_token: String,
+}
+
+/// See: https://openid.net/specs/openid-connect-core-1_0.html#IDToken
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IdTokenClaims {
+    pub(crate) iss: String,
+    pub(crate) sub: UserId,
+    pub(crate) aud: ServiceId,
+    pub(crate) iat: i64,
+    pub(crate) exp: i64,
+    /// The session the user signed in with.
+    pub(crate) sid: String,
+}
+
+/// Check an ID token we issued to the service, which may well have expired since.
+pub(crate) fn verify_id_token(
+    state: &AuthnState,
+    key: &[u8],
+    service_id: &ServiceId,
+    token: &str,
+) -> Result<IdTokenClaims> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.validate_exp = false;
+    validation.set_issuer(&[&state.env.authn_url]);
+    validation.set_audience(&[service_id.to_string()]);
+
+    let token =
+        jsonwebtoken::decode::<IdTokenClaims>(token, &DecodingKey::from_secret(key), &validation)?;
+
+    Ok(token.claims)
+}
+
+/// A one-time code for the service to swap for tokens.
+pub(crate) async fn issue_code(state: &AuthnState, session_id: &SessionId) -> Result<String> {
+    let code = CsrfToken::new_random_len(32).secret().clone();
+
+    AUTH_CODE
+        .set(
+            &state.redis,
+            &code,
+            session_id,
+            Expiration::EX(CODE_EXPIRES_IN),
+        )
+        .await?;
+
+    Ok(code)
+}
+
+/// The session, as long as it's signed in.
+pub(crate) async fn signed_in(state: &AuthnState, session_id: &SessionId) -> Option<AuthSession> {
+    let session = AUTH_SESSION
+        .get(&state.redis, &session_id.to_string())
+        .await
+        .ok()?;
+
+    matches!(session.state, AuthState::Authenticated { .. }).then_some(session)
+}
+
+/// The signed in session a code or refresh token was issued for.
+pub(crate) async fn session(
+    state: &AuthnState,
+    tokens: &TypedRedis<SessionId>,
+    token: &str,
+) -> Option<(SessionId, AuthSession)> {
+    let session_id = tokens.get(&state.redis, token).await.ok()?;
+
+    Some((session_id, signed_in(state, &session_id).await?))
+}
+
+/// The access token, and the signed in session it was issued for.
+pub(crate) async fn access_token(
+    state: &AuthnState,
+    token: &str,
+) -> Option<(AccessToken, AuthSession)> {
+    let access_token = ACCESS_TOKEN.get(&state.redis, token).await.ok()?;
+    let session = signed_in(state, &access_token.session_id).await?;
+
+    Some((access_token, session))
+}
+
+/// Hand out a fresh pair of tokens for a signed in session, bound to the key with the
+/// thumbprint, if there is one.
+pub(crate) async fn issue(
+    state: &AuthnState,
+    session_id: &SessionId,
+    session: &AuthSession,
+    jkt: Option<&str>,
+) -> Result<Tokens> {
+    let access_token = CsrfToken::new_random_len(32).secret().clone();
+    let refresh_token = CsrfToken::new_random_len(32).secret().clone();
+
+    // Neither outlives the session.
+    let expires_in = ACCESS_TOKEN_EXPIRES_IN
+        .min(machine::ends_at(session) - Utc::now().timestamp())
+        .max(1);
+
+    ACCESS_TOKEN
+        .set(
+            &state.redis,
+            &access_token,
+            &AccessToken {
+                session_id: *session_id,
+                expires_at: Utc::now().timestamp() + expires_in,
+                jkt: jkt.map(str::to_string),
+            },
+            Expiration::EX(expires_in),
+        )
+        .await?;
+
+    REFRESH_TOKEN
+        .set(
+            &state.redis,
+            &refresh_token,
+            session_id,
+            Expiration::EXAT(session.expires_at),
+        )
+        .await?;
+
+    if let Some(jkt) = jkt {
+        REFRESH_TOKEN_JKT
+            .set(
+                &state.redis,
+                &refresh_token,
+                &jkt.to_string(),
+                Expiration::EXAT(session.expires_at),
+            )
+            .await?;
+    }
+
+    let now = Utc::now().timestamp();
+    let id_token = jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &IdTokenClaims {
+            iss: state.env.authn_url.clone(),
+            sub: session.user_id,
+            aud: session.service_id,
+            iat: now,
+            exp: now + expires_in,
+            sid: session.sid.clone(),
+        },
+        &EncodingKey::from_secret(&client_auth::api_key(state, &session.service_id).await?),
+    )?;
+
+    Ok(Tokens {
+        access_token,
+        token_type: match jkt {
+            Some(_) => "DPoP",
+            None => "Bearer",
+        },
+        expires_in,
+        refresh_token,
+        id_token,
+    })
+}
@@ -1,2 +1,10 @@
+pub(crate) mod auth_request;
+pub(crate) mod client_auth;
+pub(crate) mod did;
+pub(crate) mod issuers;
+pub(crate) mod jwe;
 pub(crate) mod oidc;
+pub(crate) mod qr;
+pub(crate) mod redirects;
 pub(crate) mod sphereon;
+pub(crate) mod status_list;
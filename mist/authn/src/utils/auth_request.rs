@@ -0,0 +1,204 @@
+// Building the SIOP auth requests shown to the user as QR codes.
+//
+// Requests are short-lived: the state and nonce in each one expire along with it,
+// after `auth_request_lifetime` seconds. The browser swaps in a fresh request when
+// that happens, up to `auth_request_refreshes` times.
+// -------------------------------------------------------------------------------
+
+use chrono::Utc;
+use dif_presentation_exchange::PresentationDefinition;
+use mist_common::{crypto::decrypt_service_key, Result};
+use mist_db::models::{
+    key::KeyKind,
+    service::{ResponseMode, Service},
+};
+use openidconnect::{
+    core::{CoreClient, CoreResponseType},
+    AuthUrl, AuthenticationFlow, ClientId, IssuerUrl, JsonWebKeySet, RedirectUrl, Scope,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    session::{machine, AuthAction, SessionId},
+    state::AuthnState,
+    utils::{
+        jwe,
+        oidc::{self, TokenClaims, TokenKind},
+        qr,
+    },
+};
+
+#[derive(Serialize)]
+pub(crate) struct AuthRequest {
+    /// The request as a base64-encoded PNG QR code.
+    pub(crate) qr: String,
+    pub(crate) url: String,
+    /// Seconds until the request expires.
+    pub(crate) expires_in: i64,
+}
+
+/// When a session waiting on a new request has to be finished by, leaving time to
+/// finish up after a last-second scan.
+pub(crate) fn session_expires_at(state: &AuthnState) -> i64 {
+    Utc::now().timestamp() + state.env.auth_request_lifetime + machine::EXPIRES_IN
+}
+
+/// Create a new auth request for the session.
+pub(crate) async fn create(
+    state: &AuthnState,
+    service: &Service,
+    session_id: &SessionId,
+    action: &AuthAction,
+) -> Result<AuthRequest> {
+    let expires_in = state.env.auth_request_lifetime;
+    let expires_at = Utc::now().timestamp() + expires_in;
+
+    // Get the services' token key for signing the state and nonce.
+    // ------------------------------------------------------------
+
+    let service_key = state
+        .repos
+        .keys
+        .preferred(&service.id, &KeyKind::Token)
+        .await?;
+
+    let service_key = decrypt_service_key(&state.env.master_key, &service_key.value)?;
+
+    // Create a presentation from the service's default profile definition.
+    // --------------------------------------------------------------------
+
+    let fields = match action {
+        AuthAction::Up => {
+            let profile = state
+                .repos
+                .services
+                .get_default_profile(&service.id)
+                .await?;
+
+            if let Some(profile) = profile {
+                let fields = profile
+                    .value
+                    .fields
+                    .iter()
+                    .map(|f| json!({ "id": heck::AsSnakeCase(f.name.clone()).to_string(), "name": f.name, "constraints": {} }))
+                    .collect::<Vec<_>>();
+
+                fields
+            } else {
+                vec![json!({ "id": "skip", "name": "Skip", "constraints": {} })]
+            }
+        }
+        // Only ask for the credential they can recover with, if the service allows it.
+        AuthAction::Recover if service.recovery_claim.is_some() => {
+            let name = service.recovery_claim.clone().unwrap_or_default();
+
+            vec![
+                json!({ "id": heck::AsSnakeCase(name.clone()).to_string(), "name": name, "constraints": {} }),
+            ]
+        }
+        AuthAction::In | AuthAction::Link | AuthAction::Recover => {
+            // Ideally, we send no fields for signing in, but Sphereon seems to require we send _something_.
+            //
+            // TODO: Remove this when I can.
+            vec![json!({ "id": "skip", "name": "Skip", "constraints": {} })]
+        }
+    };
+
+    let presentation = serde_json::from_value::<PresentationDefinition>(json!({
+        "id": "registration-data",
+        "input_descriptors": fields
+    }))?;
+
+    // Create the authorization URL and render it as a QR code.
+    // --------------------------------------------------------
+
+    let oidc_client = CoreClient::new(
+        ClientId::new(service.name.clone()),
+        None,
+        // Placeholder, not used for SIOP.
+        IssuerUrl::new("https://not.needed".into())?,
+        // Only the prefix matters for SIOP.
+        AuthUrl::new("siopv2://authenticate".into())?,
+        None,
+        None,
+        JsonWebKeySet::default(),
+    );
+
+    // Both are single-use, and only valid for this session, service and action, until
+    // the request expires.
+    let state_token = oidc::create_state(
+        &service_key,
+        &TokenClaims::new(
+            TokenKind::State,
+            session_id,
+            &service.id,
+            action,
+            expires_at,
+        ),
+    )?;
+    let nonce_token = oidc::create_nonce(
+        &service_key,
+        &TokenClaims::new(
+            TokenKind::Nonce,
+            session_id,
+            &service.id,
+            action,
+            expires_at,
+        ),
+    )?;
+
+    let response_url = format!("{}/auth", state.env.authn_url);
+
+    // With `direct_post.jwt` the response goes to the `response_uri` instead.
+    let oidc_client = match service.response_mode {
+        ResponseMode::Post => oidc_client.set_redirect_uri(RedirectUrl::new(response_url.clone())?),
+        ResponseMode::DirectPostJwt => oidc_client,
+    };
+
+    let request = oidc_client
+        .authorize_url(
+            AuthenticationFlow::<CoreResponseType>::Hybrid(vec![
+                CoreResponseType::IdToken,
+                CoreResponseType::Extension("vp_token".into()),
+            ]),
+            move || state_token,
+            move || nonce_token,
+        )
+        .add_scope(Scope::new("vp_token".into()))
+        .add_extra_param("response_mode", service.response_mode.to_string())
+        .add_extra_param("id_token_type", "subject_signed_id_token")
+        .add_extra_param(
+            "presentation_definition",
+            serde_json::to_string::<PresentationDefinition>(&presentation)?,
+        );
+
+    // Ask the wallet to encrypt its response to an ephemeral key only we hold, so
+    // nothing in between (proxies, access logs) ever sees the user's data.
+    let request = match service.response_mode {
+        ResponseMode::Post => request,
+        ResponseMode::DirectPostJwt => {
+            let jwk = jwe::create_response_key(&state.redis, expires_in).await?;
+
+            request
+                .add_extra_param("response_uri", response_url)
+                .add_extra_param(
+                    "client_metadata",
+                    json!({
+                        "jwks": { "keys": [jwk] },
+                        "authorization_encrypted_response_alg": jwe::ALG,
+                        "authorization_encrypted_response_enc": jwe::ENC,
+                    })
+                    .to_string(),
+                )
+        }
+    };
+
+    let (authorize_url, _, _) = request.url();
+
+    Ok(AuthRequest {
+        qr: qr::png_base64(authorize_url.as_str())?,
+        url: authorize_url.to_string(),
+        expires_in,
+    })
+}
@@ -0,0 +1,35 @@
+use constant_time_eq::constant_time_eq;
+use http::HeaderMap;
+use mist_common::{crypto::decrypt_service_key, Result};
+use mist_db::models::{key::KeyKind, service::ServiceId};
+
+use crate::state::AuthnState;
+
+/// The service's API key, which it also uses as its client secret.
+pub(crate) async fn api_key(state: &AuthnState, service_id: &ServiceId) -> Result<Vec<u8>> {
+    let service_key = state
+        .repos
+        .keys
+        .preferred(service_id, &KeyKind::Api)
+        .await?;
+
+    decrypt_service_key(&state.env.master_key, &service_key.value)
+}
+
+/// Whether the request carries the service's API key, hex-encoded in the
+/// `Authorization` header, as with `mist_api`.
+pub(crate) async fn is_service(
+    state: &AuthnState,
+    service_id: &ServiceId,
+    headers: &HeaderMap,
+) -> Result<bool> {
+    let Some(header) = headers.get("Authorization") else {
+        return Ok(false);
+    };
+
+    let Ok(given) = hex::decode(header.as_bytes()) else {
+        return Ok(false);
+    };
+
+    Ok(constant_time_eq(&given, &api_key(state, service_id).await?))
+}
@@ -0,0 +1,156 @@
+use eyre::OptionExt;
+use mist_common::Result;
+use ssi::{
+    did::{Document, VerificationMethod, VerificationMethodMap},
+    did_resolve::ResolutionResult,
+    jwk::JWK,
+};
+
+/// Resolve a DID (or DID URL) through the Universal Resolver.
+pub(crate) async fn resolve(resolver_url: &str, did: &str) -> Result<Document> {
+    let document = reqwest::get(format!("{resolver_url}/{did}"))
+        .await?
+        .json::<ResolutionResult>()
+        .await?
+        .did_document
+        .ok_or_eyre("no document")?;
+
+    Ok(document)
+}
+
+/// Get the key the DID subject authenticates with.
+pub(crate) async fn authentication_key(resolver_url: &str, did: &str) -> Result<JWK> {
+    let document = resolve(resolver_url, did).await?;
+
+    // Get the verification method to use for authentication.
+    // ------------------------------------------------------
+
+    let verif_methods = document
+        .verification_method
+        .as_ref()
+        .ok_or_eyre("document is missing verification methods")?;
+    let auth_methods = document
+        .authentication
+        .as_ref()
+        .ok_or_eyre("document is missing authentication property")?;
+
+    // Get the first auth method for convenience.
+    let first = auth_methods
+        .first()
+        .ok_or_eyre("document is missing an auth method")?;
+
+    let method = match first {
+        // If the verification method is a DID URL, we need to resolve that DID and find the
+        // verification method where ID = DID URL.
+        VerificationMethod::DIDURL(url) => {
+            let other_document = resolve(resolver_url, &url.to_string()).await?;
+
+            find_method(
+                other_document
+                    .verification_method
+                    .as_ref()
+                    .ok_or_eyre("other document is missing verification methods")?,
+                &url.to_string(),
+            )?
+        }
+        // If the verification method is a DID URL fragment, we need to find the verification
+        // method where ID = fargment.
+        VerificationMethod::RelativeDIDURL(url) => find_method(verif_methods, &url.to_string())?,
+        // In the case where it's a map, we can just return it directly.
+        VerificationMethod::Map(method) => method.clone(),
+    };
+
+    let jwk = method
+        .public_key_jwk
+        .ok_or_eyre("could not get public jwk")?;
+
+    Ok(jwk)
+}
+
+/// Get the key an issuer signed something with, given the `kid` from the JWT header.
+///
+/// The `kid` is expected to be a DID URL. If it's just a DID, the first assertion
+/// method is used instead.
+pub(crate) async fn assertion_key(resolver_url: &str, kid: &str) -> Result<JWK> {
+    let did = kid.split('#').next().unwrap_or(kid);
+    let document = resolve(resolver_url, did).await?;
+
+    let assertion_methods = document.assertion_method.as_deref().unwrap_or_default();
+
+    let id = if kid.contains('#') {
+        kid.to_string()
+    } else {
+        assertion_methods
+            .first()
+            .ok_or_eyre("document is missing an assertion method")?
+            .get_id(did)
+    };
+
+    // Assertion methods can be embedded, so look there as well.
+    let method = document
+        .verification_method
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .chain(assertion_methods)
+        .filter_map(|m| {
+            if let VerificationMethod::Map(v) = m {
+                Some(v)
+            } else {
+                None
+            }
+        })
+        .find(|m| m.get_id(did) == id)
+        .ok_or_eyre("could not find verification method")?;
+
+    Ok(method.get_jwk()?)
+}
+
+/// Get the key a holder signed something with, given the `kid` from the JWT header.
+///
+/// The `kid` is expected to be a DID URL. If it's just a DID, the key the subject
+/// authenticates with is used instead.
+pub(crate) async fn verification_key(resolver_url: &str, kid: &str) -> Result<JWK> {
+    let Some((did, _)) = kid.split_once('#') else {
+        return authentication_key(resolver_url, kid).await;
+    };
+
+    let document = resolve(resolver_url, did).await?;
+
+    // Methods can be embedded in any relationship, so look everywhere.
+    let method = [
+        &document.verification_method,
+        &document.authentication,
+        &document.assertion_method,
+    ]
+    .into_iter()
+    .flat_map(|methods| methods.as_deref().unwrap_or_default())
+    .filter_map(|m| {
+        if let VerificationMethod::Map(v) = m {
+            Some(v)
+        } else {
+            None
+        }
+    })
+    .find(|m| m.get_id(did) == kid)
+    .ok_or_eyre("could not find verification method")?;
+
+    Ok(method.get_jwk()?)
+}
+
+fn find_method(methods: &[VerificationMethod], id: &str) -> Result<VerificationMethodMap> {
+    let method = methods
+        .iter()
+        .filter_map(|m| {
+            if let VerificationMethod::Map(v) = m {
+                Some(v)
+            } else {
+                None
+            }
+        })
+        .find(|m| m.id == id)
+        .ok_or_eyre("could not find verification method")?
+        .clone();
+
+    Ok(method)
+}
@@ -0,0 +1,483 @@
+// Verifying who issued a presented credential, and whether we trust them.
+//
+// Credentials are signed either by a key in the issuer's DID document (found via
+// the `kid`, or the `iss` claim), or by an x509 certificate sent in the `x5c`
+// header. If a service has trusted issuers configured, a DID issuer must match one
+// of them. Anyone can make a certificate for any name, so certificates are only
+// accepted when they chain to one of the service's trusted anchors.
+// -------------------------------------------------------------------------------
+
+use base64::prelude::*;
+use eyre::{eyre, OptionExt};
+use mist_common::Result;
+use mist_db::models::trusted_issuer::{IssuerKind, TrustedIssuer};
+use serde_json::Value;
+use ssi::{
+    jwk::Algorithm,
+    jws::{self, DecodedJWS},
+    vc::Credential,
+};
+use webpki::{
+    ring as algs,
+    types::{CertificateDer, ServerName, UnixTime},
+    EndEntityCert, KeyUsage,
+};
+
+use super::{did, sphereon::SphereonCredentialWrapper};
+
+/// `anyExtendedKeyUsage`, so certificates that restrict their usage have to allow it.
+const ANY_EXTENDED_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25, 0x00];
+
+pub(crate) struct VerifiedCredential {
+    /// The issuer's DID, or for credentials signed with a certificate, its `iss`, which
+    /// names the certificate's host.
+    pub(crate) issuer: String,
+    /// Who the credential was issued to, from `sub` or the subject's `id`.
+    pub(crate) subject: Option<String>,
+    pub(crate) credential: Credential,
+}
+
+/// Verify a JWT-secured credential's signature and make sure its issuer is trusted.
+///
+/// An empty `trusted` list means the service hasn't restricted issuers, so any DID
+/// issuer is accepted as long as the signature checks out.
+pub(crate) async fn verify_credential(
+    resolver_url: &str,
+    jwt: &str,
+    trusted: &[TrustedIssuer],
+) -> Result<VerifiedCredential> {
+    let (header, payload, signature) = jws::split_jws(jwt)?;
+    let DecodedJWS {
+        header,
+        signing_input,
+        payload,
+        signature,
+    } = jws::decode_jws_parts(header, payload.as_bytes(), signature)?;
+
+    let claims = serde_json::from_slice::<Value>(&payload)?;
+    let iss = issuer_claim(&claims);
+
+    let issuer = match &header.x509_certificate_chain {
+        Some(chain) => {
+            let issuer = iss.ok_or_eyre("credential is missing an issuer")?;
+            verify_x509(
+                header.algorithm,
+                chain,
+                &signing_input,
+                &signature,
+                &issuer,
+                trusted,
+            )?;
+
+            issuer
+        }
+        None => {
+            let kid = header.key_id.as_deref().or(iss.as_deref());
+            let kid = kid.ok_or_eyre("credential is missing an issuer")?;
+            let did = kid.split('#').next().unwrap_or(kid).to_string();
+
+            // Don't let someone sign with their own key while claiming to be someone else.
+            if iss.as_ref().is_some_and(|iss| *iss != did) {
+                return Err(eyre!("credential was not signed by its issuer").into());
+            }
+
+            let key = did::assertion_key(resolver_url, kid).await?;
+            jws::verify_bytes(header.algorithm, &signing_input, &key, &signature)?;
+
+            if !trusted.is_empty() && !trusted.iter().any(|t| matches_did(t, &did)) {
+                return Err(eyre!("credential issuer is not trusted: {did}").into());
+            }
+
+            did
+        }
+    };
+
+    if let Some(exp) = claims["exp"].as_i64() {
+        if exp < chrono::Utc::now().timestamp() {
+            return Err(eyre!("credential has expired").into());
+        }
+    }
+
+    let subject = claims["sub"]
+        .as_str()
+        .or_else(|| claims["vc"]["credentialSubject"]["id"].as_str())
+        .map(String::from);
+
+    let wrapper = serde_json::from_value::<SphereonCredentialWrapper>(claims)?;
+
+    Ok(VerifiedCredential {
+        issuer,
+        subject,
+        credential: wrapper.vc,
+    })
+}
+
+/// VC-JWT puts the issuer in `iss`, but fall back to the credential's own issuer.
+fn issuer_claim(claims: &Value) -> Option<String> {
+    let issuer = &claims["vc"]["issuer"];
+
+    claims["iss"]
+        .as_str()
+        .or_else(|| issuer.as_str())
+        .or_else(|| issuer["id"].as_str())
+        .map(String::from)
+}
+
+fn matches_did(trusted: &TrustedIssuer, issuer: &str) -> bool {
+    match trusted.kind {
+        IssuerKind::Did => trusted.value == issuer,
+        // Only match whole segments, so `did:web:example.com` doesn't trust
+        // `did:web:example.com.evil.org`.
+        IssuerKind::DidPrefix => issuer.strip_prefix(&trusted.value).is_some_and(|rest| {
+            rest.is_empty() || trusted.value.ends_with(':') || rest.starts_with(':')
+        }),
+        IssuerKind::X509 => false,
+    }
+}
+
+/// Verify the signature with the leaf certificate, that the certificate is for the
+/// issuer's host, and that the chain leads to one of the trusted anchors.
+fn verify_x509(
+    algorithm: Algorithm,
+    chain: &[String],
+    message: &[u8],
+    signature: &[u8],
+    issuer: &str,
+    trusted: &[TrustedIssuer],
+) -> Result<()> {
+    // `x5c` uses standard base64, unlike the rest of the JWS.
+    let chain = chain
+        .iter()
+        .map(|cert| Ok(CertificateDer::from(BASE64_STANDARD.decode(cert)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let (leaf, intermediates) = chain.split_first().ok_or_eyre("empty x5c")?;
+    let leaf = EndEntityCert::try_from(leaf).map_err(|e| eyre!("invalid certificate: {e}"))?;
+
+    // Verify the signature.
+    // ---------------------
+
+    let (alg, signature) = match algorithm {
+        Algorithm::ES256 => (algs::ECDSA_P256_SHA256, ecdsa_der(signature)),
+        Algorithm::ES384 => (algs::ECDSA_P384_SHA384, ecdsa_der(signature)),
+        Algorithm::EdDSA => (algs::ED25519, signature.to_vec()),
+        Algorithm::RS256 => (algs::RSA_PKCS1_2048_8192_SHA256, signature.to_vec()),
+        Algorithm::PS256 => (
+            algs::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+            signature.to_vec(),
+        ),
+        alg => return Err(eyre!("unsupported certificate signature alg: {alg:?}").into()),
+    };
+
+    leaf.verify_signature(alg, message, &signature)
+        .map_err(|e| eyre!("invalid credential signature: {e}"))?;
+
+    // Make sure the certificate is the issuer's.
+    // -------------------------------------------
+
+    let url = reqwest::Url::parse(issuer)?;
+    let host = url
+        .host_str()
+        .filter(|_| url.scheme() == "https")
+        .ok_or_eyre("credential issuer is not an https url")?;
+    let name = ServerName::try_from(host).map_err(|e| eyre!("invalid issuer host: {e}"))?;
+
+    leaf.verify_is_valid_for_subject_name(&name)
+        .map_err(|e| eyre!("credential issuer does not match its certificate: {e}"))?;
+
+    // Check the chain against the trust anchors.
+    // ------------------------------------------
+
+    let anchors = trusted
+        .iter()
+        .filter(|t| t.kind == IssuerKind::X509)
+        .flat_map(|t| {
+            rustls_pemfile::certs(&mut t.value.as_bytes())
+                .filter_map(|cert| cert.ok())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let anchors = anchors
+        .iter()
+        .filter_map(|cert| webpki::anchor_from_trusted_cert(cert).ok())
+        .collect::<Vec<_>>();
+
+    if anchors.is_empty() {
+        return Err(eyre!("no trusted certificate authorities for {issuer}").into());
+    }
+
+    leaf.verify_for_usage(
+        &[
+            algs::ECDSA_P256_SHA256,
+            algs::ECDSA_P384_SHA384,
+            algs::ED25519,
+            algs::RSA_PKCS1_2048_8192_SHA256,
+            algs::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+        ],
+        &anchors,
+        intermediates,
+        UnixTime::now(),
+        KeyUsage::required_if_present(ANY_EXTENDED_KEY_USAGE),
+        None,
+        None,
+    )
+    .map_err(|e| eyre!("certificate chain is not trusted: {e}"))?;
+
+    Ok(())
+}
+
+/// JWS ECDSA signatures are the raw `r || s`, but webpki expects them DER-encoded.
+fn ecdsa_der(signature: &[u8]) -> Vec<u8> {
+    // Leave anything malformed for the verifier to reject.
+    if signature.is_empty() || !signature.len().is_multiple_of(2) {
+        return signature.to_vec();
+    }
+
+    let (r, s) = signature.split_at(signature.len() / 2);
+
+    let mut sequence = Vec::new();
+
+    for int in [r, s] {
+        let start = int.iter().position(|b| *b != 0).unwrap_or(int.len() - 1);
+        let int = &int[start..];
+
+        sequence.push(0x02);
+
+        if int[0] & 0x80 != 0 {
+            sequence.push(int.len() as u8 + 1);
+            sequence.push(0x00);
+        } else {
+            sequence.push(int.len() as u8);
+        }
+
+        sequence.extend(int);
+    }
+
+    let mut der = vec![0x30, sequence.len() as u8];
+    der.extend(sequence);
+
+    der
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing, Json, Router};
+    use base64::prelude::*;
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair, PKCS_ECDSA_P256_SHA256};
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+    };
+    use serde_json::json;
+    use ssi::jwk::JWK;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const ISSUER: &str = "did:example:issuer";
+    const HOLDER: &str = "did:example:holder";
+
+    fn claims(iss: &str) -> Value {
+        json!({
+            "iss": iss,
+            "sub": HOLDER,
+            "vc": {
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiableCredential"],
+                "issuer": iss,
+                "issuanceDate": "2024-01-01T00:00:00Z",
+                "credentialSubject": { "email": "jane@example.com" }
+            }
+        })
+    }
+
+    fn issuer(kind: IssuerKind, value: &str) -> TrustedIssuer {
+        TrustedIssuer {
+            kind,
+            value: value.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Serve the issuer's DID document, like a resolver would.
+    async fn stub_resolver(key: &JWK) -> String {
+        let document = json!({
+            "didDocument": {
+                "@context": "https://www.w3.org/ns/did/v1",
+                "id": ISSUER,
+                "verificationMethod": [{
+                    "id": format!("{ISSUER}#key-1"),
+                    "type": "JsonWebKey2020",
+                    "controller": ISSUER,
+                    "publicKeyJwk": key.to_public(),
+                }],
+                "assertionMethod": [format!("{ISSUER}#key-1")],
+            }
+        });
+
+        let app = Router::new().route(
+            "/identifiers/:did",
+            routing::get(|| async move { Json(document) }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{address}/identifiers")
+    }
+
+    fn did_signed(key: &JWK, iss: &str) -> String {
+        let mut key = key.clone();
+        key.key_id = Some(format!("{ISSUER}#key-1"));
+
+        ssi::jwt::encode_sign(Algorithm::ES256, &claims(iss), &key).unwrap()
+    }
+
+    /// Create a CA, and a credential signed by a certificate it issued.
+    fn x509_signed(iss: &str) -> (String, String) {
+        let ca_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let mut ca_params = CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let leaf_key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let leaf = CertificateParams::new(vec!["issuer.example.com".into()])
+            .unwrap()
+            .signed_by(&leaf_key, &ca, &ca_key)
+            .unwrap();
+
+        (sign_with_certificate(&leaf_key, leaf.der(), iss), ca.pem())
+    }
+
+    /// A credential signed by a certificate nobody issued.
+    fn self_signed(iss: &str) -> String {
+        let key = KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256).unwrap();
+        let cert = CertificateParams::new(vec!["issuer.example.com".into()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+
+        sign_with_certificate(&key, cert.der(), iss)
+    }
+
+    fn sign_with_certificate(leaf_key: &KeyPair, leaf: &[u8], iss: &str) -> String {
+        let header = json!({
+            "alg": "ES256",
+            "x5c": [BASE64_STANDARD.encode(leaf)],
+        });
+        let signing_input = format!(
+            "{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+            BASE64_URL_SAFE_NO_PAD.encode(claims(iss).to_string()),
+        );
+
+        let rng = SystemRandom::new();
+        let signer = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &leaf_key.serialize_der(),
+            &rng,
+        )
+        .unwrap();
+        let signature = signer.sign(&rng, signing_input.as_bytes()).unwrap();
+
+        format!(
+            "{signing_input}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(signature.as_ref())
+        )
+    }
+
+    #[tokio::test]
+    async fn verifies_did_issuers() -> Result<()> {
+        let key = JWK::generate_p256()?;
+        let resolver_url = stub_resolver(&key).await;
+        let jwt = did_signed(&key, ISSUER);
+
+        let verified = verify_credential(&resolver_url, &jwt, &[]).await?;
+        assert_eq!(verified.issuer, ISSUER);
+        assert_eq!(verified.subject.as_deref(), Some(HOLDER));
+
+        let trusted = [issuer(IssuerKind::Did, ISSUER)];
+        assert!(verify_credential(&resolver_url, &jwt, &trusted)
+            .await
+            .is_ok());
+
+        let trusted = [issuer(IssuerKind::Did, "did:example:other")];
+        assert!(verify_credential(&resolver_url, &jwt, &trusted)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_impersonation() -> Result<()> {
+        let key = JWK::generate_p256()?;
+        let resolver_url = stub_resolver(&key).await;
+        let jwt = did_signed(&key, "did:example:someone-else");
+
+        assert!(verify_credential(&resolver_url, &jwt, &[]).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifies_x509_chains() -> Result<()> {
+        let (jwt, anchor) = x509_signed("https://issuer.example.com");
+        let (_, other_anchor) = x509_signed("https://issuer.example.com");
+
+        // The resolver is never needed for certificates.
+        let resolver_url = "http://127.0.0.1:1/identifiers";
+
+        let trusted = [issuer(IssuerKind::X509, &anchor)];
+        let verified = verify_credential(resolver_url, &jwt, &trusted).await?;
+        assert_eq!(verified.issuer, "https://issuer.example.com");
+
+        let trusted = [issuer(IssuerKind::X509, &other_anchor)];
+        assert!(verify_credential(resolver_url, &jwt, &trusted)
+            .await
+            .is_err());
+
+        // Without an anchor there's nothing vouching for the certificate.
+        assert!(verify_credential(resolver_url, &jwt, &[]).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_issuers_the_certificate_is_not_for() -> Result<()> {
+        let (jwt, anchor) = x509_signed("https://other.example.com");
+        let resolver_url = "http://127.0.0.1:1/identifiers";
+
+        let trusted = [issuer(IssuerKind::X509, &anchor)];
+        assert!(verify_credential(resolver_url, &jwt, &trusted)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_self_signed_certificates_claiming_trusted_dids() -> Result<()> {
+        let jwt = self_signed(ISSUER);
+        let resolver_url = "http://127.0.0.1:1/identifiers";
+
+        let trusted = [issuer(IssuerKind::Did, ISSUER)];
+        assert!(verify_credential(resolver_url, &jwt, &trusted)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn matches_whole_prefix_segments() {
+        let prefix = issuer(IssuerKind::DidPrefix, "did:web:example.com");
+
+        assert!(matches_did(&prefix, "did:web:example.com"));
+        assert!(matches_did(&prefix, "did:web:example.com:issuers:1"));
+        assert!(!matches_did(&prefix, "did:web:example.com.evil.org"));
+    }
+}
@@ -0,0 +1,256 @@
+// Just enough JWE to support `direct_post.jwt` responses.
+//
+// We publish an ephemeral P-256 key in the request's client metadata and only
+// accept `ECDH-ES` (direct key agreement) with `A128GCM` or `A256GCM` content
+// encryption, which is what OpenID4VP wallets are expected to send.
+//
+// See: https://www.rfc-editor.org/rfc/rfc7518#section-4.6
+// -------------------------------------------------------------------------------
+
+use aes_gcm::{
+    aead::{Aead, OsRng, Payload},
+    Aes128Gcm, Aes256Gcm, KeyInit, Nonce,
+};
+use base64::prelude::*;
+use eyre::eyre;
+use fred::{prelude::RedisClient, types::Expiration};
+use mist_common::{redis::TypedRedis, Result};
+use p256::{ecdh::diffie_hellman, PublicKey, SecretKey};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub(crate) const ALG: &str = "ECDH-ES";
+pub(crate) const ENC: &str = "A256GCM";
+
+/// Ephemeral private keys, stored as JWKs and keyed by their `kid`.
+static RESPONSE_KEY: TypedRedis<String> = TypedRedis::new("mist-response-key");
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    enc: String,
+    kid: Option<String>,
+    epk: Value,
+    apu: Option<String>,
+    apv: Option<String>,
+}
+
+/// Create an ephemeral encryption key for a single authorization response.
+///
+/// The private key is kept in Redis for as long as the request is valid, and the
+/// public key is returned as a JWK ready to be published in the client metadata.
+pub(crate) async fn create_response_key(redis: &RedisClient, expiration: i64) -> Result<Value> {
+    let kid = Uuid::new_v4().to_string();
+    let secret = SecretKey::random(&mut OsRng);
+
+    RESPONSE_KEY
+        .set(
+            redis,
+            &kid,
+            &secret.to_jwk_string(),
+            Expiration::EX(expiration),
+        )
+        .await?;
+
+    let mut jwk = serde_json::to_value(secret.public_key().to_jwk())?;
+
+    jwk["kid"] = json!(kid);
+    jwk["use"] = json!("enc");
+    jwk["alg"] = json!(ALG);
+
+    Ok(jwk)
+}
+
+/// Decrypt a compact JWE sent as a `direct_post.jwt` response, or `None` if it
+/// can't be.
+///
+/// The key is looked up by the `kid` in the protected header and only removed once
+/// the response has been decrypted with it, so junk sent with a published `kid`
+/// can't use the key up, and a response can only ever be decrypted once.
+pub(crate) async fn decrypt_response(redis: &RedisClient, jwe: &str) -> Result<Option<Vec<u8>>> {
+    let Some(kid) = jwe
+        .split('.')
+        .next()
+        .and_then(|header| decode_header(header).ok())
+        .and_then(|header| header.kid)
+    else {
+        return Ok(None);
+    };
+
+    let secret = match RESPONSE_KEY.get(redis, &kid).await {
+        Ok(secret) => secret,
+        Err(err) if err.is_not_found() => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    let Ok(plaintext) = decrypt(&SecretKey::from_jwk_str(&secret)?, jwe) else {
+        return Ok(None);
+    };
+
+    // Whoever removes the key first gets to use the response.
+    if RESPONSE_KEY.take(redis, &kid).await?.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(plaintext))
+}
+
+fn decrypt(secret: &SecretKey, jwe: &str) -> Result<Vec<u8>> {
+    let [encoded_header, encrypted_key, iv, ciphertext, tag] =
+        jwe.split('.').collect::<Vec<_>>()[..]
+    else {
+        return Err(eyre!("jwe does not match expected structure").into());
+    };
+
+    let header = decode_header(encoded_header)?;
+
+    // With direct key agreement there's no wrapped key.
+    if header.alg != ALG || !encrypted_key.is_empty() {
+        return Err(eyre!("unsupported jwe alg: {}", header.alg).into());
+    }
+
+    let key_length = match header.enc.as_str() {
+        "A128GCM" => 16,
+        "A256GCM" => 32,
+        enc => return Err(eyre!("unsupported jwe enc: {enc}").into()),
+    };
+
+    // Derive the content encryption key from the shared secret.
+    // ----------------------------------------------------------
+
+    let epk = PublicKey::from_jwk_str(&header.epk.to_string())?;
+    let shared = diffie_hellman(secret.to_nonzero_scalar(), epk.as_affine());
+
+    let apu = BASE64_URL_SAFE_NO_PAD.decode(header.apu.unwrap_or_default())?;
+    let apv = BASE64_URL_SAFE_NO_PAD.decode(header.apv.unwrap_or_default())?;
+
+    let cek = concat_kdf(
+        shared.raw_secret_bytes(),
+        &header.enc,
+        &apu,
+        &apv,
+        key_length,
+    );
+
+    // Decrypt the content.
+    // --------------------
+
+    let iv = BASE64_URL_SAFE_NO_PAD.decode(iv)?;
+
+    // AES-GCM only takes 96-bit IVs.
+    if iv.len() != 12 {
+        return Err(eyre!("jwe iv must be 96 bits").into());
+    }
+
+    let mut message = BASE64_URL_SAFE_NO_PAD.decode(ciphertext)?;
+    message.extend(BASE64_URL_SAFE_NO_PAD.decode(tag)?);
+
+    let payload = Payload {
+        msg: &message,
+        aad: encoded_header.as_bytes(),
+    };
+
+    let plaintext = match key_length {
+        16 => Aes128Gcm::new_from_slice(&cek)?.decrypt(Nonce::from_slice(&iv), payload),
+        _ => Aes256Gcm::new_from_slice(&cek)?.decrypt(Nonce::from_slice(&iv), payload),
+    }?;
+
+    Ok(plaintext)
+}
+
+fn decode_header(encoded: &str) -> Result<Header> {
+    Ok(serde_json::from_slice(
+        &BASE64_URL_SAFE_NO_PAD.decode(encoded)?,
+    )?)
+}
+
+/// Single-pass Concat KDF, which is all we need for keys up to 256 bits.
+///
+/// See: https://www.rfc-editor.org/rfc/rfc7518#section-4.6.2
+fn concat_kdf(z: &[u8], enc: &str, apu: &[u8], apv: &[u8], key_length: usize) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+
+    hasher.update(1u32.to_be_bytes());
+    hasher.update(z);
+
+    for info in [enc.as_bytes(), apu, apv] {
+        hasher.update((info.len() as u32).to_be_bytes());
+        hasher.update(info);
+    }
+
+    hasher.update(((key_length * 8) as u32).to_be_bytes());
+
+    hasher.finalize()[..key_length].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use aes_gcm::AeadCore;
+
+    use super::*;
+
+    /// Encrypt the way a wallet would, so we can check we decrypt it correctly.
+    fn encrypt(recipient: &PublicKey, plaintext: &[u8]) -> String {
+        let ephemeral = SecretKey::random(&mut OsRng);
+        let header = json!({
+            "alg": ALG,
+            "enc": ENC,
+            "kid": "test",
+            "epk": ephemeral.public_key().to_jwk(),
+        });
+        let encoded_header = BASE64_URL_SAFE_NO_PAD.encode(header.to_string());
+
+        let shared = diffie_hellman(ephemeral.to_nonzero_scalar(), recipient.as_affine());
+        let cek = concat_kdf(shared.raw_secret_bytes(), ENC, &[], &[], 32);
+
+        let iv = Aes256Gcm::generate_nonce(&mut OsRng);
+        let mut ciphertext = Aes256Gcm::new_from_slice(&cek)
+            .unwrap()
+            .encrypt(
+                &iv,
+                Payload {
+                    msg: plaintext,
+                    aad: encoded_header.as_bytes(),
+                },
+            )
+            .unwrap();
+        let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+        format!(
+            "{encoded_header}..{}.{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(iv),
+            BASE64_URL_SAFE_NO_PAD.encode(ciphertext),
+            BASE64_URL_SAFE_NO_PAD.encode(tag),
+        )
+    }
+
+    #[test]
+    fn decrypts() -> Result<()> {
+        let secret = SecretKey::random(&mut OsRng);
+        let jwe = encrypt(&secret.public_key(), b"state=abc");
+
+        assert_eq!(decrypt(&secret, &jwe)?, b"state=abc");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_wrong_key() {
+        let jwe = encrypt(&SecretKey::random(&mut OsRng).public_key(), b"state=abc");
+
+        assert!(decrypt(&SecretKey::random(&mut OsRng), &jwe).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_iv_length() {
+        let secret = SecretKey::random(&mut OsRng);
+        let jwe = encrypt(&secret.public_key(), b"state=abc");
+
+        let mut parts = jwe.split('.').map(String::from).collect::<Vec<_>>();
+        parts[2] = BASE64_URL_SAFE_NO_PAD.encode([0; 16]);
+
+        assert!(decrypt(&secret, &parts.join(".")).is_err());
+    }
+}
@@ -1,34 +1,190 @@
+// Signed, single-use `state` and `nonce` values for SIOP requests.
+//
+// Each token is `base64url(claims).base64url(hmac)`, signed with the service's
+// token key. The claims bind it to the session, service and action it was issued
+// for, and it expires along with the auth request it's part of. Once verified, a
+// token is consumed by recording its id in Redis, so a captured response can't be
+// replayed.
+// -------------------------------------------------------------------------------
+
 use base64::prelude::*;
+use chrono::Utc;
+use eyre::{eyre, OptionExt};
+use fred::{prelude::RedisClient, types::Expiration};
 use hmac::{Hmac, Mac};
-use mist_common::Result;
+use mist_common::{redis::TypedRedis, Result};
+use mist_db::models::service::ServiceId;
 use openidconnect::{CsrfToken, Nonce};
+use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::session::{AuthAction, SessionId};
 
-use crate::session::SessionId;
+/// Ids of tokens that have already been used.
+static CONSUMED_TOKEN: TypedRedis<()> = TypedRedis::new("mist-consumed-token");
 
-pub fn created_signed_state(key: &[u8], csrf: &str, id: &SessionId) -> Result<CsrfToken> {
-    let token = CsrfToken::new(format!("{}:{}:{}", csrf, id, sign_state(key, csrf, id)?));
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TokenKind {
+    State,
+    Nonce,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct TokenClaims {
+    pub(crate) jti: Uuid,
+    pub(crate) kind: TokenKind,
+    pub(crate) session_id: SessionId,
+    pub(crate) service_id: ServiceId,
+    pub(crate) action: AuthAction,
+    pub(crate) iat: i64,
+    pub(crate) exp: i64,
+}
+
+impl TokenClaims {
+    pub(crate) fn new(
+        kind: TokenKind,
+        session_id: &SessionId,
+        service_id: &ServiceId,
+        action: &AuthAction,
+        expires_at: i64,
+    ) -> Self {
+        Self {
+            jti: Uuid::new_v4(),
+            kind,
+            session_id: *session_id,
+            service_id: *service_id,
+            action: action.clone(),
+            iat: Utc::now().timestamp(),
+            exp: expires_at,
+        }
+    }
+}
+
+pub(crate) fn create_state(key: &[u8], claims: &TokenClaims) -> Result<CsrfToken> {
+    Ok(CsrfToken::new(sign(key, claims)?))
+}
 
-    Ok(token)
+pub(crate) fn create_nonce(key: &[u8], claims: &TokenClaims) -> Result<Nonce> {
+    Ok(Nonce::new(sign(key, claims)?))
 }
 
-pub fn sign_state(key: &[u8], csrf: &str, id: &SessionId) -> Result<String> {
+fn sign(key: &[u8], claims: &TokenClaims) -> Result<String> {
+    let payload = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?);
+
     let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
-    mac.update(csrf.as_bytes());
-    mac.update(id.as_ref().as_bytes());
+    mac.update(payload.as_bytes());
 
-    Ok(BASE64_URL_SAFE.encode(mac.finalize().into_bytes()))
+    Ok(format!(
+        "{payload}.{}",
+        BASE64_URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+    ))
 }
 
-pub fn create_signed_nonce(key: &[u8], nonce: &str) -> Result<Nonce> {
-    let nonce = Nonce::new(format!("{}:{}", nonce, sign_nonce(key, nonce)?));
+/// Read the claims without checking the signature.
+///
+/// Only useful for working out which service's key to verify the token with.
+pub(crate) fn decode_unverified(token: &str) -> Result<TokenClaims> {
+    let (payload, _) = token.split_once('.').ok_or_eyre("malformed token")?;
 
-    Ok(nonce)
+    Ok(serde_json::from_slice(
+        &BASE64_URL_SAFE_NO_PAD.decode(payload)?,
+    )?)
 }
 
-pub fn sign_nonce(key: &[u8], nonce: &str) -> Result<String> {
+/// Check the token's signature, kind and lifetime, and return its claims.
+pub(crate) fn verify(key: &[u8], token: &str, kind: TokenKind) -> Result<TokenClaims> {
+    let (payload, signature) = token.split_once('.').ok_or_eyre("malformed token")?;
+
     let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
-    mac.update(nonce.as_bytes());
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&BASE64_URL_SAFE_NO_PAD.decode(signature)?)
+        .map_err(|_| eyre!("invalid token signature"))?;
+
+    let claims = serde_json::from_slice::<TokenClaims>(&BASE64_URL_SAFE_NO_PAD.decode(payload)?)?;
+
+    if claims.kind != kind {
+        return Err(eyre!("expected a {kind:?} token").into());
+    }
+
+    if claims.exp < Utc::now().timestamp() {
+        return Err(eyre!("token has expired").into());
+    }
+
+    Ok(claims)
+}
+
+/// Mark the token as used, failing if it already has been.
+pub(crate) async fn consume(redis: &RedisClient, claims: &TokenClaims) -> Result<()> {
+    // Only needs remembering until the token would have expired anyway.
+    let ttl = (claims.exp - Utc::now().timestamp()).max(1);
+
+    let consumed = CONSUMED_TOKEN
+        .set_nx(redis, &claims.jti.to_string(), &(), Expiration::EX(ttl))
+        .await?;
+
+    if !consumed {
+        return Err(eyre!("token has already been used").into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"a very secret service token key";
+
+    fn claims(kind: TokenKind) -> TokenClaims {
+        TokenClaims::new(
+            kind,
+            &SessionId::new(),
+            &ServiceId::from(Uuid::new_v4()),
+            &AuthAction::In,
+            Utc::now().timestamp() + 60,
+        )
+    }
+
+    #[test]
+    fn verifies_signed_tokens() {
+        let issued = claims(TokenKind::State);
+        let token = create_state(KEY, &issued).unwrap();
+
+        let verified = verify(KEY, token.secret(), TokenKind::State).unwrap();
+
+        assert_eq!(verified.jti, issued.jti);
+        assert_eq!(verified.service_id, issued.service_id);
+        assert_eq!(decode_unverified(token.secret()).unwrap().jti, issued.jti);
+    }
+
+    #[test]
+    fn rejects_tampered_tokens() {
+        let token = create_nonce(KEY, &claims(TokenKind::Nonce)).unwrap();
+        let (_, signature) = token.secret().split_once('.').unwrap();
+
+        let forged = sign(b"some other key", &claims(TokenKind::Nonce)).unwrap();
+        let (payload, _) = forged.split_once('.').unwrap();
+
+        assert!(verify(KEY, &format!("{payload}.{signature}"), TokenKind::Nonce).is_err());
+        assert!(verify(b"some other key", token.secret(), TokenKind::Nonce).is_err());
+    }
+
+    #[test]
+    fn rejects_the_wrong_kind() {
+        let token = create_nonce(KEY, &claims(TokenKind::Nonce)).unwrap();
+
+        assert!(verify(KEY, token.secret(), TokenKind::State).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_tokens() {
+        let mut expired = claims(TokenKind::State);
+        expired.exp = Utc::now().timestamp() - 1;
+
+        let token = create_state(KEY, &expired).unwrap();
 
-    Ok(BASE64_URL_SAFE.encode(mac.finalize().into_bytes()))
+        assert!(verify(KEY, token.secret(), TokenKind::State).is_err());
+    }
 }
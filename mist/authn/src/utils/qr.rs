@@ -0,0 +1,17 @@
+use std::io::Cursor;
+
+use base64::prelude::*;
+use image::{ImageFormat, Luma};
+use mist_common::Result;
+use qrcode::QrCode;
+
+/// Render the data as a QR code, as a base64-encoded PNG ready for a data URL.
+pub(crate) fn png_base64(data: &str) -> Result<String> {
+    let code = QrCode::new(data)?;
+    let image = code.render::<Luma<u8>>().max_dimensions(500, 500).build();
+    let mut buf = Vec::new();
+    let mut cursor = Cursor::new(&mut buf);
+    image.write_to(&mut cursor, ImageFormat::Png)?;
+
+    Ok(BASE64_STANDARD.encode(buf))
+}
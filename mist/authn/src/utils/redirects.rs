@@ -0,0 +1,77 @@
+use mist_common::Result;
+use mist_db::models::{redirect_uri::RedirectUriKind, service::Service};
+use reqwest::Url;
+
+use crate::{
+    session::{AuthSession, ReturnTo, SessionId},
+    state::AuthnState,
+    tokens,
+};
+
+/// Whether the service allows sending users (or wallets) to the URI: either its own
+/// URL for the kind of redirect, or one it registered. Either way, it has to match
+/// exactly.
+pub(crate) async fn is_allowed(
+    state: &AuthnState,
+    service: &Service,
+    kind: &RedirectUriKind,
+    uri: &str,
+) -> Result<bool> {
+    let own = match kind {
+        RedirectUriKind::Redirect => Some(&service.redirect_url),
+        RedirectUriKind::PostLogout => Some(&service.logout_url),
+        // Wallets only ever go back to URIs registered for them.
+        RedirectUriKind::Wallet => None,
+    };
+
+    if own.is_some_and(|own| own == uri) {
+        return Ok(true);
+    }
+
+    state
+        .repos
+        .redirect_uris
+        .exists(&service.id, kind, uri)
+        .await
+}
+
+/// Whether the service is allowed to ask for the user to be sent back there.
+pub(crate) async fn is_allowed_return(
+    state: &AuthnState,
+    service: &Service,
+    return_to: &ReturnTo,
+) -> Result<bool> {
+    match &return_to.redirect_uri {
+        Some(uri) => is_allowed(state, service, &RedirectUriKind::Redirect, uri).await,
+        None => Ok(true),
+    }
+}
+
+/// Where to send the user once they're signed in: wherever the service asked, with
+/// its `state`, and a code to swap for tokens if it uses them.
+pub(crate) async fn after_sign_in(
+    state: &AuthnState,
+    service: &Service,
+    session_id: &SessionId,
+    session: &AuthSession,
+) -> Result<String> {
+    let mut url = Url::parse(
+        session
+            .return_to
+            .redirect_uri
+            .as_ref()
+            .unwrap_or(&service.redirect_url),
+    )?;
+
+    if service.token_flow {
+        let code = tokens::issue_code(state, session_id).await?;
+
+        url.query_pairs_mut().append_pair("code", &code);
+    }
+
+    if let Some(value) = &session.return_to.state {
+        url.query_pairs_mut().append_pair("state", value);
+    }
+
+    Ok(url.into())
+}
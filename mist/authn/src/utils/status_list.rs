@@ -0,0 +1,464 @@
+// Revocation and suspension checks for presented credentials.
+//
+// Supports `StatusList2021Entry` and `BitstringStatusListEntry` statuses, where the
+// status list itself is a JWT-secured credential signed by a resolvable DID. Lists
+// are only fetched over https, and only count if the credential's issuer signed them.
+//
+// See: https://www.w3.org/TR/vc-bitstring-status-list/
+// ---------------------------------------------------------------------------------
+
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use base64::prelude::*;
+use eyre::{eyre, OptionExt};
+use flate2::read::GzDecoder;
+use mist_common::Result;
+use mist_db::models::service::RevocationPolicy;
+use serde::Deserialize;
+use serde_json::Value;
+use ssi::vc;
+use tokio::sync::RwLock;
+
+use super::did;
+
+/// How long a fetched status list is trusted before it's fetched again.
+const CACHE_TTL: Duration = Duration::from_secs(60 * 5);
+
+/// How many status lists are kept at once, so presenters can't grow the cache forever.
+const CACHE_CAPACITY: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StatusEntry {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default = "default_purpose")]
+    status_purpose: String,
+    status_list_index: String,
+    status_list_credential: String,
+}
+
+fn default_purpose() -> String {
+    "revocation".into()
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Status {
+    Valid,
+    Revoked,
+    Suspended,
+}
+
+struct CachedList {
+    fetched_at: Instant,
+    /// The DID that signed the list.
+    issuer: String,
+    purpose: String,
+    bits: Vec<u8>,
+}
+
+/// Fetches, verifies and caches status lists.
+#[derive(Clone)]
+pub(crate) struct StatusLists {
+    http: reqwest::Client,
+    resolver_url: String,
+    cache: Arc<RwLock<HashMap<String, Arc<CachedList>>>>,
+    /// Allow plain http lists, for tests.
+    insecure: bool,
+}
+
+impl StatusLists {
+    pub(crate) fn new(resolver_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            resolver_url: resolver_url.into(),
+            cache: Default::default(),
+            insecure: false,
+        }
+    }
+
+    /// Reject the credential if its status says it's been revoked or suspended.
+    ///
+    /// If the status list can't be reached, the service's policy decides whether we
+    /// let it through. Lists that can be reached but not trusted are always rejected.
+    pub(crate) async fn ensure_valid(
+        &self,
+        status: &vc::Status,
+        issuer: &str,
+        policy: &RevocationPolicy,
+    ) -> Result<()> {
+        let entry = serde_json::from_value::<StatusEntry>(serde_json::to_value(status)?)?;
+
+        match self.check(&entry, issuer).await? {
+            Some(Status::Valid) => Ok(()),
+            Some(Status::Revoked) => Err(eyre!("credential has been revoked").into()),
+            Some(Status::Suspended) => Err(eyre!("credential has been suspended").into()),
+            None => match policy {
+                RevocationPolicy::FailOpen => {
+                    tracing::warn!(
+                        "could not reach status list {}, allowing",
+                        entry.status_list_credential
+                    );
+
+                    Ok(())
+                }
+                RevocationPolicy::FailClosed => Err(eyre!(
+                    "could not reach status list {}",
+                    entry.status_list_credential
+                )
+                .into()),
+            },
+        }
+    }
+
+    /// The credential's status, or `None` if its status list can't be reached.
+    ///
+    /// `issuer` is the credential's verified issuer, who has to have signed the list.
+    pub(crate) async fn check(&self, entry: &StatusEntry, issuer: &str) -> Result<Option<Status>> {
+        if !matches!(
+            entry.kind.as_str(),
+            "StatusList2021Entry" | "BitstringStatusListEntry"
+        ) {
+            return Err(eyre!("unsupported credential status type: {}", entry.kind).into());
+        }
+
+        let Some(list) = self.get(&entry.status_list_credential).await? else {
+            return Ok(None);
+        };
+
+        // Otherwise anyone could vouch for a credential with a list of their own.
+        if list.issuer != issuer {
+            return Err(eyre!("status list was not signed by the credential's issuer").into());
+        }
+
+        if list.purpose != entry.status_purpose {
+            return Err(eyre!("status list purpose does not match entry").into());
+        }
+
+        let index = entry.status_list_index.parse::<usize>()?;
+        let byte = list
+            .bits
+            .get(index / 8)
+            .ok_or_eyre("status list index out of range")?;
+
+        // The first index is the left-most bit of the first byte.
+        if byte & (0b1000_0000 >> (index % 8)) == 0 {
+            return Ok(Some(Status::Valid));
+        }
+
+        match entry.status_purpose.as_str() {
+            "revocation" => Ok(Some(Status::Revoked)),
+            "suspension" => Ok(Some(Status::Suspended)),
+            purpose => Err(eyre!("unsupported status purpose: {purpose}").into()),
+        }
+    }
+
+    /// The verified list at the URL, or `None` if it can't be downloaded.
+    async fn get(&self, url: &str) -> Result<Option<Arc<CachedList>>> {
+        if let Some(cached) = self.cache.read().await.get(url) {
+            if cached.fetched_at.elapsed() < CACHE_TTL {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        if !self.insecure && reqwest::Url::parse(url)?.scheme() != "https" {
+            return Err(eyre!("status list is not served over https: {url}").into());
+        }
+
+        let jwt = match self.download(url).await {
+            Ok(jwt) => jwt,
+            Err(err) => {
+                tracing::warn!(%err, url, "could not download status list");
+
+                return Ok(None);
+            }
+        };
+
+        let list = Arc::new(self.verify(jwt.trim()).await?);
+
+        let mut cache = self.cache.write().await;
+
+        cache.retain(|_, cached| cached.fetched_at.elapsed() < CACHE_TTL);
+
+        if cache.len() >= CACHE_CAPACITY {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.fetched_at)
+                .map(|(url, _)| url.clone());
+
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+
+        cache.insert(url.into(), list.clone());
+
+        Ok(Some(list))
+    }
+
+    async fn download(&self, url: &str) -> reqwest::Result<String> {
+        self.http
+            .get(url)
+            .header("Accept", "application/vc+jwt, application/jwt")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+
+    async fn verify(&self, jwt: &str) -> Result<CachedList> {
+        // Verify the list was signed by its issuer.
+        // -----------------------------------------
+
+        let claims = ssi::jwt::decode_unverified::<Value>(jwt)?;
+        let header = jsonwebtoken::decode_header(jwt)?;
+
+        let iss = claims["iss"].as_str();
+        let kid = header
+            .kid
+            .as_deref()
+            .or(iss)
+            .ok_or_eyre("status list is missing an issuer")?;
+        let issuer = kid.split('#').next().unwrap_or(kid).to_string();
+
+        // Don't let someone sign with their own key while claiming to be someone else.
+        if iss.is_some_and(|iss| iss != issuer) {
+            return Err(eyre!("status list was not signed by its issuer").into());
+        }
+
+        let key = did::assertion_key(&self.resolver_url, kid).await?;
+        let claims = ssi::jwt::decode_verify::<Value>(jwt, &key)?;
+
+        if let Some(exp) = claims["exp"].as_i64() {
+            if exp < chrono::Utc::now().timestamp() {
+                return Err(eyre!("status list has expired").into());
+            }
+        }
+
+        // Decode the list.
+        // ----------------
+
+        // VC-JWT wraps the credential in a `vc` claim, VC-JOSE doesn't.
+        let subject = claims
+            .get("vc")
+            .unwrap_or(&claims)
+            .get("credentialSubject")
+            .ok_or_eyre("status list is missing a subject")?;
+
+        let encoded = subject["encodedList"]
+            .as_str()
+            .ok_or_eyre("status list is missing an encoded list")?;
+
+        // Bitstring Status List uses a multibase prefix, StatusList2021 doesn't.
+        let encoded = encoded.strip_prefix('u').unwrap_or(encoded);
+        let compressed = BASE64_URL_SAFE_NO_PAD
+            .decode(encoded)
+            .or_else(|_| BASE64_STANDARD.decode(encoded))?;
+
+        let mut bits = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut bits)?;
+
+        Ok(CachedList {
+            fetched_at: Instant::now(),
+            issuer,
+            purpose: subject["statusPurpose"]
+                .as_str()
+                .map(String::from)
+                .unwrap_or_else(default_purpose),
+            bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use axum::{extract::State, routing, Json, Router};
+    use flate2::{write::GzEncoder, Compression};
+    use serde_json::json;
+    use ssi::jwk::{Algorithm, JWK};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const ISSUER: &str = "did:example:issuer";
+
+    /// Serve a DID document and a status list with index `3` set, like an issuer would.
+    async fn stub_server(key: JWK) -> String {
+        let mut bits = vec![0u8; 16];
+        bits[0] = 0b0001_0000;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bits).unwrap();
+        let encoded = format!(
+            "u{}",
+            BASE64_URL_SAFE_NO_PAD.encode(encoder.finish().unwrap())
+        );
+
+        let mut signing_key = key.clone();
+        signing_key.key_id = Some(format!("{ISSUER}#key-1"));
+
+        let jwt = ssi::jwt::encode_sign(
+            Algorithm::ES256,
+            &json!({
+                "iss": ISSUER,
+                "vc": {
+                    "type": ["VerifiableCredential", "BitstringStatusListCredential"],
+                    "credentialSubject": {
+                        "type": "BitstringStatusList",
+                        "statusPurpose": "revocation",
+                        "encodedList": encoded,
+                    }
+                }
+            }),
+            &signing_key,
+        )
+        .unwrap();
+
+        let document = json!({
+            "didDocument": {
+                "@context": "https://www.w3.org/ns/did/v1",
+                "id": ISSUER,
+                "verificationMethod": [{
+                    "id": format!("{ISSUER}#key-1"),
+                    "type": "JsonWebKey2020",
+                    "controller": ISSUER,
+                    "publicKeyJwk": key.to_public(),
+                }],
+                "assertionMethod": [format!("{ISSUER}#key-1")],
+            }
+        });
+
+        let app = Router::new()
+            .route(
+                "/identifiers/:did",
+                routing::get(|State((document, _)): State<(Value, String)>| async move {
+                    Json(document)
+                }),
+            )
+            .route(
+                "/status/1",
+                routing::get(|State((_, jwt)): State<(Value, String)>| async move { jwt }),
+            )
+            .with_state((document, jwt));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{address}")
+    }
+
+    fn lists(resolver_url: &str) -> StatusLists {
+        StatusLists {
+            insecure: true,
+            ..StatusLists::new(resolver_url)
+        }
+    }
+
+    fn entry(url: &str, index: usize) -> StatusEntry {
+        StatusEntry {
+            kind: "BitstringStatusListEntry".into(),
+            status_purpose: "revocation".into(),
+            status_list_index: index.to_string(),
+            status_list_credential: format!("{url}/status/1"),
+        }
+    }
+
+    fn status(url: &str, index: usize) -> Result<vc::Status> {
+        Ok(serde_json::from_value(json!({
+            "id": format!("{url}/status/1#{index}"),
+            "type": "BitstringStatusListEntry",
+            "statusPurpose": "revocation",
+            "statusListIndex": index.to_string(),
+            "statusListCredential": format!("{url}/status/1"),
+        }))?)
+    }
+
+    #[tokio::test]
+    async fn checks_status() -> Result<()> {
+        let url = stub_server(JWK::generate_p256()?).await;
+        let lists = lists(&format!("{url}/identifiers"));
+
+        assert_eq!(
+            lists.check(&entry(&url, 3), ISSUER).await?,
+            Some(Status::Revoked)
+        );
+        assert_eq!(
+            lists.check(&entry(&url, 4), ISSUER).await?,
+            Some(Status::Valid)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_signature() -> Result<()> {
+        let url = stub_server(JWK::generate_p256()?).await;
+
+        // Resolve the issuer from a server that has a different key.
+        let other = stub_server(JWK::generate_p256()?).await;
+        let lists = lists(&format!("{other}/identifiers"));
+
+        assert!(lists.check(&entry(&url, 4), ISSUER).await.is_err());
+
+        // Failing open is only for lists that can't be reached.
+        assert!(lists
+            .ensure_valid(&status(&url, 4)?, ISSUER, &RevocationPolicy::FailOpen)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_list_from_another_issuer() -> Result<()> {
+        let url = stub_server(JWK::generate_p256()?).await;
+        let lists = lists(&format!("{url}/identifiers"));
+
+        assert!(lists
+            .check(&entry(&url, 4), "did:example:someone-else")
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn only_fetches_over_https() -> Result<()> {
+        let url = stub_server(JWK::generate_p256()?).await;
+        let lists = StatusLists::new(&format!("{url}/identifiers"));
+
+        assert!(lists.check(&entry(&url, 4), ISSUER).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn applies_policy_when_unreachable() -> Result<()> {
+        let lists = lists("http://127.0.0.1:1/identifiers");
+        let status = status("http://127.0.0.1:1", 3)?;
+
+        assert!(lists
+            .ensure_valid(&status, ISSUER, &RevocationPolicy::FailOpen)
+            .await
+            .is_ok());
+        assert!(lists
+            .ensure_valid(&status, ISSUER, &RevocationPolicy::FailClosed)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -1 +1,9 @@
+pub(crate) mod consent;
+pub(crate) mod device;
+mod layout;
+pub(crate) mod logged_out;
+pub(crate) mod offer;
+pub(crate) mod resume;
 pub(crate) mod scan;
+pub(crate) mod sign_in_first;
+pub(crate) mod sso_consent;
@@ -0,0 +1,55 @@
+use maud::{html, Markup};
+use mist_db::models::{definition::Field, service::Service};
+
+use super::layout;
+
+pub(crate) fn view(service: &Service, authn_url: &str, fields: &[Field]) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Sign up to " span class="font-semibold" { (service.name) } }
+
+            @if fields.is_empty() {
+                p class="text-slate-500" { (service.name) " will only get your wallet's identifier." }
+            } @else {
+                p class="text-slate-500" { (service.name) " will get the following from your wallet:" }
+
+                ul class="mt-4 list-disc text-slate-700" {
+                    @for field in fields {
+                        li {
+                            (field.name)
+                            @if !field.required {
+                                span class="text-slate-500" { " (optional)" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            @if let Some(purpose) = &service.consent_purpose {
+                p class="mt-4 max-w-md text-center text-slate-500" { (purpose) }
+            }
+
+            @if service.privacy_policy_url.is_some() || service.terms_url.is_some() {
+                p class="mt-4 text-sm text-slate-500" {
+                    @if let Some(url) = &service.privacy_policy_url {
+                        a class="link" href=(url) target="_blank" { "Privacy policy" }
+                    }
+                    @if service.privacy_policy_url.is_some() && service.terms_url.is_some() {
+                        " · "
+                    }
+                    @if let Some(url) = &service.terms_url {
+                        a class="link" href=(url) target="_blank" { "Terms" }
+                    }
+                }
+            }
+
+            form method="POST" action={ (authn_url) "/" (service.name) "/consent" } {
+                button class="mt-6 btn btn-neutral" type="submit" { "Agree and continue" }
+            }
+
+            a class="mt-2 text-sm link text-slate-500" href=(service.logout_url) { "Cancel" }
+        },
+    )
+}
@@ -0,0 +1,45 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+
+/// Asks for the code shown on the device, or for the user to check it if the device
+/// sent them here with it.
+pub(crate) fn enter_code(
+    service: &Service,
+    authn_url: &str,
+    user_code: Option<&str>,
+    error: Option<&str>,
+) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Sign in to " span class="font-semibold" { (service.name) } " on a device" }
+
+            p class="text-slate-500" { "Enter the code shown on your device. Only continue if you started signing in there yourself." }
+
+            form class="flex flex-col items-center" method="POST" action={ (authn_url) "/" (service.name) "/device" } {
+                input class="mt-6 text-center uppercase input input-bordered tracking-widest" type="text" name="user_code" value=[user_code] placeholder="BCDF-GHJK" autocomplete="off" required;
+
+                @if let Some(error) = error {
+                    p class="mt-2 text-sm text-error" { (error) }
+                }
+
+                button class="mt-6 btn btn-neutral" type="submit" { "Continue" }
+            }
+        },
+    )
+}
+
+pub(crate) fn signed_in(service: &Service) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Signed in to " span class="font-semibold" { (service.name) } }
+
+            p class="text-slate-500" { "You can close this page and go back to your device." }
+        },
+    )
+}
@@ -0,0 +1,106 @@
+use maud::{html, Markup, PreEscaped};
+use mist_db::models::service::Service;
+
+/// The page shell shared by every view the user sees while signing in.
+pub(crate) fn page(service: &Service, script: Option<String>, content: Markup) -> Markup {
+    html! {
+        script src="https://cdn.twind.style" crossorigin {}
+        link href="https://cdn.jsdelivr.net/npm/daisyui@4.12.10/dist/full.min.css" rel="stylesheet" type="text/css";
+        meta name="viewport" content="width=device-width, initial-scale=1";
+
+        @if let Some(script) = script {
+            script { (PreEscaped(script)) }
+        }
+
+        title { (service.name) " | Mist" }
+
+        body class="bg-gradient-to-t from-slate-200 to-slate-100";
+
+        main class="flex flex-col items-center justify-center h-screen p-12" {
+            (content)
+
+            p class="mt-4 text-slate-500" { "Your identity, your data —"
+                span class="text-slate-700" { " Anchored in Mist" }
+            }
+
+            a class="mt-4 text-black opacity-10 hover:opacity-100 transition ease-in-out hover:-translate-y-1 hover:scale-110 duration-150 tooltip tooltip-bottom"
+                href="https://mist.id"
+                target="_blank"
+                data-tip="Ready to own your identity?"
+            {
+                svg class="w-12 h-12" {
+                    path d="M7.055 25.445c0 9.93 8.015 17.946 17.945 17.946 9.852 0 17.867-8.016 17.945-17.868a4.486 4.486 0 0 1-4.508 4.352c-2.44 0-4.503-1.984-4.503-4.508v-5.32c0-7.406-6.032-13.438-13.442-13.438-7.406 0-13.437 6.032-13.437 13.438Zm6.715-7.843a2.855 2.855 0 0 1 2.855 2.859v4c0 1.578-1.29 2.86-2.855 2.86a2.86 2.86 0 0 1-2.856-2.86v-4a2.86 2.86 0 0 1 2.856-2.86Zm8.37 0A2.856 2.856 0 0 1 25 20.46v4a2.868 2.868 0 0 1-2.86 2.86 2.86 2.86 0 0 1-2.85-2.86v-4a2.86 2.86 0 0 1 2.85-2.86Zm0 0";
+                }
+            }
+        }
+    }
+}
+
+/// Follows the session's progress on the page's `#status` element, and sends the
+/// user on to the service once they're signed in.
+///
+/// Browsers without `EventSource` poll `/status` instead.
+pub(crate) fn wait_for_redirect(service: &Service, authn_url: &str) -> String {
+    format!(
+        r#"
+            document.addEventListener("DOMContentLoaded", () => {{
+                const status = document.getElementById("status");
+                const messages = {{
+                    scanned: "Code scanned, check your wallet",
+                    verifying: "Verifying your credentials…",
+                    registering: "Setting up your account…",
+                    signing_in: "Signing you in…",
+                    expired: "This code has expired, refresh the page to try again",
+                }};
+
+                // Returns true once there's nothing more to wait for.
+                const update = (event) => {{
+                    document.dispatchEvent(new CustomEvent("mist:progress", {{ detail: event }}));
+
+                    if (event.status === "authenticated") {{
+                        window.location.href = "{0}/continue?service_id={1}";
+                        return true;
+                    }}
+
+                    if (event.status === "rejected") {{
+                        status.textContent = `Sign in was rejected: ${{event.reason}}`;
+                        status.classList.add("text-error");
+                        return true;
+                    }}
+
+                    if (messages[event.status]) {{
+                        status.textContent = messages[event.status];
+                    }}
+
+                    return event.status === "expired";
+                }};
+
+                if (window.EventSource) {{
+                    const source = new EventSource("{0}/waiting?service_id={1}", {{ withCredentials: true }});
+
+                    source.onmessage = (message) => {{
+                        if (update(JSON.parse(message.data))) {{
+                            source.close();
+                        }}
+                    }};
+                }} else {{
+                    const poll = setInterval(async () => {{
+                        const response = await fetch("{0}/status?service_id={1}", {{ credentials: "include" }});
+
+                        if (response.ok && update(await response.json())) {{
+                            clearInterval(poll);
+                        }}
+                    }}, 2000);
+                }}
+            }});
+        "#,
+        authn_url, service.id
+    )
+}
+
+/// Where [`wait_for_redirect`] shows the session's progress.
+pub(crate) fn status() -> Markup {
+    html! {
+        p id="status" class="mt-4 text-slate-500" aria-live="polite" {}
+    }
+}
@@ -0,0 +1,32 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+
+/// Loads each service's front-channel logout page, then sends the user on once
+/// they've all had a chance to clear their cookies.
+const CONTINUE_AFTER_FRAMES: &str = r#"
+    window.addEventListener("load", () => {
+        setTimeout(() => {
+            window.location.href = document.getElementById("continue").href;
+        }, 500);
+    });
+"#;
+
+pub(crate) fn view(service: &Service, frontchannel_urls: &[String], redirect_url: &str) -> Markup {
+    layout::page(
+        service,
+        Some(CONTINUE_AFTER_FRAMES.into()),
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Signing you out of " span class="font-semibold" { (service.name) } }
+
+            span class="loading loading-dots loading-lg text-slate-500" {}
+
+            @for url in frontchannel_urls {
+                iframe class="hidden" src=(url) {}
+            }
+
+            a id="continue" class="mt-4 text-sm link text-slate-500" href=(redirect_url) { "Continue" }
+        },
+    )
+}
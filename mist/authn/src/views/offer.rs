@@ -0,0 +1,18 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+
+pub(crate) fn view(service: &Service, credential_name: &str, qr: &str, url: &str) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Scan to add your " span class="font-semibold" { (credential_name) } " credential" }
+
+            img class="shadow-md" src={"data:image/png;base64," (qr)};
+
+            a class="mt-6 btn btn-neutral" href=(url) { "Open in wallet" }
+        },
+    )
+}
@@ -0,0 +1,18 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+
+pub(crate) fn view(service: &Service, authn_url: &str) -> Markup {
+    layout::page(
+        service,
+        Some(layout::wait_for_redirect(service, authn_url)),
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Finishing sign in to " span class="font-semibold" { (service.name) } }
+
+            span class="loading loading-dots loading-lg text-slate-500" {}
+
+            (layout::status())
+        },
+    )
+}
@@ -1,47 +1,116 @@
-use maud::{html, Markup, PreEscaped};
+use maud::{html, Markup};
 use mist_db::models::service::Service;
 
-pub(crate) fn view(service: &Service, authn_url: &str, qr: &str) -> Markup {
-    html! {
-        script src="https://cdn.twind.style" crossorigin {}
-        link href="https://cdn.jsdelivr.net/npm/daisyui@4.12.10/dist/full.min.css" rel="stylesheet" type="text/css";
-
-        script {
-            (PreEscaped(format!(r#"
-                document.addEventListener("DOMContentLoaded", () => {{
-                    const source = new EventSource("{0}", {{ withCredentials: true }});
-
-                    source.onmessage = (event) => {{
-                        if (event.data === "ready") {{
-                            window.location.href = "{1}"
-                        }}
-                    }};
-                }});
-            "#, format!("{}/waiting", authn_url), service.redirect_url)))
-        }
+use super::layout;
+use crate::{session::AuthAction, utils::auth_request::AuthRequest};
+
+pub(crate) fn view(
+    service: &Service,
+    authn_url: &str,
+    action: &AuthAction,
+    request: &AuthRequest,
+) -> Markup {
+    let script =
+        layout::wait_for_redirect(service, authn_url) + &refresh_request(service, authn_url);
 
-        title { (service.name) " | Mist" }
+    layout::page(
+        service,
+        Some(script),
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" {
+                @match action {
+                    AuthAction::Link => "Scan to link a wallet to ",
+                    AuthAction::Recover => "Scan with your new wallet to recover your account on ",
+                    _ => "Scan to sign in to ",
+                }
+                span class="font-semibold" { (service.name) }
+            }
 
-        body class="bg-gradient-to-t from-slate-200 to-slate-100";
+            img id="qr" class="shadow-md" src={"data:image/png;base64," (request.qr)} data-action=(action.name());
 
-        main class="flex flex-col items-center justify-center h-screen p-12" {
-            h1 class="mb-4 text-2xl text-slate-700" { "Scan to sign in to " span class="font-semibold" { (service.name) } }
+            p id="countdown" class="mt-2 text-sm text-slate-500" data-expires-in=(request.expires_in) {}
 
-            img class="shadow-md" src={"data:image/png;base64," (qr)};
+            (layout::status())
 
-            p class="mt-4 text-slate-500" { "Your identity, your data —"
-                span class="text-slate-700" { " Anchored in Mist" }
-            }
+            // On a phone there's nothing to scan, so let the wallet on the same device
+            // pick up the request directly.
+            a id="wallet-link" class="mt-6 btn btn-neutral" href=(request.url) { "Open in wallet" }
 
-            a class="mt-4 text-black opacity-10 hover:opacity-100 transition ease-in-out hover:-translate-y-1 hover:scale-110 duration-150 tooltip tooltip-bottom"
-                href="https://mist.id"
-                target="_blank"
-                data-tip="Ready to own your identity?"
-            {
-                svg class="w-12 h-12" {
-                    path d="M7.055 25.445c0 9.93 8.015 17.946 17.945 17.946 9.852 0 17.867-8.016 17.945-17.868a4.486 4.486 0 0 1-4.508 4.352c-2.44 0-4.503-1.984-4.503-4.508v-5.32c0-7.406-6.032-13.438-13.442-13.438-7.406 0-13.437 6.032-13.437 13.438Zm6.715-7.843a2.855 2.855 0 0 1 2.855 2.859v4c0 1.578-1.29 2.86-2.855 2.86a2.86 2.86 0 0 1-2.856-2.86v-4a2.86 2.86 0 0 1 2.856-2.86Zm8.37 0A2.856 2.856 0 0 1 25 20.46v4a2.868 2.868 0 0 1-2.86 2.86 2.86 2.86 0 0 1-2.85-2.86v-4a2.86 2.86 0 0 1 2.85-2.86Zm0 0";
-                }
+            @if let Some(rest) = request.url.split_once("://").map(|(_, rest)| rest) {
+                a id="openid4vp-link" class="mt-2 text-sm link text-slate-500" href={ "openid4vp://" (rest) } { "Using an OpenID4VP wallet?" }
             }
-        }
-    }
+        },
+    )
+}
+
+/// Counts down until the request expires, then swaps in a fresh one. Also swaps one
+/// in when the session switches from signing in to signing up, or goes to the consent
+/// page first if the service asks for that.
+fn refresh_request(service: &Service, authn_url: &str) -> String {
+    format!(
+        r#"
+            document.addEventListener("DOMContentLoaded", () => {{
+                const countdown = document.getElementById("countdown");
+                let remaining = Number(countdown.dataset.expiresIn);
+
+                const tick = async () => {{
+                    if (remaining > 0) {{
+                        countdown.textContent = `Code expires in ${{remaining--}}s`;
+                        return;
+                    }}
+
+                    clearInterval(timer);
+                    countdown.textContent = "Getting a new code…";
+
+                    const response = await fetch("{0}/refresh?service_id={1}", {{ method: "POST", credentials: "include" }});
+
+                    // Switching over to signing up, for a service that asks first.
+                    if (response.status === 403) {{
+                        window.location.href = "{0}/{2}/consent";
+                        return;
+                    }}
+
+                    // Out of refreshes, or the user has already scanned. Either way, the
+                    // status below says what's going on.
+                    if (!response.ok) {{
+                        countdown.textContent = "";
+                        return;
+                    }}
+
+                    const request = await response.json();
+
+                    document.getElementById("qr").src = `data:image/png;base64,${{request.qr}}`;
+                    document.getElementById("wallet-link").href = request.url;
+
+                    const openid4vp = document.getElementById("openid4vp-link");
+
+                    if (openid4vp) {{
+                        openid4vp.href = request.url.replace(/^[^:]+:\/\//, "openid4vp://");
+                    }}
+
+                    remaining = request.expires_in;
+                    timer = setInterval(tick, 1000);
+                }};
+
+                let timer = setInterval(tick, 1000);
+
+                tick();
+
+                // Signing in with a wallet we don't know can switch the session over to
+                // signing up, which needs a new request.
+                document.addEventListener("mist:progress", ({{ detail }}) => {{
+                    const qr = document.getElementById("qr");
+
+                    if (detail.status === "waiting" && detail.action !== qr.dataset.action) {{
+                        qr.dataset.action = detail.action;
+                        document.getElementById("status").textContent = "No account found, scan again to sign up";
+
+                        remaining = 0;
+                        tick();
+                    }}
+                }});
+            }});
+        "#,
+        authn_url, service.id, service.name
+    )
 }
@@ -0,0 +1,18 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+
+pub(crate) fn view(service: &Service, authn_url: &str) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Sign in to " span class="font-semibold" { (service.name) } " first" }
+
+            p class="text-slate-500" { "Then scan the credential offer again to add it to your wallet." }
+
+            a class="mt-6 btn btn-neutral" href={ (authn_url) "/" (service.name) "/in" } { "Sign in" }
+        },
+    )
+}
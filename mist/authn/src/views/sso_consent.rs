@@ -0,0 +1,37 @@
+use maud::{html, Markup};
+use mist_db::models::service::Service;
+
+use super::layout;
+use crate::session::ReturnTo;
+
+pub(crate) fn view(
+    service: &Service,
+    sibling: &Service,
+    authn_url: &str,
+    return_to: &ReturnTo,
+) -> Markup {
+    layout::page(
+        service,
+        None,
+        html! {
+            h1 class="mb-4 text-2xl text-slate-700" { "Continue to " span class="font-semibold" { (service.name) } "?" }
+
+            p class="text-slate-500" {
+                "You're signed in to " span class="font-semibold" { (sibling.name) } " with a wallet you also use here, so you can continue without scanning again."
+            }
+
+            form method="POST" action={ (authn_url) "/" (service.name) "/sso" } {
+                @if let Some(redirect_uri) = &return_to.redirect_uri {
+                    input type="hidden" name="redirect_uri" value=(redirect_uri);
+                }
+                @if let Some(state) = &return_to.state {
+                    input type="hidden" name="state" value=(state);
+                }
+
+                button class="mt-6 btn btn-neutral" type="submit" { "Continue" }
+            }
+
+            a class="mt-2 text-sm link text-slate-500" href={ (authn_url) "/" (service.name) "/in?skip_sso=true" } { "Use a different wallet" }
+        },
+    )
+}
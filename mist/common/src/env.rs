@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use secstr::SecVec;
 use serde::{Deserialize, Deserializer};
 
@@ -16,6 +18,15 @@ pub struct Environment {
     pub nats_url: String,
     #[serde(default = "default_resolver_url")]
     pub resolver_url: String,
+    /// How long each auth request (and its QR code) is valid for, in seconds.
+    #[serde(default = "default_auth_request_lifetime")]
+    pub auth_request_lifetime: i64,
+    /// How many times an expired auth request is replaced before giving up.
+    #[serde(default = "default_auth_request_refreshes")]
+    pub auth_request_refreshes: u32,
+    /// Proxies in front of Mist, whose `x-forwarded-for` headers are trusted.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
     #[serde(default)]
     pub development: bool,
 }
@@ -48,6 +59,14 @@ fn default_resolver_url() -> String {
     "http://localhost:9050/1.0/identifiers".into()
 }
 
+fn default_auth_request_lifetime() -> i64 {
+    60
+}
+
+fn default_auth_request_refreshes() -> u32 {
+    5
+}
+
 impl Default for Environment {
     fn default() -> Self {
         Self {
@@ -58,6 +77,9 @@ impl Default for Environment {
             redis_url: Default::default(),
             nats_url: Default::default(),
             resolver_url: Default::default(),
+            auth_request_lifetime: Default::default(),
+            auth_request_refreshes: Default::default(),
+            trusted_proxies: Default::default(),
             development: Default::default(),
         }
     }
@@ -15,13 +15,53 @@ where
     }
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> axum::response::Response {
-        let status = if matches!(
+impl Error {
+    /// Whether this came from a query that didn't find anything.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
             self.0.downcast_ref::<sqlx::Error>(),
             Some(sqlx::Error::RowNotFound)
-        ) {
+        ) || self.0.downcast_ref::<NotFound>().is_some()
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// For lookups outside Postgres that didn't find anything, like sessions in Redis.
+#[derive(Debug)]
+pub struct NotFound(pub &'static str);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// For requests that clash with the current state of things, like removing a user's
+/// last identifier.
+#[derive(Debug)]
+pub struct Conflict(pub &'static str);
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> axum::response::Response {
+        let status = if self.is_not_found() {
             StatusCode::NOT_FOUND
+        } else if self.0.downcast_ref::<Conflict>().is_some() {
+            StatusCode::CONFLICT
         } else {
             StatusCode::INTERNAL_SERVER_ERROR
         };
@@ -2,7 +2,17 @@ use fred::prelude::*;
 use serde::{de::DeserializeOwned, Serialize};
 use std::marker::PhantomData;
 
-use crate::error::Result;
+use crate::error::{NotFound, Result};
+
+const COMPARE_AND_SET: &str = r#"
+if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+    return 0
+end
+
+redis.call('SET', KEYS[1], ARGV[2], unpack(ARGV, 3))
+
+return 1
+"#;
 
 pub struct TypedRedis<T> {
     prefix: &'static str,
@@ -21,8 +31,11 @@ impl<T: Serialize + DeserializeOwned> TypedRedis<T> {
         format!("{}-{}", self.prefix, id)
     }
 
+    /// Get the value, or a `NotFound` error if nothing is stored under the id.
     pub async fn get(&self, redis: &RedisClient, id: &str) -> Result<T> {
-        let fetched = redis.get::<String, _>(self.key(id)).await?;
+        let Some(fetched) = redis.get::<Option<String>, _>(self.key(id)).await? else {
+            return Err(NotFound("value not found").into());
+        };
 
         Ok(serde_json::from_str(&fetched)?)
     }
@@ -35,7 +48,7 @@ impl<T: Serialize + DeserializeOwned> TypedRedis<T> {
         expiration: Expiration,
     ) -> Result<()> {
         redis
-            .set(
+            .set::<(), _, _>(
                 self.key(id),
                 serde_json::to_string(data)?,
                 Some(expiration),
@@ -47,8 +60,77 @@ impl<T: Serialize + DeserializeOwned> TypedRedis<T> {
         Ok(())
     }
 
+    /// Set the value only if nothing is stored under the id yet, returning whether it was set.
+    pub async fn set_nx(
+        &self,
+        redis: &RedisClient,
+        id: &str,
+        data: &T,
+        expiration: Expiration,
+    ) -> Result<bool> {
+        let set = redis
+            .set::<Option<String>, _, _>(
+                self.key(id),
+                serde_json::to_string(data)?,
+                Some(expiration),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await?;
+
+        Ok(set.is_some())
+    }
+
+    /// Replace the value, but only if it's still `current`, returning whether it was replaced.
+    ///
+    /// The check and the write happen in a single script, so concurrent writers can't
+    /// both win.
+    pub async fn compare_and_set(
+        &self,
+        redis: &RedisClient,
+        id: &str,
+        current: &T,
+        data: &T,
+        expiration: Expiration,
+    ) -> Result<bool> {
+        let mut args = vec![
+            serde_json::to_string(current)?,
+            serde_json::to_string(data)?,
+        ];
+
+        args.extend(match expiration {
+            Expiration::EX(seconds) => vec!["EX".into(), seconds.to_string()],
+            Expiration::PX(millis) => vec!["PX".into(), millis.to_string()],
+            Expiration::EXAT(seconds) => vec!["EXAT".into(), seconds.to_string()],
+            Expiration::PXAT(millis) => vec!["PXAT".into(), millis.to_string()],
+            Expiration::KEEPTTL => vec!["KEEPTTL".into()],
+        });
+
+        let replaced = redis
+            .eval::<i64, _, _, _>(COMPARE_AND_SET, vec![self.key(id)], args)
+            .await?;
+
+        Ok(replaced == 1)
+    }
+
+    /// Have the value expire at a unix timestamp, returning whether there was one to expire.
+    pub async fn expire_at(&self, redis: &RedisClient, id: &str, timestamp: i64) -> Result<bool> {
+        let expired = redis.expire_at::<i64, _>(self.key(id), timestamp).await?;
+
+        Ok(expired == 1)
+    }
+
+    /// Get the value and remove it in one go, so only one caller ever gets it.
+    pub async fn take(&self, redis: &RedisClient, id: &str) -> Result<Option<T>> {
+        let taken = redis.getdel::<Option<String>, _>(self.key(id)).await?;
+
+        Ok(taken
+            .map(|taken| serde_json::from_str(&taken))
+            .transpose()?)
+    }
+
     pub async fn del(&self, redis: &RedisClient, id: &str) -> Result<()> {
-        redis.del(self.key(id)).await?;
+        redis.del::<(), _>(self.key(id)).await?;
 
         Ok(())
     }
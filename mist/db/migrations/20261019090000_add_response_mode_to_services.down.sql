@@ -0,0 +1,3 @@
+-- Add down migration script here
+alter table services drop column if exists response_mode;
+drop type if exists response_mode;
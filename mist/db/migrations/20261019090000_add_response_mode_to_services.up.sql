@@ -0,0 +1,4 @@
+-- Add up migration script here
+create type response_mode as enum ('post', 'direct_post.jwt');
+
+alter table services add column response_mode response_mode not null default 'post';
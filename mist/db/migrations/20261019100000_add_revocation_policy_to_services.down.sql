@@ -0,0 +1,3 @@
+-- Add down migration script here
+alter table services drop column if exists revocation_policy;
+drop type if exists revocation_policy;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+create type revocation_policy as enum ('fail_open', 'fail_closed');
+
+alter table services add column revocation_policy revocation_policy not null default 'fail_closed';
@@ -0,0 +1,4 @@
+-- Add down migration script here
+drop trigger if exists set_updated_at on trusted_issuers;
+drop table if exists trusted_issuers;
+drop type if exists issuer_kind;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+create type issuer_kind as enum ('did', 'did_prefix', 'x509');
+
+create table trusted_issuers (
+    id uuid primary key default uuid_generate_v4(),
+    kind issuer_kind not null,
+    value text not null,
+    service_id uuid references services(id) on delete cascade,
+    definition_id uuid references definitions(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+
+    -- Definitions belong to a service, so issuers scoped to one must be too.
+    check (definition_id is null or service_id is not null)
+);
+
+create index trusted_issuers_service_id_idx on trusted_issuers (service_id);
+
+create or replace trigger set_updated_at before update on trusted_issuers
+for each row execute function set_updated_at();
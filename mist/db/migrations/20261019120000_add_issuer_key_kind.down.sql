@@ -0,0 +1,7 @@
+-- Add down migration script here
+delete from keys where kind = 'issuer';
+
+alter type key_kind rename to key_kind_old;
+create type key_kind as enum ('api', 'token');
+alter table keys alter column kind type key_kind using kind::text::key_kind;
+drop type key_kind_old;
@@ -0,0 +1,2 @@
+-- Add up migration script here
+alter type key_kind add value if not exists 'issuer';
@@ -0,0 +1,3 @@
+-- Add down migration script here
+drop trigger if exists set_updated_at on credential_configurations;
+drop table if exists credential_configurations;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+create table credential_configurations (
+    id uuid primary key default uuid_generate_v4(),
+    name text not null,
+    display_name text not null,
+    claims jsonb not null,
+    valid_for int,
+    service_id uuid not null references services(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+
+    unique (service_id, name)
+);
+
+create or replace trigger set_updated_at before update on credential_configurations
+for each row execute function set_updated_at();
@@ -0,0 +1,4 @@
+-- Add down migration script here
+drop trigger if exists set_updated_at on credential_offers;
+drop table if exists credential_offers;
+drop type if exists offer_grant;
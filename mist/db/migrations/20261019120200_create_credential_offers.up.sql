@@ -0,0 +1,22 @@
+-- Add up migration script here
+create type offer_grant as enum ('pre_authorized_code', 'authorization_code');
+
+create table credential_offers (
+    id uuid primary key default uuid_generate_v4(),
+    grant_type offer_grant not null,
+    claims jsonb not null,
+    pre_authorized_code text unique,
+    expires_at timestamptz not null,
+    redeemed_at timestamptz,
+    configuration_id uuid not null references credential_configurations(id) on delete cascade,
+    user_id uuid references users(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+
+    -- The authorization code flow is how we know who's redeeming the offer.
+    check (grant_type = 'pre_authorized_code' or user_id is not null)
+);
+
+create or replace trigger set_updated_at before update on credential_offers
+for each row execute function set_updated_at();
@@ -0,0 +1,4 @@
+-- Add up migration script here
+create type webhook_mode as enum ('async', 'sync');
+
+alter table services add column webhook_mode webhook_mode not null default 'async';
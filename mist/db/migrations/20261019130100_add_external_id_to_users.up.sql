@@ -0,0 +1,2 @@
+-- Add up migration script here
+alter table users add column external_id text;
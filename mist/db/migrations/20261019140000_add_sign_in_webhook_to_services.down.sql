@@ -0,0 +1,2 @@
+-- Add down migration script here
+alter table services drop column if exists sign_in_webhook;
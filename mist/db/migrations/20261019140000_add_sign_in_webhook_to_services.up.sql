@@ -0,0 +1,2 @@
+-- Add up migration script here
+alter table services add column sign_in_webhook webhook_mode;
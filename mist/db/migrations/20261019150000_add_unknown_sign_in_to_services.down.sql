@@ -0,0 +1,3 @@
+-- Add down migration script here
+alter table services drop column if exists unknown_sign_in;
+drop type if exists unknown_sign_in;
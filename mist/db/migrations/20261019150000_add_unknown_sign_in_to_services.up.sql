@@ -0,0 +1,4 @@
+-- Add up migration script here
+create type unknown_sign_in as enum ('reject', 'register');
+
+alter table services add column unknown_sign_in unknown_sign_in not null default 'reject';
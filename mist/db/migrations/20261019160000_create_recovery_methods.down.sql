@@ -0,0 +1,4 @@
+-- Add down migration script here
+drop table if exists recovery_methods;
+drop type if exists recovery_kind;
+alter table services drop column if exists recovery_claim;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+alter table services add column recovery_claim text;
+
+create type recovery_kind as enum ('credential', 'did', 'approval');
+
+create table recovery_methods (
+    id uuid primary key default uuid_generate_v4(),
+    kind recovery_kind not null,
+    value text not null,
+    expires_at timestamptz,
+    service_id uuid not null references services(id) on delete cascade,
+    user_id uuid not null references users(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+
+    unique (service_id, kind, value)
+);
+
+create or replace trigger set_updated_at before update on recovery_methods
+for each row execute function set_updated_at();
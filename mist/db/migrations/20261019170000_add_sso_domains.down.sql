@@ -0,0 +1,3 @@
+-- Add down migration script here
+drop table if exists sso_consents;
+alter table services drop column if exists sso_domain;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+alter table services add column sso_domain text;
+
+create table sso_consents (
+    user_id uuid not null references users(id) on delete cascade,
+    service_id uuid not null references services(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+
+    primary key (user_id, service_id)
+);
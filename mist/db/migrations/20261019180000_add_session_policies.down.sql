@@ -0,0 +1,4 @@
+-- Add down migration script here
+alter table services drop column if exists token_flow;
+alter table services drop column if exists session_lifetime;
+alter table services drop column if exists session_idle_timeout;
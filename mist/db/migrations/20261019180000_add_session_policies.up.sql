@@ -0,0 +1,4 @@
+-- Add up migration script here
+alter table services add column session_idle_timeout bigint;
+alter table services add column session_lifetime bigint not null default 28800;
+alter table services add column token_flow boolean not null default false;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+alter table services drop column if exists frontchannel_logout_uri;
+alter table services drop column if exists backchannel_logout_uri;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+alter table services add column backchannel_logout_uri text;
+alter table services add column frontchannel_logout_uri text;
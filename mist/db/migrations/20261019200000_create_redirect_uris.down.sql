@@ -0,0 +1,3 @@
+-- Add down migration script here
+drop table if exists redirect_uris;
+drop type if exists redirect_uri_kind;
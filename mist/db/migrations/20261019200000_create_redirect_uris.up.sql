@@ -0,0 +1,17 @@
+-- Add up migration script here
+create type redirect_uri_kind as enum ('redirect', 'post_logout');
+
+create table redirect_uris (
+    id uuid primary key default uuid_generate_v4(),
+    kind redirect_uri_kind not null,
+    uri text not null,
+    service_id uuid not null references services(id) on delete cascade,
+
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+
+    unique (service_id, kind, uri)
+);
+
+create or replace trigger set_updated_at before update on redirect_uris
+for each row execute function set_updated_at();
@@ -0,0 +1,5 @@
+-- Add down migration script here
+alter table services drop column if exists subject_salt;
+alter table services drop column if exists subject_type;
+
+drop type if exists subject_type;
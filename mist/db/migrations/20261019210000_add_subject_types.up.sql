@@ -0,0 +1,5 @@
+-- Add up migration script here
+create type subject_type as enum ('public', 'pairwise');
+
+alter table services add column subject_type subject_type not null default 'public';
+alter table services add column subject_salt uuid not null default uuid_generate_v4();
@@ -0,0 +1,6 @@
+-- Add down migration script here
+drop table if exists sign_up_consents;
+
+alter table services drop column if exists terms_url;
+alter table services drop column if exists privacy_policy_url;
+alter table services drop column if exists consent_purpose;
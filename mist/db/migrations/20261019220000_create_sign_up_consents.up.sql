@@ -0,0 +1,22 @@
+-- Add up migration script here
+alter table services add column consent_purpose text;
+alter table services add column privacy_policy_url text;
+alter table services add column terms_url text;
+
+create table sign_up_consents (
+    id uuid primary key default uuid_generate_v4(),
+    -- Not a reference, as the user doesn't exist until they've finished signing up.
+    user_id uuid not null,
+    session_id uuid not null,
+    service_id uuid not null references services(id) on delete cascade,
+    fields text[] not null,
+    purpose text not null,
+    privacy_policy_url text,
+    terms_url text,
+    ip text,
+    user_agent text,
+
+    created_at timestamptz not null default now()
+);
+
+create index sign_up_consents_user_id_idx on sign_up_consents (user_id);
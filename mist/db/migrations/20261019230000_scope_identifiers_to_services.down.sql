@@ -0,0 +1,13 @@
+-- Add down migration script here
+alter table identifiers drop constraint identifiers_service_id_value_key;
+
+-- A DID can only belong to one user again. Where several services registered the same
+-- wallet, only the earliest identifier is kept, so this can't be fully reverted.
+delete from identifiers
+  using identifiers earlier
+  where earlier.value = identifiers.value
+    and (earlier.created_at, earlier.id) < (identifiers.created_at, identifiers.id);
+
+alter table identifiers add constraint identifiers_value_key unique (value);
+
+alter table identifiers drop column service_id;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+alter table identifiers add column service_id uuid references services(id) on delete cascade;
+
+update identifiers set service_id = users.service_id
+  from users
+  where users.id = identifiers.user_id;
+
+alter table identifiers alter column service_id set not null;
+
+-- The same wallet can be a different user at each service.
+alter table identifiers drop constraint identifiers_value_key;
+alter table identifiers add constraint identifiers_service_id_value_key unique (service_id, value);
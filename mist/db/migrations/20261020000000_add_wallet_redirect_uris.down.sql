@@ -0,0 +1,7 @@
+-- Add down migration script here
+delete from redirect_uris where kind = 'wallet';
+
+alter type redirect_uri_kind rename to redirect_uri_kind_old;
+create type redirect_uri_kind as enum ('redirect', 'post_logout');
+alter table redirect_uris alter column kind type redirect_uri_kind using kind::text::redirect_uri_kind;
+drop type redirect_uri_kind_old;
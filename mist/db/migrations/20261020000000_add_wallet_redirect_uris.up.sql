@@ -0,0 +1,2 @@
+-- Add up migration script here
+alter type redirect_uri_kind add value if not exists 'wallet';
@@ -30,18 +30,130 @@ CREATE EXTENSION IF NOT EXISTS "uuid-ossp" WITH SCHEMA public;
 COMMENT ON EXTENSION "uuid-ossp" IS 'generate universally unique identifiers (UUIDs)';
 
 
+--
+-- Name: issuer_kind; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.issuer_kind AS ENUM (
+    'did',
+    'did_prefix',
+    'x509'
+);
+
+
+ALTER TYPE public.issuer_kind OWNER TO casper;
+
 --
 -- Name: key_kind; Type: TYPE; Schema: public; Owner: casper
 --
 
 CREATE TYPE public.key_kind AS ENUM (
     'api',
-    'token'
+    'token',
+    'issuer'
 );
 
 
 ALTER TYPE public.key_kind OWNER TO casper;
 
+--
+-- Name: offer_grant; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.offer_grant AS ENUM (
+    'pre_authorized_code',
+    'authorization_code'
+);
+
+
+ALTER TYPE public.offer_grant OWNER TO casper;
+
+--
+-- Name: recovery_kind; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.recovery_kind AS ENUM (
+    'credential',
+    'did',
+    'approval'
+);
+
+
+ALTER TYPE public.recovery_kind OWNER TO casper;
+
+--
+-- Name: redirect_uri_kind; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.redirect_uri_kind AS ENUM (
+    'redirect',
+    'post_logout',
+    'wallet'
+);
+
+
+ALTER TYPE public.redirect_uri_kind OWNER TO casper;
+
+--
+-- Name: response_mode; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.response_mode AS ENUM (
+    'post',
+    'direct_post.jwt'
+);
+
+
+ALTER TYPE public.response_mode OWNER TO casper;
+
+--
+-- Name: revocation_policy; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.revocation_policy AS ENUM (
+    'fail_open',
+    'fail_closed'
+);
+
+
+ALTER TYPE public.revocation_policy OWNER TO casper;
+
+--
+-- Name: subject_type; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.subject_type AS ENUM (
+    'public',
+    'pairwise'
+);
+
+
+ALTER TYPE public.subject_type OWNER TO casper;
+
+--
+-- Name: unknown_sign_in; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.unknown_sign_in AS ENUM (
+    'reject',
+    'register'
+);
+
+
+ALTER TYPE public.unknown_sign_in OWNER TO casper;
+
+--
+-- Name: webhook_mode; Type: TYPE; Schema: public; Owner: casper
+--
+
+CREATE TYPE public.webhook_mode AS ENUM (
+    'async',
+    'sync'
+);
+
+
+ALTER TYPE public.webhook_mode OWNER TO casper;
+
 --
 -- Name: set_updated_at(); Type: FUNCTION; Schema: public; Owner: casper
 --
@@ -76,6 +188,45 @@ CREATE TABLE public._sqlx_migrations (
 
 ALTER TABLE public._sqlx_migrations OWNER TO casper;
 
+--
+-- Name: credential_configurations; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.credential_configurations (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    name text NOT NULL,
+    display_name text NOT NULL,
+    claims jsonb NOT NULL,
+    valid_for integer,
+    service_id uuid NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.credential_configurations OWNER TO casper;
+
+--
+-- Name: credential_offers; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.credential_offers (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    grant_type public.offer_grant NOT NULL,
+    claims jsonb NOT NULL,
+    pre_authorized_code text,
+    expires_at timestamp with time zone NOT NULL,
+    redeemed_at timestamp with time zone,
+    configuration_id uuid NOT NULL,
+    user_id uuid,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    CONSTRAINT credential_offers_check CHECK (((grant_type = 'pre_authorized_code'::public.offer_grant) OR (user_id IS NOT NULL)))
+);
+
+
+ALTER TABLE public.credential_offers OWNER TO casper;
+
 --
 -- Name: definitions; Type: TABLE; Schema: public; Owner: casper
 --
@@ -102,7 +253,8 @@ CREATE TABLE public.identifiers (
     value text NOT NULL,
     user_id uuid NOT NULL,
     created_at timestamp with time zone DEFAULT now() NOT NULL,
-    updated_at timestamp with time zone DEFAULT now() NOT NULL
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    service_id uuid NOT NULL
 );
 
 
@@ -126,6 +278,40 @@ CREATE TABLE public.keys (
 
 ALTER TABLE public.keys OWNER TO casper;
 
+--
+-- Name: recovery_methods; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.recovery_methods (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    kind public.recovery_kind NOT NULL,
+    value text NOT NULL,
+    expires_at timestamp with time zone,
+    service_id uuid NOT NULL,
+    user_id uuid NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.recovery_methods OWNER TO casper;
+
+--
+-- Name: redirect_uris; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.redirect_uris (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    kind public.redirect_uri_kind NOT NULL,
+    uri text NOT NULL,
+    service_id uuid NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.redirect_uris OWNER TO casper;
+
 --
 -- Name: services; Type: TABLE; Schema: public; Owner: casper
 --
@@ -137,12 +323,81 @@ CREATE TABLE public.services (
     logout_url text NOT NULL,
     webhook_url text NOT NULL,
     created_at timestamp with time zone DEFAULT now() NOT NULL,
-    updated_at timestamp with time zone DEFAULT now() NOT NULL
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    response_mode public.response_mode DEFAULT 'post'::public.response_mode NOT NULL,
+    revocation_policy public.revocation_policy DEFAULT 'fail_closed'::public.revocation_policy NOT NULL,
+    webhook_mode public.webhook_mode DEFAULT 'async'::public.webhook_mode NOT NULL,
+    sign_in_webhook public.webhook_mode,
+    unknown_sign_in public.unknown_sign_in DEFAULT 'reject'::public.unknown_sign_in NOT NULL,
+    recovery_claim text,
+    sso_domain text,
+    session_idle_timeout bigint,
+    session_lifetime bigint DEFAULT 28800 NOT NULL,
+    token_flow boolean DEFAULT false NOT NULL,
+    backchannel_logout_uri text,
+    frontchannel_logout_uri text,
+    subject_type public.subject_type DEFAULT 'public'::public.subject_type NOT NULL,
+    subject_salt uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    consent_purpose text,
+    privacy_policy_url text,
+    terms_url text
 );
 
 
 ALTER TABLE public.services OWNER TO casper;
 
+--
+-- Name: sign_up_consents; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.sign_up_consents (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    user_id uuid NOT NULL,
+    session_id uuid NOT NULL,
+    service_id uuid NOT NULL,
+    fields text[] NOT NULL,
+    purpose text NOT NULL,
+    privacy_policy_url text,
+    terms_url text,
+    ip text,
+    user_agent text,
+    created_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.sign_up_consents OWNER TO casper;
+
+--
+-- Name: sso_consents; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.sso_consents (
+    user_id uuid NOT NULL,
+    service_id uuid NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.sso_consents OWNER TO casper;
+
+--
+-- Name: trusted_issuers; Type: TABLE; Schema: public; Owner: casper
+--
+
+CREATE TABLE public.trusted_issuers (
+    id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
+    kind public.issuer_kind NOT NULL,
+    value text NOT NULL,
+    service_id uuid,
+    definition_id uuid,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    CONSTRAINT trusted_issuers_check CHECK (((definition_id IS NULL) OR (service_id IS NOT NULL)))
+);
+
+
+ALTER TABLE public.trusted_issuers OWNER TO casper;
+
 --
 -- Name: users; Type: TABLE; Schema: public; Owner: casper
 --
@@ -151,7 +406,8 @@ CREATE TABLE public.users (
     id uuid DEFAULT public.uuid_generate_v4() NOT NULL,
     service_id uuid NOT NULL,
     created_at timestamp with time zone DEFAULT now() NOT NULL,
-    updated_at timestamp with time zone DEFAULT now() NOT NULL
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    external_id text
 );
 
 
@@ -165,6 +421,38 @@ ALTER TABLE ONLY public._sqlx_migrations
     ADD CONSTRAINT _sqlx_migrations_pkey PRIMARY KEY (version);
 
 
+--
+-- Name: credential_configurations credential_configurations_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_configurations
+    ADD CONSTRAINT credential_configurations_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: credential_configurations credential_configurations_service_id_name_key; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_configurations
+    ADD CONSTRAINT credential_configurations_service_id_name_key UNIQUE (service_id, name);
+
+
+--
+-- Name: credential_offers credential_offers_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_offers
+    ADD CONSTRAINT credential_offers_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: credential_offers credential_offers_pre_authorized_code_key; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_offers
+    ADD CONSTRAINT credential_offers_pre_authorized_code_key UNIQUE (pre_authorized_code);
+
+
 --
 -- Name: definitions definitions_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
 --
@@ -190,11 +478,11 @@ ALTER TABLE ONLY public.identifiers
 
 
 --
--- Name: identifiers identifiers_value_key; Type: CONSTRAINT; Schema: public; Owner: casper
+-- Name: identifiers identifiers_service_id_value_key; Type: CONSTRAINT; Schema: public; Owner: casper
 --
 
 ALTER TABLE ONLY public.identifiers
-    ADD CONSTRAINT identifiers_value_key UNIQUE (value);
+    ADD CONSTRAINT identifiers_service_id_value_key UNIQUE (service_id, value);
 
 
 --
@@ -213,6 +501,38 @@ ALTER TABLE ONLY public.keys
     ADD CONSTRAINT keys_service_id_kind_priority_key UNIQUE (service_id, kind, priority);
 
 
+--
+-- Name: recovery_methods recovery_methods_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.recovery_methods
+    ADD CONSTRAINT recovery_methods_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: recovery_methods recovery_methods_service_id_kind_value_key; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.recovery_methods
+    ADD CONSTRAINT recovery_methods_service_id_kind_value_key UNIQUE (service_id, kind, value);
+
+
+--
+-- Name: redirect_uris redirect_uris_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.redirect_uris
+    ADD CONSTRAINT redirect_uris_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: redirect_uris redirect_uris_service_id_kind_uri_key; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.redirect_uris
+    ADD CONSTRAINT redirect_uris_service_id_kind_uri_key UNIQUE (service_id, kind, uri);
+
+
 --
 -- Name: services services_name_key; Type: CONSTRAINT; Schema: public; Owner: casper
 --
@@ -229,6 +549,30 @@ ALTER TABLE ONLY public.services
     ADD CONSTRAINT services_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: sign_up_consents sign_up_consents_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.sign_up_consents
+    ADD CONSTRAINT sign_up_consents_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: sso_consents sso_consents_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.sso_consents
+    ADD CONSTRAINT sso_consents_pkey PRIMARY KEY (user_id, service_id);
+
+
+--
+-- Name: trusted_issuers trusted_issuers_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.trusted_issuers
+    ADD CONSTRAINT trusted_issuers_pkey PRIMARY KEY (id);
+
+
 --
 -- Name: users users_pkey; Type: CONSTRAINT; Schema: public; Owner: casper
 --
@@ -237,6 +581,34 @@ ALTER TABLE ONLY public.users
     ADD CONSTRAINT users_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: sign_up_consents_user_id_idx; Type: INDEX; Schema: public; Owner: casper
+--
+
+CREATE INDEX sign_up_consents_user_id_idx ON public.sign_up_consents USING btree (user_id);
+
+
+--
+-- Name: trusted_issuers_service_id_idx; Type: INDEX; Schema: public; Owner: casper
+--
+
+CREATE INDEX trusted_issuers_service_id_idx ON public.trusted_issuers USING btree (service_id);
+
+
+--
+-- Name: credential_configurations set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
+--
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.credential_configurations FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
+
+
+--
+-- Name: credential_offers set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
+--
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.credential_offers FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
+
+
 --
 -- Name: definitions set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
 --
@@ -258,6 +630,20 @@ CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.identifiers FOR EACH ROW E
 CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.keys FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
 
 
+--
+-- Name: recovery_methods set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
+--
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.recovery_methods FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
+
+
+--
+-- Name: redirect_uris set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
+--
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.redirect_uris FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
+
+
 --
 -- Name: services set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
 --
@@ -265,6 +651,13 @@ CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.keys FOR EACH ROW EXECUTE
 CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.services FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
 
 
+--
+-- Name: trusted_issuers set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
+--
+
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.trusted_issuers FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
+
+
 --
 -- Name: users set_updated_at; Type: TRIGGER; Schema: public; Owner: casper
 --
@@ -272,6 +665,30 @@ CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.services FOR EACH ROW EXEC
 CREATE TRIGGER set_updated_at BEFORE UPDATE ON public.users FOR EACH ROW EXECUTE FUNCTION public.set_updated_at();
 
 
+--
+-- Name: credential_configurations credential_configurations_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_configurations
+    ADD CONSTRAINT credential_configurations_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
+--
+-- Name: credential_offers credential_offers_configuration_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_offers
+    ADD CONSTRAINT credential_offers_configuration_id_fkey FOREIGN KEY (configuration_id) REFERENCES public.credential_configurations(id) ON DELETE CASCADE;
+
+
+--
+-- Name: credential_offers credential_offers_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.credential_offers
+    ADD CONSTRAINT credential_offers_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
 --
 -- Name: definitions definitions_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
 --
@@ -280,6 +697,14 @@ ALTER TABLE ONLY public.definitions
     ADD CONSTRAINT definitions_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
 
 
+--
+-- Name: identifiers identifiers_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.identifiers
+    ADD CONSTRAINT identifiers_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
 --
 -- Name: identifiers identifiers_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
 --
@@ -296,6 +721,70 @@ ALTER TABLE ONLY public.keys
     ADD CONSTRAINT keys_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
 
 
+--
+-- Name: recovery_methods recovery_methods_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.recovery_methods
+    ADD CONSTRAINT recovery_methods_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
+--
+-- Name: recovery_methods recovery_methods_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.recovery_methods
+    ADD CONSTRAINT recovery_methods_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: redirect_uris redirect_uris_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.redirect_uris
+    ADD CONSTRAINT redirect_uris_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
+--
+-- Name: sign_up_consents sign_up_consents_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.sign_up_consents
+    ADD CONSTRAINT sign_up_consents_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
+--
+-- Name: sso_consents sso_consents_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.sso_consents
+    ADD CONSTRAINT sso_consents_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
+--
+-- Name: sso_consents sso_consents_user_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.sso_consents
+    ADD CONSTRAINT sso_consents_user_id_fkey FOREIGN KEY (user_id) REFERENCES public.users(id) ON DELETE CASCADE;
+
+
+--
+-- Name: trusted_issuers trusted_issuers_definition_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.trusted_issuers
+    ADD CONSTRAINT trusted_issuers_definition_id_fkey FOREIGN KEY (definition_id) REFERENCES public.definitions(id) ON DELETE CASCADE;
+
+
+--
+-- Name: trusted_issuers trusted_issuers_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
+--
+
+ALTER TABLE ONLY public.trusted_issuers
+    ADD CONSTRAINT trusted_issuers_service_id_fkey FOREIGN KEY (service_id) REFERENCES public.services(id) ON DELETE CASCADE;
+
+
 --
 -- Name: users users_service_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: casper
 --
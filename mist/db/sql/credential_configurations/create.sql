@@ -0,0 +1,3 @@
+insert into credential_configurations (name, display_name, claims, valid_for, service_id)
+  values ($1, $2, $3, $4, $5) returning
+  id, name, display_name, claims as "claims: _", valid_for, service_id, created_at, updated_at;
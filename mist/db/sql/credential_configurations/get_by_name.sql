@@ -0,0 +1,2 @@
+select id, name, display_name, claims as "claims: _", valid_for, service_id, created_at, updated_at
+  from credential_configurations where service_id = $1 and name = $2;
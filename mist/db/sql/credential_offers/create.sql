@@ -0,0 +1,5 @@
+insert into credential_offers
+  (grant_type, claims, pre_authorized_code, expires_at, configuration_id, user_id)
+  values ($1, $2, $3, $4, $5, $6) returning
+  id, grant_type as "grant_type: _", claims as "claims: _", pre_authorized_code, expires_at,
+  redeemed_at, configuration_id, user_id, created_at, updated_at;
@@ -0,0 +1,3 @@
+select id, grant_type as "grant_type: _", claims as "claims: _", pre_authorized_code, expires_at,
+  redeemed_at, configuration_id, user_id, created_at, updated_at
+  from credential_offers where id = $1;
@@ -0,0 +1,4 @@
+update credential_offers set redeemed_at = now()
+  where id = $1 and redeemed_at is null and expires_at > now() returning
+  id, grant_type as "grant_type: _", claims as "claims: _", pre_authorized_code, expires_at,
+  redeemed_at, configuration_id, user_id, created_at, updated_at;
@@ -0,0 +1 @@
+select count(*) as "count!" from identifiers where user_id = $1;
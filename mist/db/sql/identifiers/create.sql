@@ -1 +1,3 @@
-insert into identifiers (user_id, value) values ($1, $2) returning *;
+insert into identifiers (user_id, service_id, value)
+  select users.id, users.service_id, $2 from users where users.id = $1
+  returning *;
@@ -0,0 +1 @@
+delete from identifiers where user_id = $1 and id = $2 returning *;
@@ -1 +0,0 @@
-select * from identifiers where value = $1;
@@ -0,0 +1 @@
+select * from identifiers where service_id = $1 and value = $2;
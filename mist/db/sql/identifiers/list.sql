@@ -0,0 +1,4 @@
+select identifiers.* from identifiers
+  join users on users.id = identifiers.user_id
+  where users.service_id = $1 and identifiers.user_id = $2
+  order by identifiers.created_at;
@@ -0,0 +1 @@
+select id from users where service_id = $1 and id = $2 for update;
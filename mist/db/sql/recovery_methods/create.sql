@@ -0,0 +1,3 @@
+insert into recovery_methods (service_id, user_id, kind, value, expires_at)
+  select service_id, id, $3, $4, $5 from users where service_id = $1 and id = $2 returning
+  id, kind as "kind: _", value, expires_at, service_id, user_id, created_at, updated_at;
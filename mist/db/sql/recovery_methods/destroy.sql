@@ -0,0 +1,2 @@
+delete from recovery_methods where service_id = $1 and user_id = $2 and id = $3 returning
+  id, kind as "kind: _", value, expires_at, service_id, user_id, created_at, updated_at;
@@ -0,0 +1,3 @@
+select id, kind as "kind: _", value, expires_at, service_id, user_id, created_at, updated_at
+  from recovery_methods where service_id = $1 and kind = $2 and value = $3
+  and (expires_at is null or expires_at > now());
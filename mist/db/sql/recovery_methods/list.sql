@@ -0,0 +1,2 @@
+select id, kind as "kind: _", value, expires_at, service_id, user_id, created_at, updated_at
+  from recovery_methods where service_id = $1 and user_id = $2 order by created_at;
@@ -0,0 +1,2 @@
+delete from recovery_methods where id = $1 and (expires_at is null or expires_at > now()) returning
+  id, kind as "kind: _", value, expires_at, service_id, user_id, created_at, updated_at;
@@ -0,0 +1,2 @@
+insert into redirect_uris (service_id, kind, uri) values ($1, $2, $3) returning
+  id, kind as "kind: _", uri, service_id, created_at, updated_at;
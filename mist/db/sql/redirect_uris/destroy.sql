@@ -0,0 +1,2 @@
+delete from redirect_uris where service_id = $1 and id = $2 returning
+  id, kind as "kind: _", uri, service_id, created_at, updated_at;
@@ -0,0 +1,2 @@
+select exists(select 1 from redirect_uris where service_id = $1 and kind = $2 and uri = $3)
+  as "exists!";
@@ -0,0 +1,2 @@
+select id, kind as "kind: _", uri, service_id, created_at, updated_at
+  from redirect_uris where service_id = $1 order by created_at;
@@ -1 +1,13 @@
-insert into services (name, redirect_url, logout_url, webhook_url) values ($1, $2, $3, $4) returning *;
+insert into services (name, redirect_url, logout_url, webhook_url, response_mode, revocation_policy,
+  webhook_mode, sign_in_webhook, unknown_sign_in, recovery_claim, sso_domain, session_idle_timeout,
+  session_lifetime, token_flow, backchannel_logout_uri, frontchannel_logout_uri, subject_type,
+  consent_purpose, privacy_policy_url, terms_url)
+  values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19,
+  $20) returning
+  id, name, redirect_url, logout_url, webhook_url, response_mode as "response_mode: _",
+  revocation_policy as "revocation_policy: _", webhook_mode as "webhook_mode: _",
+  sign_in_webhook as "sign_in_webhook: _",
+  unknown_sign_in as "unknown_sign_in: _", recovery_claim, sso_domain,
+  session_idle_timeout, session_lifetime, token_flow, backchannel_logout_uri,
+  frontchannel_logout_uri, subject_type as "subject_type: _", subject_salt, consent_purpose,
+  privacy_policy_url, terms_url, created_at, updated_at;
@@ -1 +1,8 @@
-delete from services where id = $1 returning *;
+delete from services where id = $1 returning
+  id, name, redirect_url, logout_url, webhook_url, response_mode as "response_mode: _",
+  revocation_policy as "revocation_policy: _", webhook_mode as "webhook_mode: _",
+  sign_in_webhook as "sign_in_webhook: _",
+  unknown_sign_in as "unknown_sign_in: _", recovery_claim, sso_domain,
+  session_idle_timeout, session_lifetime, token_flow, backchannel_logout_uri,
+  frontchannel_logout_uri, subject_type as "subject_type: _", subject_salt, consent_purpose,
+  privacy_policy_url, terms_url, created_at, updated_at;
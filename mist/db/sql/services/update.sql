@@ -1 +1,13 @@
-update services set name = $2, redirect_url = $3, logout_url = $4, webhook_url = $5 where id = $1 returning *;
+update services set name = $2, redirect_url = $3, logout_url = $4, webhook_url = $5, response_mode = $6,
+  revocation_policy = $7, webhook_mode = $8, sign_in_webhook = $9, unknown_sign_in = $10,
+  recovery_claim = $11, sso_domain = $12, session_idle_timeout = $13, session_lifetime = $14,
+  token_flow = $15, backchannel_logout_uri = $16, frontchannel_logout_uri = $17,
+  subject_type = $18, consent_purpose = $19, privacy_policy_url = $20, terms_url = $21
+  where id = $1 returning
+  id, name, redirect_url, logout_url, webhook_url, response_mode as "response_mode: _",
+  revocation_policy as "revocation_policy: _", webhook_mode as "webhook_mode: _",
+  sign_in_webhook as "sign_in_webhook: _",
+  unknown_sign_in as "unknown_sign_in: _", recovery_claim, sso_domain,
+  session_idle_timeout, session_lifetime, token_flow, backchannel_logout_uri,
+  frontchannel_logout_uri, subject_type as "subject_type: _", subject_salt, consent_purpose,
+  privacy_policy_url, terms_url, created_at, updated_at;
@@ -0,0 +1,5 @@
+insert into sign_up_consents (user_id, session_id, service_id, fields, purpose, privacy_policy_url,
+  terms_url, ip, user_agent)
+  values ($1, $2, $3, $4, $5, $6, $7, $8, $9) returning
+  id, user_id, session_id, service_id, fields, purpose, privacy_policy_url, terms_url, ip,
+  user_agent, created_at;
@@ -0,0 +1 @@
+insert into sso_consents (user_id, service_id) values ($1, $2) on conflict do nothing;
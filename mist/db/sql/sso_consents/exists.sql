@@ -0,0 +1 @@
+select exists(select 1 from sso_consents where user_id = $1 and service_id = $2) as "exists!";
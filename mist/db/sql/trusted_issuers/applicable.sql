@@ -0,0 +1,4 @@
+select id, kind as "kind: _", value, service_id, definition_id, created_at, updated_at
+  from trusted_issuers
+  where service_id is null
+     or (service_id = $1 and (definition_id is null or definition_id = $2));
@@ -0,0 +1,2 @@
+insert into trusted_issuers (kind, value, service_id, definition_id) values ($1, $2, $3, $4) returning
+  id, kind as "kind: _", value, service_id, definition_id, created_at, updated_at;
@@ -0,0 +1,2 @@
+delete from trusted_issuers where id = $1 returning
+  id, kind as "kind: _", value, service_id, definition_id, created_at, updated_at;
@@ -0,0 +1,3 @@
+select id, kind as "kind: _", value, service_id, definition_id, created_at, updated_at
+  from trusted_issuers where service_id is not distinct from $1
+  order by created_at asc limit $2 offset $3;
@@ -1 +1 @@
-insert into users (service_id, id) values ($1, $2) returning *;
+insert into users (service_id, id, external_id) values ($1, $2, $3) returning *;
@@ -1,5 +1,12 @@
+pub mod credential_configuration;
+pub mod credential_offer;
 pub mod definition;
 pub mod identifier;
 pub mod key;
+pub mod recovery_method;
+pub mod redirect_uri;
 pub mod service;
+pub mod session;
+pub mod sign_up_consent;
+pub mod trusted_issuer;
 pub mod user;
@@ -0,0 +1,66 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::*, types::Json};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::service::ServiceId;
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
+)]
+pub struct CredentialConfigurationId(pub Uuid);
+
+impl CredentialConfigurationId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// A kind of credential a service issues to its users, e.g. "Member of ACME".
+#[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CredentialConfiguration {
+    pub id: CredentialConfigurationId,
+    /// The credential type, and its configuration ID in the issuer metadata.
+    pub name: String,
+    pub display_name: String,
+    #[schema(value_type = Vec<CredentialClaim>)]
+    pub claims: Json<Vec<CredentialClaim>>,
+    /// How many seconds issued credentials are valid for, if they expire.
+    pub valid_for: Option<i32>,
+    pub service_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CredentialClaim {
+    pub name: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateCredentialConfiguration {
+    #[builder(into)]
+    pub name: String,
+    #[builder(into)]
+    pub display_name: String,
+    pub claims: Vec<CredentialClaim>,
+    pub valid_for: Option<i32>,
+    pub service_id: ServiceId,
+}
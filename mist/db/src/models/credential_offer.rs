@@ -0,0 +1,71 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{prelude::*, types::Json};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{credential_configuration::CredentialConfigurationId, user::UserId};
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
+)]
+pub struct CredentialOfferId(pub Uuid);
+
+impl CredentialOfferId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// A credential waiting to be picked up by a user's wallet.
+#[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CredentialOffer {
+    pub id: CredentialOfferId,
+    pub grant_type: OfferGrant,
+    #[schema(value_type = Object)]
+    pub claims: Json<Map<String, Value>>,
+    #[serde(skip_serializing)]
+    pub pre_authorized_code: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub configuration_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How the wallet gets an access token for the offer.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "offer_grant", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum OfferGrant {
+    /// Anyone holding the offer can redeem it.
+    #[default]
+    PreAuthorizedCode,
+    /// The user has to sign in with Mist first, and only the offer's user can redeem it.
+    AuthorizationCode,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateCredentialOffer {
+    pub grant_type: OfferGrant,
+    pub claims: Map<String, Value>,
+    pub pre_authorized_code: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub configuration_id: CredentialConfigurationId,
+    pub user_id: Option<UserId>,
+}
@@ -3,12 +3,24 @@ use chrono::{DateTime, Utc};
 use derive_more::{AsRef, Display, From, Into};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::*;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::user::UserId;
+use super::{service::ServiceId, user::UserId};
 
 #[derive(
-    Default, Clone, Copy, PartialEq, Debug, Display, Serialize, Deserialize, AsRef, From, Into,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
 )]
 pub struct IdentifierId(pub Uuid);
 
@@ -18,11 +30,12 @@ impl IdentifierId {
     }
 }
 
-#[derive(Default, Serialize, Deserialize, FromRow)]
+#[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Identifier {
     pub id: IdentifierId,
     pub value: String,
     pub user_id: UserId,
+    pub service_id: ServiceId,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -50,6 +50,8 @@ pub enum KeyKind {
     #[default]
     Api,
     Token,
+    /// Signs credentials the service issues.
+    Issuer,
 }
 
 #[derive(Builder, Debug, PartialEq)]
@@ -0,0 +1,70 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::*;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{service::ServiceId, user::UserId};
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
+)]
+pub struct RecoveryMethodId(pub Uuid);
+
+impl RecoveryMethodId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// A way for a user who's lost their wallet to get back into their account.
+#[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RecoveryMethod {
+    pub id: RecoveryMethodId,
+    pub kind: RecoveryKind,
+    /// What the user has to present, see [`RecoveryKind`].
+    #[serde(skip_serializing)]
+    pub value: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub service_id: ServiceId,
+    pub user_id: UserId,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "recovery_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryKind {
+    /// A credential the user registered with, identified by a fingerprint of the
+    /// service's `recovery_claim`. It has to be reissued to the new wallet.
+    #[default]
+    Credential,
+    /// A DID set aside ahead of time, which becomes the user's identifier once used.
+    Did,
+    /// A one-time code handed out when an admin approves a recovery.
+    Approval,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateRecoveryMethod {
+    pub kind: RecoveryKind,
+    #[builder(into)]
+    pub value: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub service_id: ServiceId,
+    pub user_id: UserId,
+}
@@ -0,0 +1,65 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::*;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::service::ServiceId;
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
+)]
+pub struct RedirectUriId(pub Uuid);
+
+impl RedirectUriId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// Somewhere the service allows users to be sent back to, matched exactly.
+#[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RedirectUri {
+    pub id: RedirectUriId,
+    pub kind: RedirectUriKind,
+    pub uri: String,
+    pub service_id: ServiceId,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "redirect_uri_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectUriKind {
+    /// Where to send the user once they've signed in, as well as the service's
+    /// `redirect_url`.
+    #[default]
+    Redirect,
+    /// Where to send the user once they've logged out, as well as the service's
+    /// `logout_url`.
+    PostLogout,
+    /// Where wallets picking up a credential offer can be sent back to with a code.
+    Wallet,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateRedirectUri {
+    pub kind: RedirectUriKind,
+    #[builder(into)]
+    pub uri: String,
+    pub service_id: ServiceId,
+}
@@ -1,7 +1,11 @@
+use base64::prelude::*;
 use bon::Builder;
 use chrono::{DateTime, Utc};
 use derive_more::{AsRef, Display, From, Into};
+use hmac::{Hmac, Mac};
+use mist_common::Result;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use sqlx::prelude::*;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -28,6 +32,9 @@ impl ServiceId {
     }
 }
 
+/// How long sessions last when the service doesn't say, in seconds.
+pub const DEFAULT_SESSION_LIFETIME: i64 = 60 * 60 * 8;
+
 #[derive(Default, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Service {
     pub id: ServiceId,
@@ -35,10 +42,66 @@ pub struct Service {
     pub redirect_url: String,
     pub logout_url: String,
     pub webhook_url: String,
+    pub response_mode: ResponseMode,
+    pub revocation_policy: RevocationPolicy,
+    pub webhook_mode: WebhookMode,
+    /// Whether, and how, to ask the service before signing a user in.
+    pub sign_in_webhook: Option<WebhookMode>,
+    pub unknown_sign_in: UnknownSignIn,
+    /// The profile claim users can recover their account with, like `email`.
+    pub recovery_claim: Option<String>,
+    /// Services sharing an SSO domain can sign users in with each other's sessions.
+    pub sso_domain: Option<String>,
+    /// How long a signed in user can go without activity, in seconds. Without one,
+    /// sessions last their whole lifetime.
+    pub session_idle_timeout: Option<i64>,
+    /// How long a session can last at most, however active, in seconds.
+    pub session_lifetime: i64,
+    /// Whether the service is handed a code on redirect, to exchange for access and
+    /// refresh tokens, instead of relying on the session cookie.
+    pub token_flow: bool,
+    /// Where to POST logout tokens when a user's session ends, per OpenID back-channel
+    /// logout.
+    pub backchannel_logout_uri: Option<String>,
+    /// A page to load in a hidden iframe when a user logs out, per OpenID front-channel
+    /// logout.
+    pub frontchannel_logout_uri: Option<String>,
+    /// What the service knows users by.
+    pub subject_type: SubjectType,
+    /// Keeps pairwise subjects from being worked out from the DID. Never leaves Mist.
+    #[serde(skip)]
+    pub subject_salt: Uuid,
+    /// Why the service asks for the user's data. With one, users are shown what the
+    /// service will get, and have to agree, before they're shown the QR code to sign up.
+    pub consent_purpose: Option<String>,
+    pub privacy_policy_url: Option<String>,
+    pub terms_url: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Service {
+    /// What the service knows the user who signed in with the DID by.
+    ///
+    /// A DID is the same wherever the user takes their wallet, so services that
+    /// compare notes could tell they share a user. Services with `pairwise` subjects
+    /// are only ever given `base64url(hmac(subject_salt, did))` in its place: stable
+    /// for the service, but meaningless to any other.
+    ///
+    /// See: https://openid.net/specs/openid-connect-core-1_0.html#PairwiseAlg
+    pub fn subject(&self, did: &str) -> Result<String> {
+        match self.subject_type {
+            SubjectType::Public => Ok(did.to_string()),
+            SubjectType::Pairwise => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(self.subject_salt.as_bytes())?;
+                mac.update(did.as_bytes());
+
+                Ok(BASE64_URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes()))
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Builder)]
 pub struct CreateService {
     #[builder(into)]
@@ -49,6 +112,36 @@ pub struct CreateService {
     pub logout_url: String,
     #[builder(into)]
     pub webhook_url: String,
+    #[builder(default)]
+    pub response_mode: ResponseMode,
+    #[builder(default)]
+    pub revocation_policy: RevocationPolicy,
+    #[builder(default)]
+    pub webhook_mode: WebhookMode,
+    pub sign_in_webhook: Option<WebhookMode>,
+    #[builder(default)]
+    pub unknown_sign_in: UnknownSignIn,
+    #[builder(into)]
+    pub recovery_claim: Option<String>,
+    #[builder(into)]
+    pub sso_domain: Option<String>,
+    pub session_idle_timeout: Option<i64>,
+    #[builder(default = DEFAULT_SESSION_LIFETIME)]
+    pub session_lifetime: i64,
+    #[builder(default)]
+    pub token_flow: bool,
+    #[builder(into)]
+    pub backchannel_logout_uri: Option<String>,
+    #[builder(into)]
+    pub frontchannel_logout_uri: Option<String>,
+    #[builder(default)]
+    pub subject_type: SubjectType,
+    #[builder(into)]
+    pub consent_purpose: Option<String>,
+    #[builder(into)]
+    pub privacy_policy_url: Option<String>,
+    #[builder(into)]
+    pub terms_url: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Builder)]
@@ -61,4 +154,125 @@ pub struct UpdateService {
     pub logout_url: Option<String>,
     #[builder(into)]
     pub webhook_url: Option<String>,
+    pub response_mode: Option<ResponseMode>,
+    pub revocation_policy: Option<RevocationPolicy>,
+    pub webhook_mode: Option<WebhookMode>,
+    /// `Some(None)` turns the sign in webhook off.
+    pub sign_in_webhook: Option<Option<WebhookMode>>,
+    pub unknown_sign_in: Option<UnknownSignIn>,
+    /// `Some(None)` turns credential recovery off.
+    pub recovery_claim: Option<Option<String>>,
+    /// `Some(None)` takes the service out of its SSO domain.
+    pub sso_domain: Option<Option<String>>,
+    /// `Some(None)` turns the idle timeout off.
+    pub session_idle_timeout: Option<Option<i64>>,
+    pub session_lifetime: Option<i64>,
+    pub token_flow: Option<bool>,
+    /// `Some(None)` turns back-channel logout off.
+    pub backchannel_logout_uri: Option<Option<String>>,
+    /// `Some(None)` turns front-channel logout off.
+    pub frontchannel_logout_uri: Option<Option<String>>,
+    pub subject_type: Option<SubjectType>,
+    /// `Some(None)` turns the consent page off.
+    pub consent_purpose: Option<Option<String>>,
+    pub privacy_policy_url: Option<Option<String>>,
+    pub terms_url: Option<Option<String>>,
+}
+
+/// How the wallet sends its authorization response back to Mist.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema, Display)]
+#[sqlx(type_name = "response_mode")]
+pub enum ResponseMode {
+    /// Plain form post of `id_token` and `vp_token`.
+    #[default]
+    #[sqlx(rename = "post")]
+    #[serde(rename = "post")]
+    #[display("post")]
+    Post,
+    /// JWE-encrypted response posted to the `response_uri`.
+    #[sqlx(rename = "direct_post.jwt")]
+    #[serde(rename = "direct_post.jwt")]
+    #[display("direct_post.jwt")]
+    DirectPostJwt,
+}
+
+/// What to do with a credential when its status list can't be checked.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "revocation_policy", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationPolicy {
+    /// Accept the credential anyway.
+    FailOpen,
+    /// Reject the credential.
+    #[default]
+    FailClosed,
+}
+
+/// How registrations (and sign ins) are sent to the service's webhook.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "webhook_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookMode {
+    /// Queued, with the service calling `/complete` once it's decided.
+    #[default]
+    Async,
+    /// Called while the user waits, with the response as the decision.
+    Sync,
+}
+
+/// What to do when someone signs in with a wallet we've never seen.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "unknown_sign_in", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownSignIn {
+    /// Tell them no account was found.
+    #[default]
+    Reject,
+    /// Switch them over to signing up.
+    Register,
+}
+
+/// What services know users by.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "subject_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectType {
+    /// The DID they signed in with, the same for every service.
+    #[default]
+    Public,
+    /// An opaque id derived from the DID, different for every service, so services
+    /// can't tell they share a user.
+    Pairwise,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DID: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    fn service(subject_type: SubjectType) -> Service {
+        Service {
+            subject_type,
+            subject_salt: Uuid::new_v4(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn public_subjects_are_the_did() {
+        assert_eq!(service(SubjectType::Public).subject(DID).unwrap(), DID);
+    }
+
+    #[test]
+    fn pairwise_subjects_differ_per_service() {
+        let service = service(SubjectType::Pairwise);
+        let other = self::service(SubjectType::Pairwise);
+
+        let sub = service.subject(DID).unwrap();
+
+        assert_eq!(service.subject(DID).unwrap(), sub);
+        assert_ne!(other.subject(DID).unwrap(), sub);
+        assert!(!sub.contains(DID));
+    }
 }
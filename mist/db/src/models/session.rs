@@ -0,0 +1,56 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, FromStr, Into};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{service::ServiceId, user::UserId};
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    FromStr,
+    Into,
+    ToSchema,
+)]
+pub struct SessionId(pub Uuid);
+
+impl SessionId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// A signed in user's session, as seen from outside the auth flow.
+#[derive(Default, Clone, PartialEq, Debug, Serialize, Deserialize, ToSchema)]
+pub struct Session {
+    pub id: SessionId,
+    pub service_id: ServiceId,
+    pub user_id: UserId,
+    /// The browser the user signed in from.
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the service last checked the session.
+    pub last_seen: DateTime<Utc>,
+}
+
+#[derive(Builder)]
+pub struct CreateSession {
+    pub id: SessionId,
+    pub service_id: ServiceId,
+    pub user_id: UserId,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    /// When the session ends unless it's kept alive, as a unix timestamp.
+    pub ends_at: i64,
+}
@@ -0,0 +1,56 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::*;
+use uuid::Uuid;
+
+use super::{service::ServiceId, session::SessionId, user::UserId};
+
+#[derive(
+    Default, Clone, Copy, PartialEq, Debug, Display, Serialize, Deserialize, AsRef, From, Into,
+)]
+pub struct SignUpConsentId(pub Uuid);
+
+impl SignUpConsentId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// What a user was shown, and agreed to, before signing up. Kept for audit.
+#[derive(Default, Serialize, Deserialize, FromRow)]
+pub struct SignUpConsent {
+    pub id: SignUpConsentId,
+    /// Who the user will be once they've signed up.
+    pub user_id: UserId,
+    pub session_id: SessionId,
+    pub service_id: ServiceId,
+    /// The fields the service asked for.
+    pub fields: Vec<String>,
+    pub purpose: String,
+    pub privacy_policy_url: Option<String>,
+    pub terms_url: Option<String>,
+    /// Where the user agreed from.
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateSignUpConsent {
+    pub user_id: UserId,
+    pub session_id: SessionId,
+    pub service_id: ServiceId,
+    pub fields: Vec<String>,
+    #[builder(into)]
+    pub purpose: String,
+    #[builder(into)]
+    pub privacy_policy_url: Option<String>,
+    #[builder(into)]
+    pub terms_url: Option<String>,
+    #[builder(into)]
+    pub ip: Option<String>,
+    #[builder(into)]
+    pub user_agent: Option<String>,
+}
@@ -0,0 +1,70 @@
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use derive_more::{AsRef, Display, From, Into};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::*;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{definition::DefinitionId, service::ServiceId};
+
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
+)]
+pub struct TrustedIssuerId(pub Uuid);
+
+impl TrustedIssuerId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+/// An issuer whose credentials we accept.
+///
+/// Issuers without a service apply to every service, and issuers with a definition
+/// only apply to credentials presented for that definition.
+#[derive(Default, Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TrustedIssuer {
+    pub id: TrustedIssuerId,
+    pub kind: IssuerKind,
+    pub value: String,
+    pub service_id: Option<Uuid>,
+    pub definition_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How an issuer is matched against a credential.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize, Type, ToSchema)]
+#[sqlx(type_name = "issuer_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum IssuerKind {
+    /// The issuer's DID must equal the value.
+    #[default]
+    Did,
+    /// The issuer's DID must start with the value, e.g. `did:web:example.com`.
+    DidPrefix,
+    /// The credential must be signed by a certificate chaining up to the value, a
+    /// PEM-encoded trust anchor.
+    X509,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct CreateTrustedIssuer {
+    pub kind: IssuerKind,
+    #[builder(into)]
+    pub value: String,
+    pub service_id: Option<ServiceId>,
+    pub definition_id: Option<DefinitionId>,
+}
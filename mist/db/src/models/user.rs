@@ -3,12 +3,24 @@ use chrono::{DateTime, Utc};
 use derive_more::{AsRef, Display, From, Into};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::*;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use super::service::ServiceId;
 
 #[derive(
-    Default, Clone, Copy, PartialEq, Debug, Display, Serialize, Deserialize, AsRef, From, Into,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Debug,
+    Display,
+    Serialize,
+    Deserialize,
+    AsRef,
+    From,
+    Into,
+    ToSchema,
 )]
 pub struct UserId(pub Uuid);
 
@@ -22,6 +34,8 @@ impl UserId {
 pub struct User {
     pub id: UserId,
     pub service_id: ServiceId,
+    /// The service's own id for the user, if it gave us one.
+    pub external_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -32,4 +46,5 @@ pub struct CreateUser {
     pub id: UserId,
     #[builder(into)]
     pub service_id: Uuid,
+    pub external_id: Option<String>,
 }
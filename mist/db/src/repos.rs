@@ -1,4 +1,12 @@
+pub mod credential_configurations;
+pub mod credential_offers;
 pub mod identifiers;
 pub mod keys;
+pub mod recovery_methods;
+pub mod redirect_uris;
 pub mod services;
+pub mod sessions;
+pub mod sign_up_consents;
+pub mod sso_consents;
+pub mod trusted_issuers;
 pub mod users;
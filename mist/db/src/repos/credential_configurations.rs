@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file_as, types::Json, PgPool};
+
+use crate::models::{
+    credential_configuration::{
+        CreateCredentialConfiguration, CredentialConfiguration, CredentialConfigurationId,
+    },
+    service::ServiceId,
+};
+
+#[async_trait]
+#[mockall::automock]
+pub trait CredentialConfigurationRepo: Send + Sync {
+    async fn list(
+        &self,
+        service_id: &ServiceId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CredentialConfiguration>>;
+    async fn create(&self, data: &CreateCredentialConfiguration)
+        -> Result<CredentialConfiguration>;
+    async fn get(&self, id: &CredentialConfigurationId) -> Result<CredentialConfiguration>;
+    async fn get_by_name(
+        &self,
+        service_id: &ServiceId,
+        name: &str,
+    ) -> Result<CredentialConfiguration>;
+    async fn destroy(&self, id: &CredentialConfigurationId) -> Result<CredentialConfiguration>;
+}
+
+pub struct PgCredentialConfigurationRepo {
+    pool: PgPool,
+}
+
+impl PgCredentialConfigurationRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CredentialConfigurationRepo for PgCredentialConfigurationRepo {
+    async fn list(
+        &self,
+        service_id: &ServiceId,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CredentialConfiguration>> {
+        let configurations = query_file_as!(
+            CredentialConfiguration,
+            "sql/credential_configurations/list.sql",
+            service_id.as_ref(),
+            limit,
+            offset,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(configurations)
+    }
+
+    async fn create(
+        &self,
+        data: &CreateCredentialConfiguration,
+    ) -> Result<CredentialConfiguration> {
+        let configuration = query_file_as!(
+            CredentialConfiguration,
+            "sql/credential_configurations/create.sql",
+            data.name,
+            data.display_name,
+            Json(&data.claims) as _,
+            data.valid_for,
+            data.service_id.as_ref(),
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(configuration)
+    }
+
+    async fn get(&self, id: &CredentialConfigurationId) -> Result<CredentialConfiguration> {
+        let configuration = query_file_as!(
+            CredentialConfiguration,
+            "sql/credential_configurations/get.sql",
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(configuration)
+    }
+
+    async fn get_by_name(
+        &self,
+        service_id: &ServiceId,
+        name: &str,
+    ) -> Result<CredentialConfiguration> {
+        let configuration = query_file_as!(
+            CredentialConfiguration,
+            "sql/credential_configurations/get_by_name.sql",
+            service_id.as_ref(),
+            name
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(configuration)
+    }
+
+    async fn destroy(&self, id: &CredentialConfigurationId) -> Result<CredentialConfiguration> {
+        let configuration = query_file_as!(
+            CredentialConfiguration,
+            "sql/credential_configurations/destroy.sql",
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(configuration)
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file_as, types::Json, PgPool};
+
+use crate::models::credential_offer::{
+    CreateCredentialOffer, CredentialOffer, CredentialOfferId, OfferGrant,
+};
+
+#[async_trait]
+#[mockall::automock]
+pub trait CredentialOfferRepo: Send + Sync {
+    async fn create(&self, data: &CreateCredentialOffer) -> Result<CredentialOffer>;
+    async fn get(&self, id: &CredentialOfferId) -> Result<CredentialOffer>;
+    async fn get_by_pre_authorized_code(&self, code: &str) -> Result<CredentialOffer>;
+    /// Mark the offer as redeemed, failing if it already was or has expired.
+    async fn redeem(&self, id: &CredentialOfferId) -> Result<CredentialOffer>;
+}
+
+pub struct PgCredentialOfferRepo {
+    pool: PgPool,
+}
+
+impl PgCredentialOfferRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CredentialOfferRepo for PgCredentialOfferRepo {
+    async fn create(&self, data: &CreateCredentialOffer) -> Result<CredentialOffer> {
+        let offer = query_file_as!(
+            CredentialOffer,
+            "sql/credential_offers/create.sql",
+            data.grant_type.clone() as OfferGrant,
+            Json(&data.claims) as _,
+            data.pre_authorized_code,
+            data.expires_at,
+            data.configuration_id.as_ref(),
+            data.user_id.map(|id| id.0),
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    async fn get(&self, id: &CredentialOfferId) -> Result<CredentialOffer> {
+        let offer = query_file_as!(
+            CredentialOffer,
+            "sql/credential_offers/get.sql",
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    async fn get_by_pre_authorized_code(&self, code: &str) -> Result<CredentialOffer> {
+        let offer = query_file_as!(
+            CredentialOffer,
+            "sql/credential_offers/get_by_pre_authorized_code.sql",
+            code
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    async fn redeem(&self, id: &CredentialOfferId) -> Result<CredentialOffer> {
+        let offer = query_file_as!(
+            CredentialOffer,
+            "sql/credential_offers/redeem.sql",
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(offer)
+    }
+}
@@ -1,15 +1,32 @@
 use async_trait::async_trait;
-use mist_common::Result;
-use sqlx::{query_file_as, PgPool};
+use mist_common::{error::Conflict, Result};
+use sqlx::{query_file, query_file_as, query_file_scalar, PgPool};
 
-use crate::models::identifier::{CreateIdentifier, Identifier, IdentifierId};
+use crate::models::{
+    identifier::{CreateIdentifier, Identifier, IdentifierId},
+    service::ServiceId,
+    user::UserId,
+};
 
 #[async_trait]
 #[mockall::automock]
 pub trait IdentifierRepo: Send + Sync {
     async fn create(&self, data: &CreateIdentifier) -> Result<Identifier>;
     async fn get(&self, id: &IdentifierId) -> Result<Identifier>;
-    async fn get_by_value(&self, value: &str) -> Result<Identifier>;
+    /// The identifier with the value among the service's users.
+    async fn get_by_value_for_service(
+        &self,
+        service_id: &ServiceId,
+        value: &str,
+    ) -> Result<Identifier>;
+    async fn list(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<Identifier>>;
+    /// Remove one of the user's identifiers, as long as it isn't their last.
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &IdentifierId,
+    ) -> Result<Identifier>;
 }
 
 pub struct PgIdentifierRepo {
@@ -44,11 +61,78 @@ impl IdentifierRepo for PgIdentifierRepo {
 
         Ok(identifier)
     }
-    async fn get_by_value(&self, value: &str) -> Result<Identifier> {
-        let identifier = query_file_as!(Identifier, "sql/identifiers/get_by_value.sql", value)
-            .fetch_one(&self.pool)
+
+    async fn get_by_value_for_service(
+        &self,
+        service_id: &ServiceId,
+        value: &str,
+    ) -> Result<Identifier> {
+        let identifier = query_file_as!(
+            Identifier,
+            "sql/identifiers/get_by_value_for_service.sql",
+            service_id.as_ref(),
+            value
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(identifier)
+    }
+
+    async fn list(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<Identifier>> {
+        let identifiers = query_file_as!(
+            Identifier,
+            "sql/identifiers/list.sql",
+            service_id.as_ref(),
+            user_id.as_ref()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(identifiers)
+    }
+
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &IdentifierId,
+    ) -> Result<Identifier> {
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the user, so two removals can't both think there's another identifier left.
+        // ---------------------------------------------------------------------------------
+
+        query_file!(
+            "sql/identifiers/lock_user.sql",
+            service_id.as_ref(),
+            user_id.as_ref()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let count = query_file_scalar!("sql/identifiers/count.sql", user_id.as_ref())
+            .fetch_one(&mut *tx)
             .await?;
 
+        if count <= 1 {
+            return Err(Conflict("can't remove a user's last identifier").into());
+        }
+
+        // Remove the identifier.
+        // ----------------------
+
+        let identifier = query_file_as!(
+            Identifier,
+            "sql/identifiers/destroy.sql",
+            user_id.as_ref(),
+            id.as_ref()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
         Ok(identifier)
     }
 }
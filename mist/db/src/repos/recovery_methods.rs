@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file_as, PgPool};
+
+use crate::models::{
+    recovery_method::{CreateRecoveryMethod, RecoveryKind, RecoveryMethod, RecoveryMethodId},
+    service::ServiceId,
+    user::UserId,
+};
+
+#[async_trait]
+#[mockall::automock]
+pub trait RecoveryMethodRepo: Send + Sync {
+    async fn create(&self, data: &CreateRecoveryMethod) -> Result<RecoveryMethod>;
+    async fn list(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<RecoveryMethod>>;
+    /// Find an unexpired method matching what the user presented.
+    async fn find(
+        &self,
+        service_id: &ServiceId,
+        kind: &RecoveryKind,
+        value: &str,
+    ) -> Result<RecoveryMethod>;
+    /// Use up a method, failing if it's already been used or has expired.
+    async fn take(&self, id: &RecoveryMethodId) -> Result<RecoveryMethod>;
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &RecoveryMethodId,
+    ) -> Result<RecoveryMethod>;
+}
+
+pub struct PgRecoveryMethodRepo {
+    pool: PgPool,
+}
+
+impl PgRecoveryMethodRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RecoveryMethodRepo for PgRecoveryMethodRepo {
+    async fn create(&self, data: &CreateRecoveryMethod) -> Result<RecoveryMethod> {
+        let method = query_file_as!(
+            RecoveryMethod,
+            "sql/recovery_methods/create.sql",
+            data.service_id.as_ref(),
+            data.user_id.as_ref(),
+            data.kind.clone() as RecoveryKind,
+            data.value,
+            data.expires_at,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(method)
+    }
+
+    async fn list(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<RecoveryMethod>> {
+        let methods = query_file_as!(
+            RecoveryMethod,
+            "sql/recovery_methods/list.sql",
+            service_id.as_ref(),
+            user_id.as_ref()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(methods)
+    }
+
+    async fn find(
+        &self,
+        service_id: &ServiceId,
+        kind: &RecoveryKind,
+        value: &str,
+    ) -> Result<RecoveryMethod> {
+        let method = query_file_as!(
+            RecoveryMethod,
+            "sql/recovery_methods/find.sql",
+            service_id.as_ref(),
+            kind.clone() as RecoveryKind,
+            value
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(method)
+    }
+
+    async fn take(&self, id: &RecoveryMethodId) -> Result<RecoveryMethod> {
+        let method = query_file_as!(RecoveryMethod, "sql/recovery_methods/take.sql", id.as_ref())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(method)
+    }
+
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &RecoveryMethodId,
+    ) -> Result<RecoveryMethod> {
+        let method = query_file_as!(
+            RecoveryMethod,
+            "sql/recovery_methods/destroy.sql",
+            service_id.as_ref(),
+            user_id.as_ref(),
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(method)
+    }
+}
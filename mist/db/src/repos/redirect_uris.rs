@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file, query_file_as, PgPool};
+
+use crate::models::{
+    redirect_uri::{CreateRedirectUri, RedirectUri, RedirectUriId, RedirectUriKind},
+    service::ServiceId,
+};
+
+#[async_trait]
+#[mockall::automock]
+pub trait RedirectUriRepo: Send + Sync {
+    async fn create(&self, data: &CreateRedirectUri) -> Result<RedirectUri>;
+    async fn list(&self, service_id: &ServiceId) -> Result<Vec<RedirectUri>>;
+    /// Whether the service registered exactly this URI.
+    async fn exists(
+        &self,
+        service_id: &ServiceId,
+        kind: &RedirectUriKind,
+        uri: &str,
+    ) -> Result<bool>;
+    async fn destroy(&self, service_id: &ServiceId, id: &RedirectUriId) -> Result<RedirectUri>;
+}
+
+pub struct PgRedirectUriRepo {
+    pool: PgPool,
+}
+
+impl PgRedirectUriRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RedirectUriRepo for PgRedirectUriRepo {
+    async fn create(&self, data: &CreateRedirectUri) -> Result<RedirectUri> {
+        let uri = query_file_as!(
+            RedirectUri,
+            "sql/redirect_uris/create.sql",
+            data.service_id.as_ref(),
+            data.kind.clone() as RedirectUriKind,
+            data.uri,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(uri)
+    }
+
+    async fn list(&self, service_id: &ServiceId) -> Result<Vec<RedirectUri>> {
+        let uris = query_file_as!(
+            RedirectUri,
+            "sql/redirect_uris/list.sql",
+            service_id.as_ref()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(uris)
+    }
+
+    async fn exists(
+        &self,
+        service_id: &ServiceId,
+        kind: &RedirectUriKind,
+        uri: &str,
+    ) -> Result<bool> {
+        let row = query_file!(
+            "sql/redirect_uris/exists.sql",
+            service_id.as_ref(),
+            kind.clone() as RedirectUriKind,
+            uri
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.exists)
+    }
+
+    async fn destroy(&self, service_id: &ServiceId, id: &RedirectUriId) -> Result<RedirectUri> {
+        let uri = query_file_as!(
+            RedirectUri,
+            "sql/redirect_uris/destroy.sql",
+            service_id.as_ref(),
+            id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(uri)
+    }
+}
@@ -9,7 +9,10 @@ use sqlx::{query_file_as, PgPool};
 use crate::models::{
     definition::{CreateDefinition, Definition},
     key::{Key, KeyKind},
-    service::{CreateService, Service, ServiceId, UpdateService},
+    service::{
+        CreateService, ResponseMode, RevocationPolicy, Service, ServiceId, SubjectType,
+        UnknownSignIn, UpdateService, WebhookMode,
+    },
 };
 
 #[async_trait]
@@ -66,7 +69,23 @@ impl ServiceRepo for PgServiceRepo {
             &service.name,
             &service.redirect_url,
             &service.logout_url,
-            &service.webhook_url
+            &service.webhook_url,
+            service.response_mode.clone() as ResponseMode,
+            service.revocation_policy.clone() as RevocationPolicy,
+            service.webhook_mode.clone() as WebhookMode,
+            service.sign_in_webhook.clone() as Option<WebhookMode>,
+            service.unknown_sign_in.clone() as UnknownSignIn,
+            service.recovery_claim,
+            service.sso_domain,
+            service.session_idle_timeout,
+            service.session_lifetime,
+            service.token_flow,
+            service.backchannel_logout_uri,
+            service.frontchannel_logout_uri,
+            service.subject_type.clone() as SubjectType,
+            service.consent_purpose,
+            service.privacy_policy_url,
+            service.terms_url
         )
         .fetch_one(&mut *tx)
         .await?;
@@ -153,6 +172,48 @@ impl ServiceRepo for PgServiceRepo {
             .unwrap_or(&service.redirect_url);
         let logout_url = data.logout_url.as_deref().unwrap_or(&service.logout_url);
         let webhook_url = data.webhook_url.as_deref().unwrap_or(&service.webhook_url);
+        let response_mode = data.response_mode.clone().unwrap_or(service.response_mode);
+        let revocation_policy = data
+            .revocation_policy
+            .clone()
+            .unwrap_or(service.revocation_policy);
+        let webhook_mode = data.webhook_mode.clone().unwrap_or(service.webhook_mode);
+        let sign_in_webhook = data
+            .sign_in_webhook
+            .clone()
+            .unwrap_or(service.sign_in_webhook);
+        let unknown_sign_in = data
+            .unknown_sign_in
+            .clone()
+            .unwrap_or(service.unknown_sign_in);
+        let recovery_claim = data
+            .recovery_claim
+            .clone()
+            .unwrap_or(service.recovery_claim);
+        let sso_domain = data.sso_domain.clone().unwrap_or(service.sso_domain);
+        let session_idle_timeout = data
+            .session_idle_timeout
+            .unwrap_or(service.session_idle_timeout);
+        let session_lifetime = data.session_lifetime.unwrap_or(service.session_lifetime);
+        let token_flow = data.token_flow.unwrap_or(service.token_flow);
+        let backchannel_logout_uri = data
+            .backchannel_logout_uri
+            .clone()
+            .unwrap_or(service.backchannel_logout_uri);
+        let frontchannel_logout_uri = data
+            .frontchannel_logout_uri
+            .clone()
+            .unwrap_or(service.frontchannel_logout_uri);
+        let subject_type = data.subject_type.clone().unwrap_or(service.subject_type);
+        let consent_purpose = data
+            .consent_purpose
+            .clone()
+            .unwrap_or(service.consent_purpose);
+        let privacy_policy_url = data
+            .privacy_policy_url
+            .clone()
+            .unwrap_or(service.privacy_policy_url);
+        let terms_url = data.terms_url.clone().unwrap_or(service.terms_url);
 
         let profile = query_file_as!(
             Service,
@@ -161,7 +222,23 @@ impl ServiceRepo for PgServiceRepo {
             &name,
             &redirect_url,
             &logout_url,
-            &webhook_url
+            &webhook_url,
+            response_mode as ResponseMode,
+            revocation_policy as RevocationPolicy,
+            webhook_mode as WebhookMode,
+            sign_in_webhook as Option<WebhookMode>,
+            unknown_sign_in as UnknownSignIn,
+            recovery_claim,
+            sso_domain,
+            session_idle_timeout,
+            session_lifetime,
+            token_flow,
+            backchannel_logout_uri,
+            frontchannel_logout_uri,
+            subject_type as SubjectType,
+            consent_purpose,
+            privacy_policy_url,
+            terms_url
         )
         .fetch_one(&self.pool)
         .await?;
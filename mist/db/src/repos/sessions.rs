@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use fred::{
+    prelude::*,
+    types::{Expiration, SetOptions},
+};
+use mist_common::{error::NotFound, redis::TypedRedis, Result};
+
+use crate::models::{
+    service::ServiceId,
+    session::{CreateSession, Session, SessionId},
+    user::UserId,
+};
+
+/// Where authn keeps the auth flow's side of a session. Removing it signs the user out.
+pub const AUTH_SESSION_PREFIX: &str = "mist-auth";
+
+static AUTH_SESSION: TypedRedis<serde_json::Value> = TypedRedis::new(AUTH_SESSION_PREFIX);
+static SESSION: TypedRedis<Session> = TypedRedis::new("mist-session");
+
+/// Signed in sessions, indexed by user and by service so they can be listed and revoked.
+#[async_trait]
+#[mockall::automock]
+pub trait SessionRepo: Send + Sync {
+    async fn create(&self, data: &CreateSession) -> Result<Session>;
+    async fn get(&self, id: &SessionId) -> Result<Session>;
+    async fn list_by_service(&self, service_id: &ServiceId) -> Result<Vec<Session>>;
+    async fn list_by_user(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<Session>>;
+    /// Mark the session as just used.
+    async fn touch(&self, id: &SessionId) -> Result<()>;
+    /// Push back when the session ends, as a unix timestamp, as it's still in use.
+    async fn keep_alive(&self, id: &SessionId, ends_at: i64) -> Result<()>;
+    /// End the session, signing the user out.
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &SessionId,
+    ) -> Result<Session>;
+}
+
+pub struct RedisSessionRepo {
+    redis: RedisClient,
+}
+
+impl RedisSessionRepo {
+    pub fn new(redis: RedisClient) -> Self {
+        Self { redis }
+    }
+
+    fn user_key(user_id: &UserId) -> String {
+        format!("mist-sessions-user-{user_id}")
+    }
+
+    fn service_key(service_id: &ServiceId) -> String {
+        format!("mist-sessions-service-{service_id}")
+    }
+
+    /// Put a session in an index, scored by when it ends, dropping any that have ended.
+    ///
+    /// The index itself expires along with the last of its sessions.
+    async fn index(
+        &self,
+        key: &str,
+        id: &SessionId,
+        ends_at: i64,
+        options: Option<SetOptions>,
+    ) -> Result<()> {
+        self.redis
+            .zadd::<(), _, _>(
+                key,
+                options,
+                None,
+                false,
+                false,
+                (ends_at as f64, id.to_string()),
+            )
+            .await?;
+        self.prune(key).await?;
+
+        let last = self
+            .redis
+            .zrange::<Vec<(String, f64)>, _, _, _>(key, 0, 0, None, true, None, true)
+            .await?;
+
+        if let Some((_, ends_at)) = last.first() {
+            self.redis.expire_at::<(), _>(key, *ends_at as i64).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drop the sessions in an index that have ended.
+    async fn prune(&self, key: &str) -> Result<()> {
+        self.redis
+            .zremrangebyscore::<(), _, _, _>(key, "-inf", Utc::now().timestamp())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read the sessions in an index that haven't ended.
+    ///
+    /// Sessions can also end by being signed out, which only the auth session knows
+    /// about, so those are dropped as they're found.
+    async fn read_index(&self, key: &str) -> Result<Vec<Session>> {
+        self.prune(key).await?;
+
+        let ids = self
+            .redis
+            .zrange::<Vec<String>, _, _, _>(key, 0, -1, None, false, None, false)
+            .await?;
+        let mut sessions = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            let active = self.redis.exists::<bool, _>(AUTH_SESSION.key(&id)).await?;
+
+            match SESSION.get(&self.redis, &id).await {
+                Ok(session) if active => sessions.push(session),
+                _ => {
+                    SESSION.del(&self.redis, &id).await?;
+                    self.redis.zrem::<(), _, _>(key, id).await?
+                }
+            }
+        }
+
+        sessions.sort_by_key(|session| session.created_at);
+
+        Ok(sessions)
+    }
+}
+
+#[async_trait]
+impl SessionRepo for RedisSessionRepo {
+    async fn create(&self, data: &CreateSession) -> Result<Session> {
+        let now = Utc::now();
+        let session = Session {
+            id: data.id,
+            service_id: data.service_id,
+            user_id: data.user_id,
+            user_agent: data.user_agent.clone(),
+            ip: data.ip.clone(),
+            created_at: now,
+            last_seen: now,
+        };
+
+        SESSION
+            .set(
+                &self.redis,
+                &data.id.to_string(),
+                &session,
+                Expiration::EXAT(data.ends_at),
+            )
+            .await?;
+
+        for key in [
+            Self::user_key(&data.user_id),
+            Self::service_key(&data.service_id),
+        ] {
+            self.index(&key, &data.id, data.ends_at, None).await?;
+        }
+
+        Ok(session)
+    }
+
+    async fn get(&self, id: &SessionId) -> Result<Session> {
+        SESSION
+            .get(&self.redis, &id.to_string())
+            .await
+            .map_err(|_| NotFound("session not found").into())
+    }
+
+    async fn list_by_service(&self, service_id: &ServiceId) -> Result<Vec<Session>> {
+        self.read_index(&Self::service_key(service_id)).await
+    }
+
+    async fn list_by_user(&self, service_id: &ServiceId, user_id: &UserId) -> Result<Vec<Session>> {
+        let sessions = self.read_index(&Self::user_key(user_id)).await?;
+
+        Ok(sessions
+            .into_iter()
+            .filter(|session| session.service_id == *service_id)
+            .collect())
+    }
+
+    async fn touch(&self, id: &SessionId) -> Result<()> {
+        let session = self.get(id).await?;
+
+        SESSION
+            .set(
+                &self.redis,
+                &id.to_string(),
+                &Session {
+                    last_seen: Utc::now(),
+                    ..session
+                },
+                Expiration::KEEPTTL,
+            )
+            .await
+    }
+
+    async fn keep_alive(&self, id: &SessionId, ends_at: i64) -> Result<()> {
+        let session = self.get(id).await?;
+
+        SESSION
+            .expire_at(&self.redis, &id.to_string(), ends_at)
+            .await?;
+
+        // Only move sessions that are still indexed, rather than bring back revoked ones.
+        for key in [
+            Self::user_key(&session.user_id),
+            Self::service_key(&session.service_id),
+        ] {
+            self.index(&key, id, ends_at, Some(SetOptions::XX)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn destroy(
+        &self,
+        service_id: &ServiceId,
+        user_id: &UserId,
+        id: &SessionId,
+    ) -> Result<Session> {
+        let session = self.get(id).await?;
+
+        if session.service_id != *service_id || session.user_id != *user_id {
+            return Err(NotFound("session not found").into());
+        }
+
+        AUTH_SESSION.del(&self.redis, &id.to_string()).await?;
+        SESSION.del(&self.redis, &id.to_string()).await?;
+
+        self.redis
+            .zrem::<(), _, _>(Self::user_key(user_id), id.to_string())
+            .await?;
+        self.redis
+            .zrem::<(), _, _>(Self::service_key(service_id), id.to_string())
+            .await?;
+
+        Ok(session)
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file_as, PgPool};
+
+use crate::models::sign_up_consent::{CreateSignUpConsent, SignUpConsent};
+
+/// What users agreed to before signing up.
+#[async_trait]
+#[mockall::automock]
+pub trait SignUpConsentRepo: Send + Sync {
+    async fn create(&self, data: &CreateSignUpConsent) -> Result<SignUpConsent>;
+}
+
+pub struct PgSignUpConsentRepo {
+    pool: PgPool,
+}
+
+impl PgSignUpConsentRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SignUpConsentRepo for PgSignUpConsentRepo {
+    async fn create(&self, data: &CreateSignUpConsent) -> Result<SignUpConsent> {
+        let consent = query_file_as!(
+            SignUpConsent,
+            "sql/sign_up_consents/create.sql",
+            data.user_id.as_ref(),
+            data.session_id.as_ref(),
+            data.service_id.as_ref(),
+            &data.fields,
+            data.purpose,
+            data.privacy_policy_url,
+            data.terms_url,
+            data.ip,
+            data.user_agent,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(consent)
+    }
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file, query_file_scalar, PgPool};
+
+use crate::models::{service::ServiceId, user::UserId};
+
+/// Which services users have agreed to be signed in to through SSO.
+#[async_trait]
+#[mockall::automock]
+pub trait SsoConsentRepo: Send + Sync {
+    async fn create(&self, user_id: &UserId, service_id: &ServiceId) -> Result<()>;
+    async fn exists(&self, user_id: &UserId, service_id: &ServiceId) -> Result<bool>;
+}
+
+pub struct PgSsoConsentRepo {
+    pool: PgPool,
+}
+
+impl PgSsoConsentRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl SsoConsentRepo for PgSsoConsentRepo {
+    async fn create(&self, user_id: &UserId, service_id: &ServiceId) -> Result<()> {
+        query_file!(
+            "sql/sso_consents/create.sql",
+            user_id.as_ref(),
+            service_id.as_ref()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn exists(&self, user_id: &UserId, service_id: &ServiceId) -> Result<bool> {
+        let exists = query_file_scalar!(
+            "sql/sso_consents/exists.sql",
+            user_id.as_ref(),
+            service_id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use mist_common::Result;
+use sqlx::{query_file_as, PgPool};
+
+use crate::models::{
+    definition::DefinitionId,
+    service::ServiceId,
+    trusted_issuer::{CreateTrustedIssuer, IssuerKind, TrustedIssuer, TrustedIssuerId},
+};
+
+#[async_trait]
+#[mockall::automock]
+pub trait TrustedIssuerRepo: Send + Sync {
+    /// List a service's own issuers, or the global ones if no service is given.
+    async fn list(
+        &self,
+        service_id: Option<ServiceId>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TrustedIssuer>>;
+    async fn create(&self, data: &CreateTrustedIssuer) -> Result<TrustedIssuer>;
+    async fn get(&self, id: &TrustedIssuerId) -> Result<TrustedIssuer>;
+    async fn destroy(&self, id: &TrustedIssuerId) -> Result<TrustedIssuer>;
+    /// Every issuer that applies to credentials presented for the given definition.
+    async fn applicable(
+        &self,
+        service_id: &ServiceId,
+        definition_id: Option<DefinitionId>,
+    ) -> Result<Vec<TrustedIssuer>>;
+}
+
+pub struct PgTrustedIssuerRepo {
+    pool: PgPool,
+}
+
+impl PgTrustedIssuerRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TrustedIssuerRepo for PgTrustedIssuerRepo {
+    async fn list(
+        &self,
+        service_id: Option<ServiceId>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TrustedIssuer>> {
+        let issuers = query_file_as!(
+            TrustedIssuer,
+            "sql/trusted_issuers/list.sql",
+            service_id.map(|id| id.0),
+            limit,
+            offset,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(issuers)
+    }
+
+    async fn create(&self, data: &CreateTrustedIssuer) -> Result<TrustedIssuer> {
+        let issuer = query_file_as!(
+            TrustedIssuer,
+            "sql/trusted_issuers/create.sql",
+            data.kind.clone() as IssuerKind,
+            data.value,
+            data.service_id.map(|id| id.0),
+            data.definition_id.map(|id| id.0),
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(issuer)
+    }
+
+    async fn get(&self, id: &TrustedIssuerId) -> Result<TrustedIssuer> {
+        let issuer = query_file_as!(TrustedIssuer, "sql/trusted_issuers/get.sql", &id.as_ref())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(issuer)
+    }
+
+    async fn destroy(&self, id: &TrustedIssuerId) -> Result<TrustedIssuer> {
+        let issuer = query_file_as!(
+            TrustedIssuer,
+            "sql/trusted_issuers/destroy.sql",
+            &id.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(issuer)
+    }
+
+    async fn applicable(
+        &self,
+        service_id: &ServiceId,
+        definition_id: Option<DefinitionId>,
+    ) -> Result<Vec<TrustedIssuer>> {
+        let issuers = query_file_as!(
+            TrustedIssuer,
+            "sql/trusted_issuers/applicable.sql",
+            service_id.as_ref(),
+            definition_id.map(|id| id.0),
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(issuers)
+    }
+}
@@ -28,7 +28,8 @@ impl UserRepo for PgUserRepo {
             User,
             "sql/users/create.sql",
             data.service_id,
-            data.id.as_ref()
+            data.id.as_ref(),
+            data.external_id
         )
         .fetch_one(&self.pool)
         .await?;
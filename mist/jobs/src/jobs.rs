@@ -1,3 +1,5 @@
+pub mod logouts;
 pub mod webhooks;
 
-pub use webhooks::Webhook;
+pub use logouts::BackChannelLogout;
+pub use webhooks::{JetStreamWebhookQueue, Webhook, WebhookQueue};
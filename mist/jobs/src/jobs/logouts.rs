@@ -0,0 +1,46 @@
+use async_nats::jetstream::{publish::PublishAck, Context};
+use bytes::Bytes;
+use mist_common::error::Error;
+use serde::{Deserialize, Serialize};
+
+pub const STREAM_NAME: &str = "jobs-logouts";
+pub const CONSUMER_NAME: &str = "jobs-logouts-consumer";
+
+/// A logout token to POST to a service's back-channel logout URI.
+///
+/// See: https://openid.net/specs/openid-connect-backchannel-1_0.html#BCRequest
+#[derive(Serialize, Deserialize)]
+pub struct BackChannelLogout {
+    pub(crate) url: String,
+    pub(crate) logout_token: String,
+}
+
+impl BackChannelLogout {
+    pub async fn publish(
+        jetstream: &Context,
+        url: &str,
+        logout_token: &str,
+    ) -> Result<PublishAck, Error> {
+        let published = jetstream
+            .publish(
+                format!("{STREAM_NAME}.backchannel"),
+                BackChannelLogout {
+                    url: url.into(),
+                    logout_token: logout_token.into(),
+                }
+                .try_into()?,
+            )
+            .await?
+            .await?;
+
+        Ok(published)
+    }
+}
+
+impl TryInto<Bytes> for BackChannelLogout {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Bytes, Self::Error> {
+        Ok(serde_json::to_string(&self)?.into())
+    }
+}
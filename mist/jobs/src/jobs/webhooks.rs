@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use async_nats::jetstream::{publish::PublishAck, Context};
+use async_trait::async_trait;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
 use mist_common::error::Error;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 pub const STREAM_NAME: &str = "jobs-webhooks";
@@ -37,6 +41,55 @@ impl Webhook {
 
         Ok(published)
     }
+
+    /// Call the webhook straight away instead of queueing it, and return what it
+    /// responds with.
+    pub async fn call<T: Serialize, R: DeserializeOwned>(
+        url: &str,
+        kind: &str,
+        data: &T,
+        timeout: Duration,
+    ) -> Result<R, Error> {
+        let payload = Payload::new(kind, data);
+
+        // Sent the same way as queued webhooks, so services can handle both alike.
+        let response = reqwest::Client::new()
+            .post(url)
+            .json(&serde_json::to_string(&payload)?)
+            .timeout(timeout)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Somewhere to queue webhooks, for callers that would rather not hold on to NATS
+/// themselves.
+#[async_trait]
+#[mockall::automock]
+pub trait WebhookQueue: Send + Sync {
+    async fn publish(&self, url: &str, kind: &str, data: &Value) -> Result<(), Error>;
+}
+
+pub struct JetStreamWebhookQueue {
+    jetstream: Context,
+}
+
+impl JetStreamWebhookQueue {
+    pub fn new(jetstream: Context) -> Self {
+        Self { jetstream }
+    }
+}
+
+#[async_trait]
+impl WebhookQueue for JetStreamWebhookQueue {
+    async fn publish(&self, url: &str, kind: &str, data: &Value) -> Result<(), Error> {
+        Webhook::publish(&self.jetstream, url, kind, data).await?;
+
+        Ok(())
+    }
 }
 
 impl TryInto<Bytes> for Webhook {
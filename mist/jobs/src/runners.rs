@@ -1 +1,2 @@
+pub mod logouts;
 pub mod webhooks;
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use async_nats::jetstream::{
+    consumer,
+    stream::{Config, RetentionPolicy},
+};
+use futures::StreamExt;
+use mist_common::{env::Environment, error::Result};
+use tokio::task::JoinHandle;
+
+use crate::jobs::logouts::{BackChannelLogout, CONSUMER_NAME, STREAM_NAME};
+
+pub async fn run(env: &Environment) -> Result<JoinHandle<()>> {
+    let client = async_nats::connect(env.nats_url.clone()).await?;
+    let jetstream = async_nats::jetstream::new(client);
+
+    let logout_stream = match jetstream.get_stream(&STREAM_NAME).await {
+        Ok(stream) => stream,
+        Err(_) => {
+            jetstream
+                .create_stream(Config {
+                    name: STREAM_NAME.into(),
+                    retention: RetentionPolicy::WorkQueue,
+                    subjects: vec![format!("{STREAM_NAME}.>")],
+                    ..Default::default()
+                })
+                .await?
+        }
+    };
+
+    let logout_consumer = match logout_stream.get_consumer(CONSUMER_NAME).await {
+        Ok(consumer) => consumer,
+        Err(_) => {
+            logout_stream
+                .create_consumer(consumer::pull::Config {
+                    durable_name: Some(CONSUMER_NAME.into()),
+                    ack_wait: Duration::from_secs(30),
+                    max_deliver: 5,
+                    ..Default::default()
+                })
+                .await?
+        }
+    };
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let Ok(mut messages) = logout_consumer.fetch().messages().await else {
+                tracing::error!("failed to fetch messages");
+
+                continue;
+            };
+
+            while let Some(Ok(message)) = messages.next().await {
+                match serde_json::from_slice::<BackChannelLogout>(&message.payload) {
+                    Ok(logout) => {
+                        // Services answer 200 once they've logged the user out, and
+                        // anything else is worth trying again.
+                        let sent = reqwest::Client::new()
+                            .post(&logout.url)
+                            .form(&[("logout_token", &logout.logout_token)])
+                            .send()
+                            .await
+                            .and_then(|response| response.error_for_status());
+
+                        if let Err(e) = sent {
+                            tracing::error!("failed to send logout token: {:?}", e);
+
+                            continue;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("failed to deserialize logout: {:?}", e);
+
+                        continue;
+                    }
+                }
+
+                if let Err(e) = message.ack().await {
+                    tracing::error!("failed to ack message: {:?}", e);
+                }
+            }
+        }
+    });
+
+    Ok(handle)
+}
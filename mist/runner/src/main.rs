@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use mist_common::{env::Environment, Result};
-use mist_jobs::runners::webhooks;
+use mist_jobs::runners::{logouts, webhooks};
 use sqlx::postgres::PgPoolOptions;
 use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
@@ -44,7 +44,9 @@ async fn main() -> Result<()> {
             listener,
             mist_authn::app::app(authn_env)
                 .await
-                .layer(TraceLayer::new_for_http()),
+                .layer(TraceLayer::new_for_http())
+                // Sessions note the IP they were started from.
+                .into_make_service_with_connect_info::<SocketAddr>(),
         )
         .await
         .unwrap();
@@ -54,6 +56,7 @@ async fn main() -> Result<()> {
         _ = api => tracing::info!("Api complete"),
         _ = authn => tracing::info!("Authn complete"),
         _ = webhooks::run(&env).await.unwrap() => tracing::info!("Webhooks job complete"),
+        _ = logouts::run(&env).await.unwrap() => tracing::info!("Logouts job complete"),
     }
 
     Ok(())